@@ -0,0 +1,139 @@
+//! Bluetooth Low Energy advertising data for HID devices.
+//!
+//! Hosts scanning for LE devices decide what to show from the advertising data before they
+//! connect: the HID Service UUID, the Appearance, which selects an icon, and the local name. The
+//! data here matches the HID Service of `hogp`, so dual-mode devices appear the same over both
+//! transports.
+
+use crate::hid::report;
+use crate::hid::usage::{consumer, digitizer, page};
+use crate::hid::{DescriptorKind, DeviceType, Peripheral};
+use crate::hogp::uuid16;
+use crate::Configuration;
+
+/// Types of advertising data structures, from Bluetooth Assigned Numbers.
+pub mod ad_type {
+    pub const FLAGS: u8 = 0x01;
+    pub const INCOMPLETE_UUID16_LIST: u8 = 0x02;
+    pub const COMPLETE_UUID16_LIST: u8 = 0x03;
+    pub const SHORTENED_LOCAL_NAME: u8 = 0x08;
+    pub const COMPLETE_LOCAL_NAME: u8 = 0x09;
+    pub const APPEARANCE: u8 = 0x19;
+}
+
+/// Bits of the Flags structure.
+pub mod flags {
+    pub const LE_LIMITED_DISCOVERABLE: u8 = 0x01;
+    pub const LE_GENERAL_DISCOVERABLE: u8 = 0x02;
+    pub const BR_EDR_NOT_SUPPORTED: u8 = 0x04;
+}
+
+/// Appearance values of input devices, from Bluetooth Assigned Numbers.
+pub mod appearance {
+    pub const REMOTE_CONTROL: u16 = 0x0180;
+    pub const HID: u16 = 0x03c0;
+    pub const KEYBOARD: u16 = 0x03c1;
+    pub const MOUSE: u16 = 0x03c2;
+    pub const JOYSTICK: u16 = 0x03c3;
+    pub const GAMEPAD: u16 = 0x03c4;
+    pub const DIGITIZER_TABLET: u16 = 0x03c5;
+    pub const CARD_READER: u16 = 0x03c6;
+    pub const DIGITAL_PEN: u16 = 0x03c7;
+    pub const BARCODE_SCANNER: u16 = 0x03c8;
+    pub const TOUCHPAD: u16 = 0x03c9;
+}
+
+/// Longest legacy advertising payload.
+pub const MAX_ADVERTISING_DATA_LEN: usize = 31;
+
+/// The advertising data of a HID device.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdvertisingData {
+    /// Bits of `flags`, or `None` to omit the Flags structure, as in scan responses.
+    pub flags: Option<u8>,
+    /// 16-bit service UUIDs, starting with the HID Service.
+    pub service_uuids: Vec<u16>,
+    pub appearance: u16,
+    pub local_name: Option<String>,
+}
+
+impl AdvertisingData {
+    /// Encode the data as advertising data structures, fitting in `MAX_ADVERTISING_DATA_LEN`
+    /// bytes. A local name too long to fit is shortened, and marked so.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        if let Some(flags) = self.flags {
+            out.extend_from_slice(&[2, ad_type::FLAGS, flags]);
+        }
+        if !self.service_uuids.is_empty() {
+            out.push(1 + 2 * self.service_uuids.len() as u8);
+            out.push(ad_type::COMPLETE_UUID16_LIST);
+            for uuid in &self.service_uuids {
+                out.extend_from_slice(&uuid.to_le_bytes());
+            }
+        }
+        let [low, high] = self.appearance.to_le_bytes();
+        out.extend_from_slice(&[3, ad_type::APPEARANCE, low, high]);
+
+        if let Some(name) = &self.local_name {
+            // The structure needs a length and a type byte besides the name.
+            let room = MAX_ADVERTISING_DATA_LEN.saturating_sub(out.len() + 2);
+            let (name, name_type) = if name.len() <= room {
+                (name.as_str(), ad_type::COMPLETE_LOCAL_NAME)
+            } else {
+                let end = (0..=room).rev().find(|&end| name.is_char_boundary(end)).unwrap_or(0);
+                (&name[..end], ad_type::SHORTENED_LOCAL_NAME)
+            };
+            if !name.is_empty() {
+                out.push(1 + name.len() as u8);
+                out.push(name_type);
+                out.extend_from_slice(name.as_bytes());
+            }
+        }
+        out
+    }
+}
+
+impl Configuration {
+    /// Return the Appearance of the device, from its device subclass, refined by the top-level
+    /// usages of its report descriptor for touchpads and remote controls.
+    pub fn appearance(&self) -> u16 {
+        let subclass = self.hid.device_subclass;
+        let usages = self.hid.class_descriptors.iter()
+            .find_map(|descriptor| match descriptor.kind() {
+                DescriptorKind::Report(data) => Some(report::top_level_usages(data)),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let has_usage = |usage| usages.contains(&usage);
+
+        match (subclass.peripheral, subclass.device_type) {
+            (Peripheral::Keyboard, _) => appearance::KEYBOARD,
+            (Peripheral::Pointing, _) if has_usage((page::DIGITIZER, digitizer::TOUCH_PAD)) =>
+                appearance::TOUCHPAD,
+            (Peripheral::Pointing, _) => appearance::MOUSE,
+            (Peripheral::Combo, _) => appearance::HID,
+            (_, DeviceType::Joystick) => appearance::JOYSTICK,
+            (_, DeviceType::Gamepad) => appearance::GAMEPAD,
+            (_, DeviceType::RemoteControl) => appearance::REMOTE_CONTROL,
+            (_, DeviceType::DigitizerTablet) => appearance::DIGITIZER_TABLET,
+            (_, DeviceType::CardReader) => appearance::CARD_READER,
+            (_, DeviceType::DigitalPen) => appearance::DIGITAL_PEN,
+            (_, DeviceType::HandheldScanner) => appearance::BARCODE_SCANNER,
+            _ if has_usage((page::CONSUMER, consumer::CONSUMER_CONTROL)) =>
+                appearance::REMOTE_CONTROL,
+            _ => appearance::HID,
+        }
+    }
+
+    /// Return the advertising data of the device: generally discoverable, with the HID Service,
+    /// its Appearance and its service name.
+    pub fn advertising_data(&self) -> AdvertisingData {
+        AdvertisingData {
+            flags: Some(flags::LE_GENERAL_DISCOVERABLE),
+            service_uuids: vec![uuid16::HID_SERVICE],
+            appearance: self.appearance(),
+            local_name: self.service_name.as_ref().map(|name| name.to_string()),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Conversion to and from the SDP settings of Android's HID device API.
+//!
+//! Android apps register a HID device with `BluetoothHidDeviceAppSdpSettings`, which holds only
+//! the service strings, the device subclass and one report descriptor. The Android stack writes
+//! every other attribute of the record itself, so configurations which set them cannot be
+//! reproduced on Android; `android_unsupported_attributes` names those a configuration sets.
+
+use std::borrow::Cow;
+
+use crate::attribute::*;
+use crate::hid::{self, CountryCode, DescriptorKind};
+use crate::{encoding, language, Configuration, ProfileVersion};
+
+/// The fields of `BluetoothHidDeviceAppSdpSettings`, in the order of its constructor.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AndroidSdpSettings {
+    pub name: String,
+    pub description: String,
+    pub provider: String,
+    /// Device subclass, as the byte written in the record.
+    pub subclass: u8,
+    /// Report descriptor.
+    pub descriptors: Vec<u8>,
+}
+
+impl AndroidSdpSettings {
+    /// Convert the settings into a configuration, with the attributes Android does not let apps
+    /// set taking the values of `Configuration::minimal`. Empty strings are left unset.
+    pub fn to_configuration(&self) -> Configuration {
+        let subclass = hid::DeviceSubclass::from(self.subclass);
+        let hid = hid::Configuration::new(subclass)
+            .with_virtual_cable(true)
+            .with_reconnect_initiate(true)
+            .with_class_descriptor(hid::ClassDescriptor::report(self.descriptors.clone()))
+            .with_boot_device(subclass.is_keyboard() || subclass.is_pointing());
+        let mut config =
+            Configuration::new(language::ENGLISH, encoding::UTF_8, ProfileVersion::V1_1, hid);
+        let non_empty = |s: &str| (!s.is_empty()).then(|| Cow::Owned(s.to_string()));
+        config.service_name = non_empty(&self.name);
+        config.service_description = non_empty(&self.description);
+        config.provider_name = non_empty(&self.provider);
+        config
+    }
+}
+
+impl From<AndroidSdpSettings> for Configuration {
+    fn from(settings: AndroidSdpSettings) -> Configuration {
+        settings.to_configuration()
+    }
+}
+
+impl Configuration {
+    /// Return the settings with which an Android app registers this device. Unset strings are
+    /// empty, and only the first report descriptor is kept.
+    ///
+    /// Android cannot express every attribute; see `android_unsupported_attributes`.
+    pub fn to_android_sdp_settings(&self) -> AndroidSdpSettings {
+        let descriptors = self.hid.class_descriptors.iter()
+            .find_map(|descriptor| match descriptor.kind() {
+                DescriptorKind::Report(data) => Some(data.to_vec()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        AndroidSdpSettings {
+            name: self.service_name.as_deref().unwrap_or_default().to_string(),
+            description: self.service_description.as_deref().unwrap_or_default().to_string(),
+            provider: self.provider_name.as_deref().unwrap_or_default().to_string(),
+            subclass: self.hid.device_subclass.into(),
+            descriptors,
+        }
+    }
+
+    /// Return the names of the attributes this configuration sets which Android apps cannot
+    /// set, so the record Android registers differs from this configuration in each of them.
+    pub fn android_unsupported_attributes(&self) -> Vec<&'static str> {
+        let hid = &self.hid;
+        let report_descriptors = hid.class_descriptors.iter()
+            .filter(|d| d.descriptor_type() == hid::descriptor_type::REPORT)
+            .count();
+        let checks = [
+            (HidDescriptorList::NAME,
+                hid.class_descriptors.len() > 1 || report_descriptors != 1),
+            (HidCountryCode::NAME, hid.country_code != CountryCode::NotLocalized),
+            (HidLangBaseAttributeIdList::NAME, !self.additional_language_bases().is_empty()),
+            (HidBatteryPower::NAME, hid.battery_power.is_some()),
+            (HidRemoteWake::NAME, hid.remote_wake.is_some()),
+            (HidSupervisionTimeout::NAME, hid.supervision_timeout.is_some()),
+            (HidNormallyConnectable::NAME, hid.normally_connectable.is_some()),
+            (HidBootDevice::NAME, hid.boot_device),
+            (HidSsrHostMaxLatency::NAME, hid.ssr_host_max_latency.is_some()),
+            (HidSsrHostMinTimeout::NAME, hid.ssr_host_min_timeout.is_some()),
+            (DocumentationUrl::NAME, self.documentation_url.is_some()),
+            (ClientExecutableUrl::NAME, self.client_executable_url.is_some()),
+            (IconUrl::NAME, self.icon_url.is_some()),
+            (ProtocolDescriptorList::NAME, self.transport.control_psm.is_some()),
+            (AdditionalProtocolDescriptorLists::NAME, self.transport.interrupt_psm.is_some()),
+        ];
+        checks.into_iter()
+            .filter(|&(_, unsupported)| unsupported)
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
@@ -0,0 +1,94 @@
+//! Memory-mapped reading of record archives.
+//!
+//! Archives of captured records can hold thousands of records. Rather than reading a whole
+//! archive into memory, the file is mapped and records are decoded one at a time straight from
+//! the mapping, so peak memory stays close to the size of a single record.
+//!
+//! An archive is a file of binary SDP records, each an attribute list data element, stored back
+//! to back.
+
+use memmap2::Mmap;
+use sdp_xml_reader::parse_sdp_xml;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use crate::{Error, PartialConfiguration};
+use crate::data_element::decode_record_prefix;
+use crate::from_sdp::Result;
+
+/// A memory-mapped archive of binary SDP records.
+pub struct Archive {
+    map: Mmap,
+}
+
+impl Archive {
+    /// Map the archive at the given path.
+    ///
+    /// The file must not be modified while the archive is open.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // Safety: the mapping is read-only, and callers are required not to modify the file
+        // while it is mapped.
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(Archive { map })
+    }
+
+    /// Return the raw contents of the archive.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Iterate over the records in the archive.
+    pub fn records(&self) -> Records<'_> {
+        Records { data: &self.map, offset: 0 }
+    }
+}
+
+/// Iterator over the records in an archive.
+///
+/// Iteration stops after the first record which fails to decode, since the position of the next
+/// record cannot be known.
+pub struct Records<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Records<'a> {
+    /// Return the offset in the archive of the next record.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Result<PartialConfiguration>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = &self.data[self.offset..];
+        if remaining.is_empty() {
+            return None;
+        }
+        match decode_record_prefix(remaining) {
+            Ok((record, size)) => {
+                self.offset += size;
+                Some(PartialConfiguration::from_sdp_tag(record))
+            },
+            Err(e) => {
+                self.offset = self.data.len();
+                Some(Err(e))
+            },
+        }
+    }
+}
+
+/// Parse the SDP XML file at the given path, reading it through a memory mapping instead of an
+/// intermediate buffer.
+pub fn read_sdp_xml_path(path: impl AsRef<Path>) -> io::Result<Result<PartialConfiguration>> {
+    let file = File::open(path)?;
+    // Safety: see Archive::open.
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(parse_sdp_xml(&map)
+        .map_err(Error::XmlParseError)
+        .and_then(PartialConfiguration::from_sdp_tag))
+}
@@ -0,0 +1,539 @@
+//! Typed SDP attributes.
+//!
+//! Every attribute of the HID record is a separate type implementing `SdpAttribute`, which ties
+//! together the attribute's ID, its Rust representation and its conversions to and from tags.
+//! The serializer and parser are both built on these types, so adding an attribute only requires
+//! a new type here and a field in the configuration.
+
+use sdp_xml::Tag;
+use hid_device_id::bluetooth::{attribute_id, protocol};
+use uuid::Uuid;
+
+use crate::from_sdp::{
+    Error,
+    Result,
+    expect_boolean,
+    expect_len,
+    expect_sequence,
+    expect_text,
+    expect_uint8,
+    expect_uint16,
+    expect_uint32,
+    expect_url,
+    expect_any_uuid,
+    try_initialize,
+};
+use crate::device_id;
+use crate::hid::{ClassDescriptor, DescriptorForm, LanguageBase};
+use crate::ids::ServiceClass;
+use crate::to_sdp::DescriptorEncoding;
+
+/// An SDP attribute with a fixed ID.
+pub trait SdpAttribute: Sized {
+    /// ID of the attribute.
+    const ATTRIBUTE_ID: u16;
+    /// Name of the attribute, as used in the specifications.
+    const NAME: &'static str;
+
+    /// Create the tag holding the attribute's value.
+    fn to_tag(&self) -> Tag;
+
+    /// Create the tag holding the attribute's value, moving data into it where possible.
+    fn into_tag(self) -> Tag {
+        self.to_tag()
+    }
+
+    /// Read the attribute's value from a tag.
+    fn from_tag(tag: Tag) -> Result<Self>;
+
+    /// Create the attribute tag, containing both the ID and the value.
+    fn into_attribute(self) -> Tag {
+        Tag::attribute(Self::ATTRIBUTE_ID, self.into_tag())
+    }
+}
+
+/// Define an attribute holding a single value of a tag type.
+macro_rules! value_attribute {
+    ($(#[$meta:meta])* $name:ident, $id:expr, $display:literal, $type:ty, $variant:ident, $expect:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug)]
+        pub struct $name(pub $type);
+
+        impl SdpAttribute for $name {
+            const ATTRIBUTE_ID: u16 = $id;
+            const NAME: &'static str = $display;
+
+            fn to_tag(&self) -> Tag {
+                Tag::$variant(self.0.clone())
+            }
+
+            fn into_tag(self) -> Tag {
+                Tag::$variant(self.0)
+            }
+
+            fn from_tag(tag: Tag) -> Result<Self> {
+                $expect(Self::ATTRIBUTE_ID, tag).map(Self)
+            }
+        }
+    };
+}
+
+/// Read a sequence of UUIDs.
+fn uuid_list(attribute: u16, tag: Tag) -> Result<Vec<Uuid>> {
+    expect_sequence(attribute, tag)?
+        .into_iter()
+        .map(|tag| expect_any_uuid(attribute, tag))
+        .collect()
+}
+
+/// Create a sequence of UUIDs.
+fn uuid_list_tag(uuids: &[Uuid]) -> Tag {
+    Tag::Sequence(uuids.iter().map(|uuid| Tag::Uuid(*uuid)).collect())
+}
+
+/// Read the L2CAP PSM from a protocol descriptor list, of the form
+/// `((L2CAP, psm), (HIDP))`.
+fn l2cap_psm(attribute: u16, tag: Tag) -> Result<u16> {
+    let mut protocol_descriptors = expect_sequence(attribute, tag)?;
+    if protocol_descriptors.is_empty() {
+        return Err(Error::UnexpectedSequenceLen { attribute, expected: 2, actual: 0 });
+    }
+    let mut l2cap = expect_sequence(attribute, protocol_descriptors.remove(0))?;
+    expect_len(attribute, &l2cap, 2)?;
+    expect_uint16(attribute, l2cap.remove(1))
+}
+
+/// Create a protocol descriptor list for HIDP over L2CAP on the given PSM.
+fn l2cap_psm_tag(psm: u16) -> Tag {
+    Tag::from((
+        // Protocol Descriptor 0
+        (
+            protocol::L2CAP,
+            psm,
+        ),
+        // Protocol Descriptor 1
+        (
+            protocol::HID_PROTOCOL,
+        ),
+    ))
+}
+
+/// Return true if the text can be written in an XML attribute value. XML 1.0 forbids most
+/// control characters, even when escaped.
+pub(crate) fn is_xml_safe(text: &str) -> bool {
+    text.chars().all(|c| c >= ' ' || c == '\t' || c == '\n' || c == '\r')
+}
+
+/// Create the tag carrying a class descriptor's data, with the given encoding.
+///
+/// Report descriptors are arbitrary bytes, so data that is not valid UTF-8 or contains control
+/// characters is always written as raw text, whatever the requested encoding. The raw text tag is
+/// created directly, so that the data is never converted to a string on the way. The data is
+/// moved into the tag, not copied.
+pub(crate) fn descriptor_data_tag(data: Vec<u8>, encoding: DescriptorEncoding) -> Tag {
+    match encoding {
+        DescriptorEncoding::Raw => match String::from_utf8(data) {
+            Ok(text) if is_xml_safe(&text) => Tag::Text(text),
+            Ok(text) => Tag::RawText(text.into_bytes()),
+            Err(e) => Tag::RawText(e.into_bytes()),
+        },
+        DescriptorEncoding::Hex => Tag::RawText(data),
+    }
+}
+
+value_attribute!(
+    /// Handle of the service record.
+    ServiceRecordHandle, attribute_id::SERVICE_RECORD_HANDLE, "Service Record Handle",
+    u32, UInt32, expect_uint32);
+
+value_attribute!(
+    /// Value which changes whenever the record changes.
+    ServiceRecordState, attribute_id::SERVICE_RECORD_STATE, "Service Record State",
+    u32, UInt32, expect_uint32);
+
+value_attribute!(
+    /// Number of seconds for which the record is expected to remain valid.
+    ServiceInfoTimeToLive, attribute_id::SERVICE_INFO_TIME_TO_LIVE, "Service Info Time To Live",
+    u32, UInt32, expect_uint32);
+
+value_attribute!(
+    /// Relative ability of the service to accept additional clients.
+    ServiceAvailability, attribute_id::SERVICE_AVAILABILITY, "Service Availability",
+    u8, UInt8, expect_uint8);
+
+value_attribute!(
+    /// Name of the service, in the primary language.
+    ServiceName, attribute_id::SERVICE_NAME, "Service Name",
+    String, Text, expect_text);
+
+value_attribute!(
+    /// Description of the service, in the primary language.
+    ServiceDescription, attribute_id::SERVICE_DESCRIPTION, "Service Description",
+    String, Text, expect_text);
+
+value_attribute!(
+    /// Name of the service provider, in the primary language.
+    ProviderName, attribute_id::PROVIDER_NAME, "Provider Name",
+    String, Text, expect_text);
+
+value_attribute!(
+    /// URL of documentation for the service.
+    DocumentationUrl, attribute_id::DOCUMENTATION_URL, "Documentation URL",
+    String, Url, expect_url);
+
+value_attribute!(
+    /// URL of a client application which may be used to access the service.
+    ClientExecutableUrl, attribute_id::CLIENT_EXECUTABLE_URL, "Client Executable URL",
+    String, Url, expect_url);
+
+value_attribute!(
+    /// URL of an icon representing the service.
+    IconUrl, attribute_id::ICON_URL, "Icon URL",
+    String, Url, expect_url);
+
+value_attribute!(
+    /// Version of the HID parser, always 0x0111.
+    HidParserVersion, attribute_id::hid::HID_PARSER_VERSION, "HID Parser Version",
+    u16, UInt16, expect_uint16);
+
+value_attribute!(
+    /// Deprecated version of the HID profile.
+    HidProfileVersion, attribute_id::hid::HID_PROFILE_VERSION, "HID Profile Version",
+    u16, UInt16, expect_uint16);
+
+value_attribute!(
+    /// Device subclass, the minor device class.
+    HidDeviceSubclass, attribute_id::hid::HID_DEVICE_SUBCLASS, "HID Device Subclass",
+    u8, UInt8, expect_uint8);
+
+value_attribute!(
+    /// Country code of localized hardware.
+    HidCountryCode, attribute_id::hid::HID_COUNTRY_CODE, "HID Country Code",
+    u8, UInt8, expect_uint8);
+
+value_attribute!(
+    /// Whether the device supports virtual cables.
+    HidVirtualCable, attribute_id::hid::HID_VIRTUAL_CABLE, "HID Virtual Cable",
+    bool, Boolean, expect_boolean);
+
+value_attribute!(
+    /// Whether the device initiates reconnection.
+    HidReconnectInitiate, attribute_id::hid::HID_RECONNECT_INITIATE, "HID Reconnect Initiate",
+    bool, Boolean, expect_boolean);
+
+value_attribute!(
+    /// Whether the device is battery-powered.
+    HidBatteryPower, attribute_id::hid::HID_BATTERY_POWER, "HID Battery Power",
+    bool, Boolean, expect_boolean);
+
+value_attribute!(
+    /// Whether the device may wake the host.
+    HidRemoteWake, attribute_id::hid::HID_REMOTE_WAKE, "HID Remote Wake",
+    bool, Boolean, expect_boolean);
+
+value_attribute!(
+    /// Supervision timeout, in 625 microsecond baseband slots.
+    HidSupervisionTimeout, attribute_id::hid::HID_SUPERVISION_TIMEOUT, "HID Supervision Timeout",
+    u16, UInt16, expect_uint16);
+
+value_attribute!(
+    /// Whether the device is always in page scan mode.
+    HidNormallyConnectable, attribute_id::hid::HID_NORMALLY_CONNECTABLE, "HID Normally Connectable",
+    bool, Boolean, expect_boolean);
+
+value_attribute!(
+    /// Whether the device supports the boot protocol.
+    HidBootDevice, attribute_id::hid::HID_BOOT_DEVICE, "HID Boot Device",
+    bool, Boolean, expect_boolean);
+
+value_attribute!(
+    /// Maximum sniff subrating latency, in 625 microsecond baseband slots.
+    HidSsrHostMaxLatency, attribute_id::hid::HID_SSR_HOST_MAX_LATENCY, "HID SSR Host Max Latency",
+    u16, UInt16, expect_uint16);
+
+value_attribute!(
+    /// Minimum sniff subrating timeout, in 625 microsecond baseband slots.
+    HidSsrHostMinTimeout, attribute_id::hid::HID_SSR_HOST_MIN_TIMEOUT, "HID SSR Host Min Timeout",
+    u16, UInt16, expect_uint16);
+
+/// Service classes of the record.
+#[derive(Clone, Debug)]
+pub struct ServiceClassIdList(pub Vec<Uuid>);
+
+impl SdpAttribute for ServiceClassIdList {
+    const ATTRIBUTE_ID: u16 = attribute_id::SERVICE_CLASS_ID_LIST;
+    const NAME: &'static str = "Service Class ID List";
+
+    fn to_tag(&self) -> Tag {
+        uuid_list_tag(&self.0)
+    }
+
+    fn from_tag(tag: Tag) -> Result<Self> {
+        uuid_list(Self::ATTRIBUTE_ID, tag).map(Self)
+    }
+}
+
+/// Browse groups in which the service is listed.
+#[derive(Clone, Debug)]
+pub struct BrowseGroupList(pub Vec<Uuid>);
+
+impl BrowseGroupList {
+    /// List containing only the public browse group.
+    pub fn public() -> Self {
+        BrowseGroupList(vec![Uuid::from(ServiceClass::PublicBrowseGroup)])
+    }
+}
+
+impl SdpAttribute for BrowseGroupList {
+    const ATTRIBUTE_ID: u16 = attribute_id::BROWSE_GROUP_LIST;
+    const NAME: &'static str = "Browse Group List";
+
+    fn to_tag(&self) -> Tag {
+        uuid_list_tag(&self.0)
+    }
+
+    fn from_tag(tag: Tag) -> Result<Self> {
+        uuid_list(Self::ATTRIBUTE_ID, tag).map(Self)
+    }
+}
+
+/// Protocol descriptor list, giving the PSM of the HID control channel.
+#[derive(Clone, Debug)]
+pub struct ProtocolDescriptorList {
+    pub psm: u16,
+}
+
+impl SdpAttribute for ProtocolDescriptorList {
+    const ATTRIBUTE_ID: u16 = attribute_id::PROTOCOL_DESCRIPTOR_LIST;
+    const NAME: &'static str = "Protocol Descriptor List";
+
+    fn to_tag(&self) -> Tag {
+        l2cap_psm_tag(self.psm)
+    }
+
+    fn from_tag(tag: Tag) -> Result<Self> {
+        l2cap_psm(Self::ATTRIBUTE_ID, tag).map(|psm| ProtocolDescriptorList { psm })
+    }
+}
+
+/// Additional protocol descriptor lists, giving the PSM of the HID interrupt channel.
+#[derive(Clone, Debug)]
+pub struct AdditionalProtocolDescriptorLists {
+    pub psm: u16,
+}
+
+impl SdpAttribute for AdditionalProtocolDescriptorLists {
+    const ATTRIBUTE_ID: u16 = attribute_id::ADDITIONAL_PROTOCOL_DESCRIPTOR_LISTS;
+    const NAME: &'static str = "Additional Protocol Descriptor Lists";
+
+    fn to_tag(&self) -> Tag {
+        Tag::Sequence(vec![l2cap_psm_tag(self.psm)])
+    }
+
+    fn from_tag(tag: Tag) -> Result<Self> {
+        let mut lists = expect_sequence(Self::ATTRIBUTE_ID, tag)?;
+        expect_len(Self::ATTRIBUTE_ID, &lists, 1)?;
+        l2cap_psm(Self::ATTRIBUTE_ID, lists.remove(0))
+            .map(|psm| AdditionalProtocolDescriptorLists { psm })
+    }
+}
+
+/// Primary language of the service strings.
+/// Described in 5.1.8 of Bluetooth Core.
+#[derive(Clone, Debug)]
+pub struct LanguageBaseAttributeIdList {
+    /// ISO 639:1988 (E/F) language code.
+    pub language: u16,
+    /// MIBEnum encoding from IANA's database.
+    pub encoding: u16,
+    /// Base attribute ID of the strings. Must be 0x0100 for the primary language.
+    pub base: u16,
+}
+
+impl SdpAttribute for LanguageBaseAttributeIdList {
+    const ATTRIBUTE_ID: u16 = attribute_id::LANGUAGE_BASE_ATTRIBUTE_ID_LIST;
+    const NAME: &'static str = "Language Base Attribute ID List";
+
+    fn to_tag(&self) -> Tag {
+        Tag::from((self.language, self.encoding, self.base))
+    }
+
+    fn from_tag(tag: Tag) -> Result<Self> {
+        let id = Self::ATTRIBUTE_ID;
+        let mut list = expect_sequence(id, tag)?;
+        expect_len(id, &list, 3)?;
+        let language = expect_uint16(id, list.remove(0))?;
+        let encoding = expect_uint16(id, list.remove(0))?;
+        let base = expect_uint16(id, list.remove(0))?;
+        Ok(LanguageBaseAttributeIdList { language, encoding, base })
+    }
+}
+
+/// Profiles supported by the service, with their versions.
+#[derive(Clone, Debug)]
+pub struct BluetoothProfileDescriptorList(pub Vec<(Uuid, u16)>);
+
+impl SdpAttribute for BluetoothProfileDescriptorList {
+    const ATTRIBUTE_ID: u16 = attribute_id::BLUETOOTH_PROFILE_DESCRIPTOR_LIST;
+    const NAME: &'static str = "Profile Descriptor List";
+
+    fn to_tag(&self) -> Tag {
+        Tag::Sequence(self.0.iter()
+            .map(|(uuid, version)| Tag::Sequence(vec![Tag::Uuid(*uuid), Tag::UInt16(*version)]))
+            .collect())
+    }
+
+    fn from_tag(tag: Tag) -> Result<Self> {
+        // A sequence of profile descriptors, each a sequence of the profile UUID and version,
+        // such as 0x0101.
+        let id = Self::ATTRIBUTE_ID;
+        let profiles = expect_sequence(id, tag)?;
+        let mut list = Vec::new();
+        for profile in profiles {
+            let mut profile = expect_sequence(id, profile)?;
+            expect_len(id, &profile, 2)?;
+            let uuid = expect_any_uuid(id, profile.remove(0))?;
+            let version = expect_uint16(id, profile.remove(0))?;
+            list.push((uuid, version));
+        }
+        Ok(BluetoothProfileDescriptorList(list))
+    }
+}
+
+/// Class descriptors of the device, which will likely contain a HID report descriptor.
+#[derive(Clone, Debug)]
+pub struct HidDescriptorList(pub Vec<ClassDescriptor>);
+
+impl HidDescriptorList {
+    /// Create the tag, writing every descriptor with the given encoding, or with its own form
+    /// if none is given.
+    pub fn into_tag_with_encoding(self, encoding: Option<DescriptorEncoding>) -> Tag {
+        Tag::Sequence(self.0.into_iter()
+            .map(|ClassDescriptor(t, data, form)| {
+                let encoding = encoding.unwrap_or(DescriptorEncoding::from(form));
+                Tag::Sequence(vec![Tag::UInt8(t), descriptor_data_tag(data.to_vec(), encoding)])
+            })
+            .collect())
+    }
+}
+
+impl SdpAttribute for HidDescriptorList {
+    const ATTRIBUTE_ID: u16 = attribute_id::hid::HID_DESCRIPTOR_LIST;
+    const NAME: &'static str = "HID Descriptor List";
+
+    fn to_tag(&self) -> Tag {
+        self.clone().into_tag()
+    }
+
+    fn into_tag(self) -> Tag {
+        self.into_tag_with_encoding(None)
+    }
+
+    fn from_tag(tag: Tag) -> Result<Self> {
+        let id = Self::ATTRIBUTE_ID;
+        let maybe_descriptors = expect_sequence(id, tag)?;
+        let mut descriptors = Vec::new();
+        for maybe_descriptor in maybe_descriptors {
+            // Each descriptor is a sequence containing an ID (u8) and text.
+            let descriptor = expect_sequence(id, maybe_descriptor)?;
+            let mut descriptor_type = None;
+            let mut descriptor_value = None;
+            let mut descriptor_form = DescriptorForm::RawText;
+            // Read each element in the descriptor, searching for an ID and descriptor
+            // text.
+            for element in descriptor {
+                match element {
+                    Tag::UInt8(v) => {
+                        try_initialize(id, &mut descriptor_type, v)
+                            .map_err(|_| Error::DuplicateDescriptorId)?;
+                    },
+                    Tag::Text(v) => {
+                        try_initialize(id, &mut descriptor_value, v.into_bytes())
+                            .map_err(|_| Error::DuplicateDescriptorText)?;
+                        descriptor_form = DescriptorForm::Text;
+                    },
+                    Tag::RawText(v) => {
+                        try_initialize(id, &mut descriptor_value, v)
+                            .map_err(|_| Error::DuplicateDescriptorText)?;
+                    },
+                    _ => {
+                        return Err(Error::UnexpectedTag(element));
+                    },
+                };
+            }
+            // Convert the optional descriptor type and value into a concrete class
+            // descriptor.
+            let class_descriptor = match (descriptor_type, descriptor_value) {
+                (Some(t), Some(v)) => ClassDescriptor(t, v.into(), descriptor_form),
+                (None, _) => {
+                    return Err(Error::MissingRecord("descriptor id"));
+                },
+                (Some(_), None) => {
+                    return Err(Error::MissingRecord("descriptor value"));
+                },
+            };
+            descriptors.push(class_descriptor);
+        }
+        Ok(HidDescriptorList(descriptors))
+    }
+}
+
+/// Language IDs and string base attribute IDs of each language, the first being the primary
+/// language. Described in 5.3.4.8 of HID.
+#[derive(Clone, Debug)]
+pub struct HidLangBaseAttributeIdList(pub Vec<LanguageBase>);
+
+impl SdpAttribute for HidLangBaseAttributeIdList {
+    const ATTRIBUTE_ID: u16 = attribute_id::hid::HID_LANG_BASE_ATTRIBUTE;
+    const NAME: &'static str = "HID Language Base Attribute ID List";
+
+    fn to_tag(&self) -> Tag {
+        Tag::Sequence(self.0.iter()
+            .map(|l| Tag::Sequence(vec![Tag::UInt16(l.language), Tag::UInt16(l.base)]))
+            .collect())
+    }
+
+    fn from_tag(tag: Tag) -> Result<Self> {
+        let id = Self::ATTRIBUTE_ID;
+        let lang_base_id_list = expect_sequence(id, tag)?;
+        let mut list = Vec::new();
+        for maybe_lang_base_id in lang_base_id_list {
+            let mut lang_base_id = expect_sequence(id, maybe_lang_base_id)?;
+            expect_len(id, &lang_base_id, 2)?;
+            let language = expect_uint16(id, lang_base_id.remove(0))?;
+            let base = expect_uint16(id, lang_base_id.remove(0))?;
+            list.push(LanguageBase { language, base });
+        }
+        Ok(HidLangBaseAttributeIdList(list))
+    }
+}
+
+value_attribute!(
+    /// Version of the Device ID specification implemented, such as 0x0103 for 1.3.
+    DeviceIdSpecificationId, device_id::attribute_id::SPECIFICATION_ID, "Specification ID",
+    u16, UInt16, expect_uint16);
+
+value_attribute!(
+    /// Vendor ID, assigned by the organization given by the vendor ID source.
+    DeviceIdVendorId, device_id::attribute_id::VENDOR_ID, "Vendor ID",
+    u16, UInt16, expect_uint16);
+
+value_attribute!(
+    /// Product ID, assigned by the vendor.
+    DeviceIdProductId, device_id::attribute_id::PRODUCT_ID, "Product ID",
+    u16, UInt16, expect_uint16);
+
+value_attribute!(
+    /// Version of the product, as 0xJJMN for version JJ.M.N.
+    DeviceIdVersion, device_id::attribute_id::VERSION, "Version",
+    u16, UInt16, expect_uint16);
+
+value_attribute!(
+    /// Whether this is the primary Device ID record of the device.
+    DeviceIdPrimaryRecord, device_id::attribute_id::PRIMARY_RECORD, "Primary Record",
+    bool, Boolean, expect_boolean);
+
+value_attribute!(
+    /// Organization which assigned the vendor ID.
+    DeviceIdVendorIdSource, device_id::attribute_id::VENDOR_ID_SOURCE, "Vendor ID Source",
+    u16, UInt16, expect_uint16);
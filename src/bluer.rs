@@ -0,0 +1,30 @@
+//! Conveniences for users of bluer.
+//!
+//! The core of this crate only depends on the `uuid` crate. With the `bluer` feature, the
+//! identifiers in configurations can be looked up in bluer's tables of assigned numbers, and the
+//! `profile` and `watch` features register and follow records through bluer.
+
+use ::bluer::id::{Manufacturer, ServiceClass};
+
+use crate::device_id::vendor_id_source;
+use crate::Configuration;
+
+impl Configuration {
+    /// Return the service classes of the record known to bluer, starting with HID. Additional
+    /// service classes bluer has no name for are skipped.
+    pub fn service_classes(&self) -> Vec<ServiceClass> {
+        let mut classes = vec![ServiceClass::Hid];
+        classes.extend(self.additional_service_classes.iter()
+            .filter_map(|uuid| ServiceClass::try_from(*uuid).ok()));
+        classes
+    }
+
+    /// Return the manufacturer named by the vendor ID, if the Bluetooth SIG assigned it and
+    /// bluer knows it.
+    pub fn manufacturer(&self) -> Option<Manufacturer> {
+        if self.vendor_id_source != Some(vendor_id_source::BLUETOOTH) {
+            return None;
+        }
+        Manufacturer::try_from(self.vendor_id?).ok()
+    }
+}
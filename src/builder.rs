@@ -0,0 +1,289 @@
+//! Constructors and builder methods for configurations.
+//!
+//! The configuration structs are `#[non_exhaustive]`, so that attributes can be added without
+//! breaking users, and cannot be created with struct literals outside this crate. Instead, they
+//! are created with a constructor and adjusted with the `with_*` methods here. Their fields
+//! remain public, so they may still be read and modified directly.
+
+use sdp_xml::Tag;
+use std::borrow::Cow;
+use uuid::Uuid;
+
+use crate::qos::FlowSpec;
+use crate::{
+    Configuration,
+    DeviceIdConfiguration,
+    LanguageCode,
+    ProfileVersion,
+    ServiceStrings,
+    TransportConfiguration,
+    hid,
+};
+
+impl ServiceStrings {
+    /// Create service strings with only a service name.
+    pub fn new(service_name: impl Into<Cow<'static, str>>) -> Self {
+        ServiceStrings { service_name: Some(service_name.into()), ..Self::default() }
+    }
+
+    pub fn with_service_description(mut self, description: impl Into<Cow<'static, str>>) -> Self {
+        self.service_description = Some(description.into());
+        self
+    }
+
+    pub fn with_provider_name(mut self, provider_name: impl Into<Cow<'static, str>>) -> Self {
+        self.provider_name = Some(provider_name.into());
+        self
+    }
+}
+
+impl hid::Configuration {
+    /// Create a HID configuration for a device of the given subclass, with no descriptors and
+    /// every optional attribute unset.
+    pub fn new(device_subclass: hid::DeviceSubclass) -> Self {
+        hid::Configuration { device_subclass, ..Self::default() }
+    }
+
+    pub fn with_country_code(mut self, country_code: hid::CountryCode) -> Self {
+        self.country_code = country_code;
+        self
+    }
+
+    pub fn with_virtual_cable(mut self, virtual_cable: bool) -> Self {
+        self.virtual_cable = virtual_cable;
+        self
+    }
+
+    pub fn with_reconnect_initiate(mut self, reconnect_initiate: bool) -> Self {
+        self.reconnect_initiate = reconnect_initiate;
+        self
+    }
+
+    /// Add a class descriptor after the existing descriptors.
+    pub fn with_class_descriptor(mut self, class_descriptor: hid::ClassDescriptor) -> Self {
+        self.class_descriptors.push(class_descriptor);
+        self
+    }
+
+    /// Add an additional language after the existing languages.
+    pub fn with_additional_language(mut self, language_base: hid::LanguageBase) -> Self {
+        self.additional_languages.push(language_base);
+        self
+    }
+
+    pub fn with_battery_power(mut self, battery_power: bool) -> Self {
+        self.battery_power = Some(battery_power);
+        self
+    }
+
+    pub fn with_remote_wake(mut self, remote_wake: bool) -> Self {
+        self.remote_wake = Some(remote_wake);
+        self
+    }
+
+    pub fn with_supervision_timeout(mut self, supervision_timeout: hid::SupervisionTimeout) -> Self {
+        self.supervision_timeout = Some(supervision_timeout);
+        self
+    }
+
+    pub fn with_normally_connectable(mut self, normally_connectable: bool) -> Self {
+        self.normally_connectable = Some(normally_connectable);
+        self
+    }
+
+    pub fn with_boot_device(mut self, boot_device: bool) -> Self {
+        self.boot_device = boot_device;
+        self
+    }
+
+    /// If this is a boot device, also enable virtual cables and reconnection, which boot
+    /// devices require, HID 5.3.4.12. Other devices are unchanged.
+    pub fn with_boot_requirements(mut self) -> Self {
+        if self.boot_device {
+            self.virtual_cable = true;
+            self.reconnect_initiate = true;
+        }
+        self
+    }
+
+    pub fn with_ssr_host_max_latency(mut self, latency: hid::SsrHostMaxLatency) -> Self {
+        self.ssr_host_max_latency = Some(latency);
+        self
+    }
+
+    pub fn with_ssr_host_min_timeout(mut self, timeout: hid::SsrHostMinTimeout) -> Self {
+        self.ssr_host_min_timeout = Some(timeout);
+        self
+    }
+}
+
+impl TransportConfiguration {
+    pub fn with_control_psm(mut self, psm: u16) -> Self {
+        self.control_psm = Some(psm);
+        self
+    }
+
+    pub fn with_interrupt_psm(mut self, psm: u16) -> Self {
+        self.interrupt_psm = Some(psm);
+        self
+    }
+
+    /// Set the browse groups. An empty list makes the service unbrowsable.
+    pub fn with_browse_groups(mut self, browse_groups: Vec<Uuid>) -> Self {
+        self.browse_groups = Some(browse_groups);
+        self
+    }
+
+    pub fn with_control_qos(mut self, flow_spec: FlowSpec) -> Self {
+        self.control_qos = Some(flow_spec);
+        self
+    }
+
+    pub fn with_interrupt_qos(mut self, flow_spec: FlowSpec) -> Self {
+        self.interrupt_qos = Some(flow_spec);
+        self
+    }
+}
+
+impl Configuration {
+    /// Create a configuration from the given HID configuration, in the given language and
+    /// encoding, with every optional attribute unset.
+    pub fn new(
+        primary_language: LanguageCode,
+        encoding: u16,
+        version: ProfileVersion,
+        hid: hid::Configuration,
+    ) -> Self {
+        Configuration {
+            service_record_handle: None,
+            service_record_state: None,
+            service_info_time_to_live: None,
+            service_availability: None,
+            additional_service_classes: Vec::new(),
+            primary_language,
+            encoding,
+            service_name: None,
+            service_description: None,
+            provider_name: None,
+            localized_strings: Default::default(),
+            documentation_url: None,
+            client_executable_url: None,
+            icon_url: None,
+            version,
+            additional_profiles: Vec::new(),
+            vendor_id: None,
+            product_id: None,
+            product_version: None,
+            vendor_id_source: None,
+            raw_attributes: Vec::new(),
+            transport: TransportConfiguration::default(),
+            hid,
+        }
+    }
+
+    pub fn with_service_record_handle(mut self, handle: u32) -> Self {
+        self.service_record_handle = Some(handle);
+        self
+    }
+
+    pub fn with_service_name(mut self, service_name: impl Into<Cow<'static, str>>) -> Self {
+        self.service_name = Some(service_name.into());
+        self
+    }
+
+    pub fn with_service_description(mut self, description: impl Into<Cow<'static, str>>) -> Self {
+        self.service_description = Some(description.into());
+        self
+    }
+
+    pub fn with_provider_name(mut self, provider_name: impl Into<Cow<'static, str>>) -> Self {
+        self.provider_name = Some(provider_name.into());
+        self
+    }
+
+    /// Set the service strings of an additional language, replacing any previous strings.
+    pub fn with_localized_strings(
+        mut self,
+        language_base: hid::LanguageBase,
+        strings: ServiceStrings,
+    ) -> Self {
+        self.localized_strings.insert(language_base, strings);
+        self
+    }
+
+    /// Set the browse groups. An empty list makes the service unbrowsable.
+    pub fn with_browse_groups(mut self, browse_groups: Vec<Uuid>) -> Self {
+        self.transport.browse_groups = Some(browse_groups);
+        self
+    }
+
+    /// Add a service class after the HID service class.
+    pub fn with_additional_service_class(mut self, service_class: Uuid) -> Self {
+        self.additional_service_classes.push(service_class);
+        self
+    }
+
+    /// Add a profile after the HID profile.
+    pub fn with_additional_profile(mut self, profile: Uuid, version: u16) -> Self {
+        self.additional_profiles.push((profile, version));
+        self
+    }
+
+    /// Set the identity of the device, as published in its Device ID record.
+    pub fn with_device_identity(
+        mut self,
+        vendor_id_source: u16,
+        vendor_id: u16,
+        product_id: u16,
+        product_version: u16,
+    ) -> Self {
+        self.vendor_id_source = Some(vendor_id_source);
+        self.vendor_id = Some(vendor_id);
+        self.product_id = Some(product_id);
+        self.product_version = Some(product_version);
+        self
+    }
+
+    pub fn with_documentation_url(mut self, url: impl Into<Cow<'static, str>>) -> Self {
+        self.documentation_url = Some(url.into());
+        self
+    }
+
+    pub fn with_client_executable_url(mut self, url: impl Into<Cow<'static, str>>) -> Self {
+        self.client_executable_url = Some(url.into());
+        self
+    }
+
+    pub fn with_icon_url(mut self, url: impl Into<Cow<'static, str>>) -> Self {
+        self.icon_url = Some(url.into());
+        self
+    }
+
+    /// Add an attribute to be emitted as given, replacing any attribute with the same ID.
+    pub fn with_raw_attribute(mut self, id: u16, tag: Tag) -> Self {
+        self.add_raw_attribute(id, tag);
+        self
+    }
+
+    pub fn with_transport(mut self, transport: TransportConfiguration) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    pub fn with_hid(mut self, hid: hid::Configuration) -> Self {
+        self.hid = hid;
+        self
+    }
+}
+
+impl DeviceIdConfiguration {
+    pub fn with_primary_record(mut self, primary_record: bool) -> Self {
+        self.primary_record = primary_record;
+        self
+    }
+
+    pub fn with_service_description(mut self, description: impl Into<Cow<'static, str>>) -> Self {
+        self.service_description = Some(description.into());
+        self
+    }
+}
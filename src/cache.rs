@@ -0,0 +1,144 @@
+//! Reading HID records from BlueZ's on-disk cache.
+//!
+//! BlueZ stores the SDP records of every remote device it has resolved under
+//! `/var/lib/bluetooth/<adapter>/cache/<device>`, in an INI-style file whose `[ServiceRecords]`
+//! section maps each record handle to the hex-encoded binary record, for example:
+//!
+//! ```text
+//! [ServiceRecords]
+//! 0x00010001=35...
+//! ```
+
+use sdp_xml::Tag;
+use hid_device_id::bluetooth::attribute_id;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::{Error, PartialConfiguration};
+use crate::data_element::decode_record;
+use crate::ids::UuidExt;
+
+/// Directory in which BlueZ stores adapter data.
+pub const STORAGE_DIR: &str = "/var/lib/bluetooth";
+
+/// Error type for reading cache files
+#[derive(Debug)]
+pub enum CacheError {
+    Io(io::Error),
+    MalformedLine(usize),
+    InvalidHex(usize),
+    InvalidRecord(u32),
+    MissingHidRecord,
+    Parse(Error),
+}
+
+impl Display for CacheError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) =>
+                write!(f, "I/O error: {}", e),
+            Self::MalformedLine(line) =>
+                write!(f, "line {}: malformed entry", line),
+            Self::InvalidHex(line) =>
+                write!(f, "line {}: invalid hex data", line),
+            Self::InvalidRecord(handle) =>
+                write!(f, "record 0x{:08x}: invalid data element", handle),
+            Self::MissingHidRecord =>
+                write!(f, "no HID record in cache"),
+            Self::Parse(e) =>
+                write!(f, "{}", e),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, CacheError>;
+
+/// Return the path of the cache file for the given device, as seen by the given adapter.
+///
+/// Addresses are formatted in upper case, as BlueZ does.
+pub fn cache_path(adapter: impl Display, device: impl Display) -> PathBuf {
+    Path::new(STORAGE_DIR)
+        .join(adapter.to_string().to_uppercase())
+        .join("cache")
+        .join(device.to_string().to_uppercase())
+}
+
+/// Decode a hex string into bytes.
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len()).step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Return true if the record's service class ID list contains the HID service class.
+pub(crate) fn is_hid_record(record: &Tag) -> bool {
+    let hid = Uuid::from_u16(0x1124);
+    let attributes = match record {
+        Tag::Record(attributes) => attributes,
+        _ => return false,
+    };
+    attributes.iter().any(|attribute| match attribute {
+        Tag::Attribute(attribute_id::SERVICE_CLASS_ID_LIST, value) => match value.as_ref() {
+            Tag::Sequence(classes) => classes.iter().any(|c| matches!(c, Tag::Uuid(u) if *u == hid)),
+            _ => false,
+        },
+        _ => false,
+    })
+}
+
+/// Read every service record in the contents of a cache file, with its handle.
+pub fn parse_cache_records(contents: &str) -> Result<Vec<(u32, Tag)>> {
+    let mut records = Vec::new();
+    let mut in_records = false;
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_records = line == "[ServiceRecords]";
+            continue;
+        }
+        if !in_records {
+            continue;
+        }
+        let (key, value) = line.split_once('=')
+            .ok_or(CacheError::MalformedLine(line_number))?;
+        let handle_hex = key.trim().trim_start_matches("0x").trim_start_matches("0X");
+        let handle = u32::from_str_radix(handle_hex, 16)
+            .map_err(|_| CacheError::MalformedLine(line_number))?;
+        let data = decode_hex(value.trim())
+            .ok_or(CacheError::InvalidHex(line_number))?;
+        let record = decode_record(&data)
+            .map_err(|_| CacheError::InvalidRecord(handle))?;
+        records.push((handle, record));
+    }
+    Ok(records)
+}
+
+/// Read the HID record in the contents of a cache file.
+pub fn parse_cache(contents: &str) -> Result<PartialConfiguration> {
+    let (_, record) = parse_cache_records(contents)?
+        .into_iter()
+        .find(|(_, record)| is_hid_record(record))
+        .ok_or(CacheError::MissingHidRecord)?;
+    PartialConfiguration::from_sdp_tag(record).map_err(CacheError::Parse)
+}
+
+/// Read the HID record in the cache file at the given path.
+pub fn read_cache_path(path: impl AsRef<Path>) -> Result<PartialConfiguration> {
+    let contents = fs::read_to_string(path).map_err(CacheError::Io)?;
+    parse_cache(&contents)
+}
+
+/// Read the HID record BlueZ has cached for the given device, as seen by the given adapter.
+pub fn read_cache(adapter: impl Display, device: impl Display) -> Result<PartialConfiguration> {
+    read_cache_path(cache_path(adapter, device))
+}
@@ -0,0 +1,87 @@
+//! Canonical form and fingerprints of configurations.
+//!
+//! The same record may be described by several configurations, such as with an optional
+//! attribute set to the value hosts assume when it is absent. The canonical form picks one of
+//! them, so that configurations read from different firmware versions of a device can be
+//! compared by their fingerprints.
+
+use hid_device_id::bluetooth::psm;
+use uuid::Uuid;
+
+use crate::{Configuration, hid};
+use crate::hid::DescriptorForm;
+use crate::ids::ServiceClass;
+
+/// FNV-1a offset basis and prime, for 64-bit hashes.
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x00000100000001b3;
+
+/// Hash bytes with 64-bit FNV-1a. Unlike the standard library's hashers, the result is specified,
+/// so it is stable across Rust versions and platforms.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(FNV_OFFSET_BASIS, |hash, byte| (hash ^ *byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// Unset an optional value if it is equal to the value assumed when it is absent.
+fn unset_default<T: PartialEq>(value: &mut Option<T>, default: T) {
+    if value.as_ref() == Some(&default) {
+        *value = None;
+    }
+}
+
+impl Configuration {
+    /// Return the canonical form of this configuration, which produces a record describing the
+    /// same service to hosts.
+    ///
+    /// Optional attributes set to their default values and default PSMs are unset, the public
+    /// browse group is left implicit, class descriptors are written as raw text, empty
+    /// localized strings are removed and raw attributes are sorted by ID.
+    pub fn canonicalize(mut self) -> Self {
+        let public_browse_group = Uuid::from(ServiceClass::PublicBrowseGroup);
+        if self.transport.browse_groups.as_deref() == Some(&[public_browse_group]) {
+            self.transport.browse_groups = None;
+        }
+
+        let hid_service_class = Uuid::from(ServiceClass::Hid);
+        self.additional_service_classes.retain(|class| *class != hid_service_class);
+
+        // Languages are kept in the language base list even if they have no strings.
+        self.hid.additional_languages = self.additional_language_bases();
+        self.localized_strings.retain(|_, strings| {
+            strings.service_name.is_some()
+                || strings.service_description.is_some()
+                || strings.provider_name.is_some()
+        });
+
+        self.raw_attributes.sort_by_key(|(id, _)| *id);
+
+        unset_default(&mut self.hid.battery_power, hid::defaults::BATTERY_POWER);
+        unset_default(&mut self.hid.remote_wake, hid::defaults::REMOTE_WAKE);
+        unset_default(&mut self.hid.supervision_timeout, hid::defaults::SUPERVISION_TIMEOUT);
+        unset_default(&mut self.hid.normally_connectable, hid::defaults::NORMALLY_CONNECTABLE);
+        unset_default(&mut self.transport.control_psm, psm::HID_CONTROL);
+        unset_default(&mut self.transport.interrupt_psm, psm::HID_INTERRUPT);
+
+        for descriptor in &mut self.hid.class_descriptors {
+            descriptor.2 = DescriptorForm::RawText;
+        }
+
+        self
+    }
+
+    /// Return a fingerprint of the record described by this configuration.
+    ///
+    /// The fingerprint is a hash of the binary record of the canonical form, so configurations
+    /// describing the same record have the same fingerprint. It is stable across versions of
+    /// this crate as long as the generated record does not change.
+    pub fn fingerprint(&self) -> u64 {
+        fnv1a(&self.clone().canonicalize().to_sdp_bytes())
+    }
+
+    /// Return true if both configurations describe the same record to hosts, ignoring the order
+    /// of raw attributes, the form of class descriptors and optional attributes set to their
+    /// default values.
+    pub fn semantically_equal(&self, other: &Configuration) -> bool {
+        self.clone().canonicalize() == other.clone().canonicalize()
+    }
+}
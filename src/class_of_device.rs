@@ -0,0 +1,110 @@
+//! The Bluetooth Class of Device of HID devices.
+//!
+//! Hosts see the Class of Device during inquiry, before they read the SDP record, and use it to
+//! choose an icon and whether to offer pairing. The minor device class of a peripheral has the
+//! same encoding as the HID device subclass, so the Class of Device of a HID device follows from
+//! its configuration.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::hid::DeviceSubclass;
+use crate::Configuration;
+
+/// Major device classes, from Bluetooth Assigned Numbers.
+pub mod major_device_class {
+    pub const MISCELLANEOUS: u8 = 0x00;
+    pub const COMPUTER: u8 = 0x01;
+    pub const PHONE: u8 = 0x02;
+    pub const AUDIO_VIDEO: u8 = 0x04;
+    pub const PERIPHERAL: u8 = 0x05;
+    pub const UNCATEGORIZED: u8 = 0x1f;
+}
+
+/// Major service class bits, from Bluetooth Assigned Numbers.
+pub mod service_class {
+    pub const LIMITED_DISCOVERABLE: u16 = 0x0001;
+    pub const POSITIONING: u16 = 0x0008;
+    pub const NETWORKING: u16 = 0x0010;
+    pub const RENDERING: u16 = 0x0020;
+    pub const CAPTURING: u16 = 0x0040;
+    pub const OBJECT_TRANSFER: u16 = 0x0080;
+    pub const AUDIO: u16 = 0x0100;
+    pub const TELEPHONY: u16 = 0x0200;
+    pub const INFORMATION: u16 = 0x0400;
+}
+
+/// A Class of Device, as the 24-bit value of the Class of Device field.
+///
+/// Bits 23-13 hold the major service classes, bits 12-8 the major device class, and bits 7-2
+/// the minor device class. The lowest two bits give the format, which is always zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ClassOfDevice(pub u32);
+
+impl ClassOfDevice {
+    pub const fn new(service_classes: u16, major_device_class: u8, minor_device_class: u8) -> Self {
+        ClassOfDevice(
+            (service_classes as u32 & 0x07ff) << 13
+                | (major_device_class as u32 & 0x1f) << 8
+                | (minor_device_class as u32 & 0xfc))
+    }
+
+    /// Create the Class of Device of a peripheral with the given device subclass and no major
+    /// service classes.
+    pub fn peripheral(subclass: DeviceSubclass) -> Self {
+        Self::new(0, major_device_class::PERIPHERAL, subclass.into())
+    }
+
+    /// Return the major service class bits, as defined by `service_class`.
+    pub const fn service_classes(&self) -> u16 {
+        (self.0 >> 13) as u16 & 0x07ff
+    }
+
+    pub const fn major_device_class(&self) -> u8 {
+        (self.0 >> 8) as u8 & 0x1f
+    }
+
+    /// Return the minor device class, with its lowest two bits clear.
+    pub const fn minor_device_class(&self) -> u8 {
+        self.0 as u8 & 0xfc
+    }
+
+    /// Return a copy with the given major service class bits set.
+    pub const fn with_service_classes(self, service_classes: u16) -> Self {
+        ClassOfDevice(self.0 | (service_classes as u32 & 0x07ff) << 13)
+    }
+
+    pub const fn is_peripheral(&self) -> bool {
+        self.major_device_class() == major_device_class::PERIPHERAL
+    }
+
+    /// Return the device subclass given by the minor device class, if this is a peripheral.
+    pub fn device_subclass(&self) -> Option<DeviceSubclass> {
+        self.is_peripheral().then(|| DeviceSubclass::from(self.minor_device_class()))
+    }
+}
+
+impl From<u32> for ClassOfDevice {
+    fn from(value: u32) -> Self {
+        ClassOfDevice(value & 0x00ff_ffff)
+    }
+}
+
+impl From<ClassOfDevice> for u32 {
+    fn from(class: ClassOfDevice) -> u32 {
+        class.0
+    }
+}
+
+impl Display for ClassOfDevice {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "0x{:06x}", self.0)
+    }
+}
+
+impl Configuration {
+    /// Return the Class of Device matching the device subclass of the record, which the adapter
+    /// should advertise while the record is registered.
+    pub fn class_of_device(&self) -> ClassOfDevice {
+        ClassOfDevice::peripheral(self.hid.device_subclass)
+    }
+}
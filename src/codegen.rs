@@ -0,0 +1,75 @@
+//! Generating source code embedding records.
+//!
+//! Firmware whose SDP server takes binary records can carry the record of a fixed configuration
+//! as a byte array in flash, instead of building it at runtime. The functions here write the
+//! binary record as a Rust `static` or a C array definition, for a build script or a generated
+//! file checked into the firmware project.
+
+use crate::{Configuration, SerializeOptions};
+
+/// Number of bytes written on each line of an array.
+const BYTES_PER_LINE: usize = 12;
+
+/// Language of generated source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    C,
+}
+
+/// Return the definition of a byte array holding `bytes`, in the given language.
+///
+/// The name is written as given, so it must be a valid identifier. In Rust, the array is a
+/// public `static` of type `[u8; N]`; in C, it is a `const uint8_t` array, which requires
+/// `<stdint.h>`.
+pub fn byte_array(name: &str, bytes: &[u8], language: Language) -> String {
+    let mut source = match language {
+        Language::Rust => format!("pub static {}: [u8; {}] = [\n", name, bytes.len()),
+        Language::C => format!("const uint8_t {}[{}] = {{\n", name, bytes.len()),
+    };
+    for line in bytes.chunks(BYTES_PER_LINE) {
+        let line: Vec<String> = line.iter().map(|b| format!("0x{:02x},", b)).collect();
+        source.push_str("    ");
+        source.push_str(&line.join(" "));
+        source.push('\n');
+    }
+    source.push_str(match language {
+        Language::Rust => "];\n",
+        Language::C => "};\n",
+    });
+    source
+}
+
+impl Configuration {
+    /// Return a Rust `static` named `name` holding the binary SDP record of the configuration.
+    pub fn to_rust_source(&self, name: &str) -> String {
+        self.to_source_with_options(name, Language::Rust, &SerializeOptions::default())
+    }
+
+    /// Return a C array named `name` holding the binary SDP record of the configuration.
+    pub fn to_c_source(&self, name: &str) -> String {
+        self.to_source_with_options(name, Language::C, &SerializeOptions::default())
+    }
+
+    /// Return an array named `name` holding the binary SDP record of the configuration, in the
+    /// given language, serializing the record with the given options. The definition follows
+    /// a comment giving the service name and length of the record.
+    pub fn to_source_with_options(
+        &self,
+        name: &str,
+        language: Language,
+        options: &SerializeOptions,
+    ) -> String {
+        let record = self.to_sdp_bytes_with_options(options);
+        let service_name = self.service_name.as_deref()
+            // A line break, or a backslash continuing the comment in C, would end the comment
+            // elsewhere.
+            .filter(|service_name| {
+                !service_name.contains(['\n', '\r']) && !service_name.ends_with('\\')
+            })
+            .unwrap_or("HID device");
+        let mut source = format!("// SDP record of {}, {} bytes.\n", service_name, record.len());
+        source.push_str(&byte_array(name, &record, language));
+        source
+    }
+}
@@ -0,0 +1,170 @@
+//! Normalization of SDP XML dialects.
+//!
+//! BlueZ's `sdptool`, `bluetoothctl` and various third-party tools all emit slightly different
+//! flavours of the SDP XML format: element names in upper case, decimal instead of hexadecimal
+//! values, hex values without zero padding, booleans spelled `TRUE` or `1`, and so on. The
+//! reader in `sdp_xml_reader` only accepts the canonical form produced by BlueZ's sdpd, so the
+//! functions here rewrite a document into that form before it is parsed.
+
+/// Width in hex digits of the value of the given numeric element, if it is numeric.
+fn numeric_width(element: &str) -> Option<usize> {
+    match element {
+        "uint8" | "int8" => Some(2),
+        "uint16" | "int16" => Some(4),
+        "uint32" | "int32" => Some(8),
+        "uint64" | "int64" => Some(16),
+        "uint128" | "int128" => Some(32),
+        _ => None,
+    }
+}
+
+/// Parse a decimal or hexadecimal integer, as written by any of the common tools.
+fn parse_integer(value: &str) -> Option<u128> {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        u128::from_str_radix(hex, 16).ok()
+    } else if let Some(negative) = value.strip_prefix('-') {
+        // Negative values are stored as two's complement, and truncated when formatted.
+        negative.parse::<i128>().ok().map(|v| (-v) as u128)
+    } else {
+        value.parse::<u128>().ok()
+    }
+}
+
+/// Format an integer as zero-padded lowercase hex of the given width.
+fn format_integer(value: u128, width: usize) -> String {
+    let mask = if width >= 32 { u128::MAX } else { (1u128 << (width * 4)) - 1 };
+    format!("0x{:0width$x}", value & mask, width = width)
+}
+
+/// Normalize the value of a `uuid` element.
+fn normalize_uuid(value: &str) -> String {
+    let value = value.trim().to_ascii_lowercase();
+    if value.contains('-') {
+        // Full 128-bit UUID, only the case needs fixing.
+        return value;
+    }
+    let digits = value.strip_prefix("0x").unwrap_or(&value);
+    match u32::from_str_radix(digits, 16) {
+        Ok(short) if short <= 0xffff => format!("0x{:04x}", short),
+        Ok(short) => format!("0x{:08x}", short),
+        Err(_) => value,
+    }
+}
+
+/// Normalize the value of a `boolean` element.
+fn normalize_boolean(value: &str) -> String {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" => String::from("true"),
+        "false" | "0" | "no" => String::from("false"),
+        other => other.to_owned(),
+    }
+}
+
+/// Normalize a single XML attribute on the given element.
+fn normalize_attribute(element: &str, name: &str, value: &str) -> String {
+    match (element, name) {
+        ("attribute", "id") => match parse_integer(value) {
+            Some(id) => format_integer(id, 4),
+            None => value.to_owned(),
+        },
+        ("boolean", "value") => normalize_boolean(value),
+        ("uuid", "value") => normalize_uuid(value),
+        ("text", "encoding") => value.trim().to_ascii_lowercase(),
+        ("text", "value") => value.to_owned(),
+        (_, "value") => match (numeric_width(element), parse_integer(value)) {
+            (Some(width), Some(v)) => format_integer(v, width),
+            _ => value.to_owned(),
+        },
+        _ => value.to_owned(),
+    }
+}
+
+/// Split the inside of a tag, such as `uint8 value="0x01" /`, into the element name, its
+/// attributes and whether it is self-closing.
+fn split_tag(inner: &str) -> (String, Vec<(String, String)>, bool) {
+    let inner = inner.trim();
+    let (inner, self_closing) = match inner.strip_suffix('/') {
+        Some(stripped) => (stripped.trim_end(), true),
+        None => (inner, false),
+    };
+    let name_end = inner.find(char::is_whitespace).unwrap_or(inner.len());
+    let name = inner[..name_end].to_ascii_lowercase();
+    let mut attributes = Vec::new();
+    let mut rest = inner[name_end..].trim_start();
+    while let Some(eq) = rest.find('=') {
+        let attribute_name = rest[..eq].trim().to_ascii_lowercase();
+        let after_eq = rest[eq + 1..].trim_start();
+        let quote = match after_eq.chars().next() {
+            Some(q @ ('"' | '\'')) => q,
+            _ => break,
+        };
+        let value_end = match after_eq[1..].find(quote) {
+            Some(end) => end + 1,
+            None => break,
+        };
+        attributes.push((attribute_name, after_eq[1..value_end].to_owned()));
+        rest = after_eq[value_end + 1..].trim_start();
+    }
+    (name, attributes, self_closing)
+}
+
+/// Rewrite an SDP XML document in any of the common dialects into the canonical form expected
+/// by `sdp_xml_reader`.
+///
+/// Element and attribute names are lower-cased, numeric values are rewritten as zero-padded
+/// hex of the element's width, booleans become `true`/`false` and short UUIDs are padded.
+/// Values that cannot be interpreted are left untouched, so that the strict parser can report
+/// them.
+pub fn normalize_sdp_xml(xml: &[u8]) -> Vec<u8> {
+    let text = String::from_utf8_lossy(xml);
+    let mut output = String::with_capacity(text.len());
+    let mut rest: &str = &text;
+
+    while let Some(start) = rest.find('<') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = match after.find('>') {
+            Some(end) => end,
+            None => {
+                // Unterminated tag, copy verbatim.
+                output.push_str(&rest[start..]);
+                rest = "";
+                break;
+            },
+        };
+        let inner = &after[..end];
+        rest = &after[end + 1..];
+
+        if inner.starts_with('?') || inner.starts_with('!') {
+            // Processing instructions and doctype declarations are dropped, some readers reject
+            // a DOCTYPE referencing a missing DTD.
+            if inner.starts_with("?xml") {
+                output.push('<');
+                output.push_str(inner);
+                output.push('>');
+            }
+            continue;
+        }
+        if let Some(closing) = inner.strip_prefix('/') {
+            output.push_str("</");
+            output.push_str(&closing.trim().to_ascii_lowercase());
+            output.push('>');
+            continue;
+        }
+
+        let (element, attributes, self_closing) = split_tag(inner);
+        output.push('<');
+        output.push_str(&element);
+        for (name, value) in attributes {
+            let value = normalize_attribute(&element, &name, &value);
+            output.push_str(&format!(" {}=\"{}\"", name, value));
+        }
+        if self_closing {
+            output.push_str(" /");
+        }
+        output.push('>');
+    }
+    output.push_str(rest);
+    output.into_bytes()
+}
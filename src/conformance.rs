@@ -0,0 +1,131 @@
+//! Reference configurations and helpers for conformance tests.
+//!
+//! The vectors are modelled on the keyboard and mouse records given as examples in the HID 1.1
+//! specification: conforming records, and copies of them each breaking a single requirement.
+//! Downstream crates can check their own configurations with `assert_conformant`, and check
+//! that tooling built on this crate rejects the bad vectors.
+
+use crate::hid::{self, report, DeviceSubclass};
+use crate::validate::{Severity, ValidateOptions, Violation};
+use crate::{Configuration, TransportConfiguration};
+
+/// A reference configuration.
+#[derive(Clone, Debug)]
+pub struct Vector {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub configuration: Configuration,
+    /// Name of the rule the configuration violates with `Severity::Error`, or `None` if it
+    /// conforms.
+    pub violates: Option<&'static str>,
+}
+
+impl Vector {
+    /// Return true if the configuration conforms to the HID profile.
+    pub fn is_conformant(&self) -> bool {
+        self.violates.is_none()
+    }
+}
+
+/// The boot keyboard of the HID 1.1 examples.
+fn keyboard() -> Configuration {
+    Configuration::minimal(report::BOOT_KEYBOARD.to_vec(), DeviceSubclass::KEYBOARD)
+        .with_service_name("Keyboard")
+        .with_provider_name("Example")
+}
+
+/// The boot mouse of the HID 1.1 examples.
+fn mouse() -> Configuration {
+    Configuration::minimal(report::BOOT_MOUSE.to_vec(), DeviceSubclass::MOUSE)
+        .with_service_name("Mouse")
+        .with_provider_name("Example")
+}
+
+/// Return every reference configuration, conforming vectors first.
+pub fn vectors() -> Vec<Vector> {
+    let mut no_virtual_cable = keyboard();
+    no_virtual_cable.hid.virtual_cable = false;
+
+    let mut no_report_descriptor = mouse();
+    no_report_descriptor.hid.class_descriptors.clear();
+
+    let mut truncated_descriptor = mouse();
+    // Drop the two End Collection items closing the descriptor.
+    let truncated = report::BOOT_MOUSE[..report::BOOT_MOUSE.len() - 2].to_vec();
+    truncated_descriptor.hid.class_descriptors = vec![hid::ClassDescriptor::report(truncated)];
+
+    let mut reserved_psm = keyboard();
+    reserved_psm.transport = TransportConfiguration::default().with_control_psm(0x0100);
+
+    let mut overlapping_languages = keyboard();
+    overlapping_languages.hid.additional_languages = vec![hid::LanguageBase::new(0x040c, 0x0101)];
+
+    vec![
+        Vector {
+            name: "boot-keyboard",
+            description: "boot keyboard with the report descriptor of the HID 1.1 examples",
+            configuration: keyboard(),
+            violates: None,
+        },
+        Vector {
+            name: "boot-mouse",
+            description: "boot mouse with the report descriptor of the HID 1.1 examples",
+            configuration: mouse(),
+            violates: None,
+        },
+        Vector {
+            name: "boot-keyboard-without-virtual-cable",
+            description: "boot keyboard which does not support virtual cables",
+            configuration: no_virtual_cable,
+            violates: Some("boot-device"),
+        },
+        Vector {
+            name: "mouse-without-report-descriptor",
+            description: "mouse whose descriptor list has no report descriptor",
+            configuration: no_report_descriptor,
+            violates: Some("report-descriptor-required"),
+        },
+        Vector {
+            name: "mouse-with-truncated-descriptor",
+            description: "mouse whose report descriptor ends without closing its collections",
+            configuration: truncated_descriptor,
+            violates: Some("report-descriptor"),
+        },
+        Vector {
+            name: "keyboard-with-reserved-psm",
+            description: "keyboard whose control channel uses an even PSM",
+            configuration: reserved_psm,
+            violates: Some("psm"),
+        },
+        Vector {
+            name: "keyboard-with-overlapping-languages",
+            description: "keyboard with a second language whose strings overlap the primary",
+            configuration: overlapping_languages,
+            violates: Some("language-bases"),
+        },
+    ]
+}
+
+/// Return the reference configuration with the given name.
+pub fn vector(name: &str) -> Option<Vector> {
+    vectors().into_iter().find(|vector| vector.name == name)
+}
+
+/// Panic, listing every error, if validation finds errors in the configuration.
+#[track_caller]
+pub fn assert_conformant(config: &Configuration) {
+    assert_conformant_with_options(config, &ValidateOptions::default());
+}
+
+/// Panic, listing every error, if validation using the given options finds errors in the
+/// configuration.
+#[track_caller]
+pub fn assert_conformant_with_options(config: &Configuration, options: &ValidateOptions) {
+    let errors: Vec<Violation> = config.validate_with_options(options).into_iter()
+        .filter(|violation| violation.severity == Severity::Error)
+        .collect();
+    if !errors.is_empty() {
+        let list: Vec<String> = errors.iter().map(|error| format!("  {}", error)).collect();
+        panic!("configuration does not conform:\n{}", list.join("\n"));
+    }
+}
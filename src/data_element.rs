@@ -0,0 +1,328 @@
+//! Binary SDP data element encoding.
+//!
+//! Over the air, SDP records are transferred as data elements: a header byte holding a 5-bit
+//! type descriptor and a 3-bit size index, an optional length, and the data itself. The functions
+//! here decode that representation into the same `Tag` tree produced by the XML reader, so that
+//! captures from btmon or Wireshark, and live SDP responses, can be read like XML dumps.
+//!
+//! See Bluetooth Core, Vol 3, Part B, section 3.
+
+use sdp_xml::Tag;
+use uuid::Uuid;
+
+use crate::from_sdp::{Error, Result};
+use crate::ids::UuidExt;
+
+/// Data element type descriptors.
+pub mod element_type {
+    pub const NIL: u8 = 0;
+    pub const UINT: u8 = 1;
+    pub const INT: u8 = 2;
+    pub const UUID: u8 = 3;
+    pub const TEXT: u8 = 4;
+    pub const BOOLEAN: u8 = 5;
+    pub const SEQUENCE: u8 = 6;
+    pub const ALTERNATIVE: u8 = 7;
+    pub const URL: u8 = 8;
+}
+
+/// Cursor over a buffer of data elements, tracking the offset for error reporting.
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], offset: usize) -> Self {
+        Reader { data, offset }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Take the given number of bytes from the front of the buffer.
+    fn take(&mut self, size: usize) -> Result<Reader<'a>> {
+        if self.data.len() < size {
+            return Err(Error::UnexpectedEndOfData(self.offset + self.data.len()));
+        }
+        let (bytes, rest) = self.data.split_at(size);
+        let taken = Reader::new(bytes, self.offset);
+        self.data = rest;
+        self.offset += size;
+        Ok(taken)
+    }
+
+    /// Read a big-endian integer of the given size.
+    fn take_uint(&mut self, size: usize) -> Result<u128> {
+        let bytes = self.take(size)?;
+        Ok(bytes.data.iter().fold(0u128, |acc, b| (acc << 8) | *b as u128))
+    }
+
+    /// Read a single data element.
+    fn take_element(&mut self) -> Result<Tag> {
+        let offset = self.offset;
+        let header = self.take_uint(1)? as u8;
+        let element_type = header >> 3;
+        let size = match header & 0x07 {
+            0 if element_type == element_type::NIL => 0,
+            index @ 0..=4 => 1usize << index,
+            5 => self.take_uint(1)? as usize,
+            6 => self.take_uint(2)? as usize,
+            _ => self.take_uint(4)? as usize,
+        };
+        let mut body = self.take(size)?;
+
+        let tag = match (element_type, size) {
+            (element_type::NIL, _) => Tag::Nil,
+            (element_type::UINT, 1) => Tag::UInt8(body.take_uint(1)? as u8),
+            (element_type::UINT, 2) => Tag::UInt16(body.take_uint(2)? as u16),
+            (element_type::UINT, 4) => Tag::UInt32(body.take_uint(4)? as u32),
+            (element_type::UINT, 8) => Tag::UInt64(body.take_uint(8)? as u64),
+            (element_type::UINT, 16) => Tag::UInt128(body.take_uint(16)?),
+            (element_type::INT, 1) => Tag::Int8(body.take_uint(1)? as i8),
+            (element_type::INT, 2) => Tag::Int16(body.take_uint(2)? as i16),
+            (element_type::INT, 4) => Tag::Int32(body.take_uint(4)? as i32),
+            (element_type::INT, 8) => Tag::Int64(body.take_uint(8)? as i64),
+            (element_type::INT, 16) => Tag::Int128(body.take_uint(16)? as i128),
+            (element_type::UUID, 2) => Tag::Uuid(Uuid::from_u16(body.take_uint(2)? as u16)),
+            (element_type::UUID, 4) => Tag::Uuid(Uuid::from_u32(body.take_uint(4)? as u32)),
+            (element_type::UUID, 16) => Tag::Uuid(Uuid::from_u128(body.take_uint(16)?)),
+            (element_type::TEXT, _) => match String::from_utf8(body.data.to_vec()) {
+                Ok(text) => Tag::Text(text),
+                Err(e) => Tag::RawText(e.into_bytes()),
+            },
+            (element_type::BOOLEAN, 1) => Tag::Boolean(body.take_uint(1)? != 0),
+            (element_type::SEQUENCE, _) | (element_type::ALTERNATIVE, _) => {
+                let mut children = Vec::new();
+                while !body.is_empty() {
+                    children.push(body.take_element()?);
+                }
+                if element_type == element_type::SEQUENCE {
+                    Tag::Sequence(children)
+                } else {
+                    Tag::Alternate(children)
+                }
+            },
+            (element_type::URL, _) => Tag::Url(String::from_utf8_lossy(body.data).into_owned()),
+            _ => {
+                return Err(Error::InvalidDataElement(offset));
+            },
+        };
+        Ok(tag)
+    }
+
+    /// Read an attribute list, a sequence of attribute ID and value pairs, as a record.
+    fn take_record(&mut self) -> Result<Tag> {
+        let offset = self.offset;
+        let elements = match self.take_element()? {
+            Tag::Sequence(elements) => elements,
+            tag => {
+                return Err(Error::ExpectedSequence(0, tag));
+            },
+        };
+        if elements.len() % 2 != 0 {
+            return Err(Error::InvalidDataElement(offset));
+        }
+        let mut attributes = Vec::new();
+        let mut elements = elements.into_iter();
+        while let (Some(id), Some(value)) = (elements.next(), elements.next()) {
+            match id {
+                Tag::UInt16(id) => attributes.push(Tag::Attribute(id, Box::new(value))),
+                _ => {
+                    return Err(Error::InvalidDataElement(offset));
+                },
+            }
+        }
+        Ok(Tag::Record(attributes))
+    }
+}
+
+/// Decode a single data element.
+pub fn decode_element(data: &[u8]) -> Result<Tag> {
+    let mut reader = Reader::new(data, 0);
+    let tag = reader.take_element()?;
+    if !reader.is_empty() {
+        return Err(Error::TrailingData(reader.offset));
+    }
+    Ok(tag)
+}
+
+/// Decode a service record, sent as an attribute list in SDP ServiceAttribute responses.
+pub fn decode_record(data: &[u8]) -> Result<Tag> {
+    let mut reader = Reader::new(data, 0);
+    let record = reader.take_record()?;
+    if !reader.is_empty() {
+        return Err(Error::TrailingData(reader.offset));
+    }
+    Ok(record)
+}
+
+/// Decode the service record at the front of the data, returning it along with the number of
+/// bytes it occupied. Used to walk buffers holding several records back to back.
+pub fn decode_record_prefix(data: &[u8]) -> Result<(Tag, usize)> {
+    let mut reader = Reader::new(data, 0);
+    let record = reader.take_record()?;
+    Ok((record, reader.offset))
+}
+
+/// Decode a list of service records, sent as a sequence of attribute lists in SDP
+/// ServiceSearchAttribute responses.
+pub fn decode_records(data: &[u8]) -> Result<Vec<Tag>> {
+    let mut reader = Reader::new(data, 0);
+    let header_offset = reader.offset;
+    let header = reader.take_uint(1)? as u8;
+    if header >> 3 != element_type::SEQUENCE {
+        return Err(Error::InvalidDataElement(header_offset));
+    }
+    let size = match header & 0x07 {
+        5 => reader.take_uint(1)? as usize,
+        6 => reader.take_uint(2)? as usize,
+        7 => reader.take_uint(4)? as usize,
+        _ => {
+            return Err(Error::InvalidDataElement(header_offset));
+        },
+    };
+    let mut body = reader.take(size)?;
+    let mut records = Vec::new();
+    while !body.is_empty() {
+        records.push(body.take_record()?);
+    }
+    if !reader.is_empty() {
+        return Err(Error::TrailingData(reader.offset));
+    }
+    Ok(records)
+}
+
+/// Append a data element header with the given type and fixed size index.
+fn push_header(output: &mut Vec<u8>, element_type: u8, size_index: u8) {
+    output.push((element_type << 3) | size_index);
+}
+
+/// Append a data element header with the given type and variable data length, using the
+/// smallest length field that fits.
+fn push_variable_header(output: &mut Vec<u8>, element_type: u8, len: usize) {
+    if len <= u8::MAX as usize {
+        push_header(output, element_type, 5);
+        output.push(len as u8);
+    } else if len <= u16::MAX as usize {
+        push_header(output, element_type, 6);
+        output.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        push_header(output, element_type, 7);
+        output.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+/// Append a sequence or alternative of the given elements.
+fn push_list<'a>(output: &mut Vec<u8>, element_type: u8, children: impl IntoIterator<Item = &'a Tag>) {
+    let mut body = Vec::new();
+    for child in children {
+        encode_element_into(child, &mut body);
+    }
+    push_variable_header(output, element_type, body.len());
+    output.extend_from_slice(&body);
+}
+
+/// Append the encoding of a UUID, using the 16- or 32-bit short form if possible.
+fn push_uuid(output: &mut Vec<u8>, uuid: &Uuid) {
+    if let Some(short) = uuid.as_u16() {
+        push_header(output, element_type::UUID, 1);
+        output.extend_from_slice(&short.to_be_bytes());
+    } else if let Some(short) = uuid.as_u32() {
+        push_header(output, element_type::UUID, 2);
+        output.extend_from_slice(&short.to_be_bytes());
+    } else {
+        push_header(output, element_type::UUID, 4);
+        output.extend_from_slice(uuid.as_bytes());
+    }
+}
+
+/// Append the encoding of the given element.
+///
+/// Records are encoded as attribute lists: a sequence of alternating attribute IDs and values.
+pub fn encode_element_into(tag: &Tag, output: &mut Vec<u8>) {
+    match tag {
+        Tag::Nil => push_header(output, element_type::NIL, 0),
+        Tag::Boolean(v) => {
+            push_header(output, element_type::BOOLEAN, 0);
+            output.push(*v as u8);
+        },
+        Tag::UInt8(v) => {
+            push_header(output, element_type::UINT, 0);
+            output.push(*v);
+        },
+        Tag::UInt16(v) => {
+            push_header(output, element_type::UINT, 1);
+            output.extend_from_slice(&v.to_be_bytes());
+        },
+        Tag::UInt32(v) => {
+            push_header(output, element_type::UINT, 2);
+            output.extend_from_slice(&v.to_be_bytes());
+        },
+        Tag::UInt64(v) => {
+            push_header(output, element_type::UINT, 3);
+            output.extend_from_slice(&v.to_be_bytes());
+        },
+        Tag::UInt128(v) => {
+            push_header(output, element_type::UINT, 4);
+            output.extend_from_slice(&v.to_be_bytes());
+        },
+        Tag::Int8(v) => {
+            push_header(output, element_type::INT, 0);
+            output.extend_from_slice(&v.to_be_bytes());
+        },
+        Tag::Int16(v) => {
+            push_header(output, element_type::INT, 1);
+            output.extend_from_slice(&v.to_be_bytes());
+        },
+        Tag::Int32(v) => {
+            push_header(output, element_type::INT, 2);
+            output.extend_from_slice(&v.to_be_bytes());
+        },
+        Tag::Int64(v) => {
+            push_header(output, element_type::INT, 3);
+            output.extend_from_slice(&v.to_be_bytes());
+        },
+        Tag::Int128(v) => {
+            push_header(output, element_type::INT, 4);
+            output.extend_from_slice(&v.to_be_bytes());
+        },
+        Tag::Uuid(uuid) => push_uuid(output, uuid),
+        Tag::Text(text) => {
+            push_variable_header(output, element_type::TEXT, text.len());
+            output.extend_from_slice(text.as_bytes());
+        },
+        Tag::RawText(bytes) => {
+            push_variable_header(output, element_type::TEXT, bytes.len());
+            output.extend_from_slice(bytes);
+        },
+        Tag::Url(url) => {
+            push_variable_header(output, element_type::URL, url.len());
+            output.extend_from_slice(url.as_bytes());
+        },
+        Tag::Sequence(children) => push_list(output, element_type::SEQUENCE, children),
+        Tag::Alternate(children) => push_list(output, element_type::ALTERNATIVE, children),
+        Tag::Record(attributes) => {
+            let mut body = Vec::new();
+            for attribute in attributes {
+                encode_element_into(attribute, &mut body);
+            }
+            push_variable_header(output, element_type::SEQUENCE, body.len());
+            output.extend_from_slice(&body);
+        },
+        Tag::Attribute(id, value) => {
+            // Only valid inside a record, where the ID and value are flattened into the
+            // enclosing attribute list.
+            encode_element_into(&Tag::UInt16(*id), output);
+            encode_element_into(value, output);
+        },
+    }
+}
+
+/// Encode the given element.
+pub fn encode_element(tag: &Tag) -> Vec<u8> {
+    let mut output = Vec::new();
+    encode_element_into(tag, &mut output);
+    output
+}
@@ -0,0 +1,355 @@
+//! Device definition files, describing configurations outside the program which registers them.
+//!
+//! Definitions are read from TOML with the `toml` feature, and from YAML with the `yaml` feature.
+//! A definition has sections for the service strings, the identity of the device, the HID
+//! attributes and the class descriptors. In TOML:
+//!
+//! ```toml
+//! [strings]
+//! service_name = "Keyboard"
+//! provider_name = "Example"
+//!
+//! [identity]
+//! vendor_id = 0x1d6b
+//! product_id = 0x0246
+//!
+//! [hid]
+//! subclass = "keyboard"
+//! remote_wake = true
+//! supervision_timeout_ms = 2000
+//!
+//! [[descriptors]]
+//! hex = "05 01 09 06 a1 01 ... c0"
+//! ```
+//!
+//! Every field is optional except the subclass, and unset HID attributes take the values of
+//! `Configuration::minimal`: virtual cables and reconnection are supported, and keyboards and
+//! pointing devices are boot devices. The language defaults to English, and the encoding to
+//! UTF-8.
+//!
+//! Each descriptor gives its data in exactly one of `hex`, inline hex bytes optionally separated
+//! by spaces or commas; `file`, the path of a binary file; or `source`, the path of a textual
+//! descriptor compiled by `hid::report::compile`. Paths are relative to the definition file.
+//! The descriptor `type` is "report", the default, "physical", or a number.
+//!
+//! The same definition in YAML has a mapping for each section, and a sequence of descriptors:
+//!
+//! ```yaml
+//! strings:
+//!   service_name: Keyboard
+//! hid:
+//!   subclass: keyboard
+//! descriptors:
+//!   - source: keyboard.hid
+//! ```
+
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::hid::{self, report, DeviceSubclass};
+use crate::{encoding, language, Configuration, LanguageCode, ProfileVersion};
+
+/// Error type for definitions which cannot be read.
+#[derive(Debug)]
+pub enum DefinitionError {
+    /// A file, either the definition or one it refers to, could not be read.
+    Io(PathBuf, io::Error),
+    /// The definition is not valid in its format, or its fields have the wrong types.
+    Syntax(String),
+    /// A field has a value outside its range or an unknown name.
+    InvalidValue { field: &'static str, value: String },
+    /// The descriptor at the given index does not give its data in exactly one way, or gives
+    /// invalid hex.
+    InvalidDescriptor(usize),
+    /// The textual descriptor at the given path could not be compiled.
+    Compile(PathBuf, report::CompileError),
+}
+
+impl Display for DefinitionError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Io(path, e) =>
+                write!(f, "{}: {}", path.display(), e),
+            Self::Syntax(message) =>
+                write!(f, "{}", message),
+            Self::InvalidValue { field, value } =>
+                write!(f, "invalid value {} for {}", value, field),
+            Self::InvalidDescriptor(index) =>
+                write!(f, "descriptor {} must give one of hex, file or source", index),
+            Self::Compile(path, e) =>
+                write!(f, "{}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for DefinitionError {}
+
+pub type Result<T> = std::result::Result<T, DefinitionError>;
+
+/// A value given either by name or by number.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NameOrNumber {
+    Name(String),
+    Number(u64),
+}
+
+impl NameOrNumber {
+    fn to_string_lossy(&self) -> String {
+        match self {
+            Self::Name(name) => format!("{:?}", name),
+            Self::Number(value) => value.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LanguageSection {
+    iso_code: u16,
+    hid_code: u16,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StringsSection {
+    service_name: Option<String>,
+    service_description: Option<String>,
+    provider_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IdentitySection {
+    vendor_id_source: Option<u16>,
+    vendor_id: u16,
+    product_id: u16,
+    version: Option<u16>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HidSection {
+    subclass: NameOrNumber,
+    country_code: Option<u8>,
+    virtual_cable: Option<bool>,
+    reconnect_initiate: Option<bool>,
+    boot_device: Option<bool>,
+    battery_power: Option<bool>,
+    remote_wake: Option<bool>,
+    normally_connectable: Option<bool>,
+    supervision_timeout_ms: Option<u64>,
+    ssr_host_max_latency_ms: Option<u64>,
+    ssr_host_min_timeout_ms: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DescriptorSection {
+    #[serde(rename = "type")]
+    descriptor_type: Option<NameOrNumber>,
+    hex: Option<String>,
+    file: Option<PathBuf>,
+    source: Option<PathBuf>,
+}
+
+/// A device definition, as read from a file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Definition {
+    language: Option<LanguageSection>,
+    encoding: Option<u16>,
+    #[serde(default)]
+    strings: StringsSection,
+    identity: Option<IdentitySection>,
+    hid: HidSection,
+    #[serde(default)]
+    descriptors: Vec<DescriptorSection>,
+}
+
+/// Decode hex bytes, optionally separated by spaces or commas and prefixed by `0x`.
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    for token in hex.split(|c: char| c.is_whitespace() || c == ',').filter(|t| !t.is_empty()) {
+        let digits = token.trim_start_matches("0x").trim_start_matches("0X");
+        if digits.len() % 2 != 0 {
+            return None;
+        }
+        for i in (0..digits.len()).step_by(2) {
+            data.push(u8::from_str_radix(digits.get(i..i + 2)?, 16).ok()?);
+        }
+    }
+    Some(data)
+}
+
+/// Parse a device subclass, by name or as the byte written in the record.
+fn parse_subclass(value: &NameOrNumber) -> Option<DeviceSubclass> {
+    match value {
+        NameOrNumber::Number(value) => u8::try_from(*value).ok().map(DeviceSubclass::from),
+        NameOrNumber::Name(name) => match name.to_ascii_lowercase().replace('_', "-").as_str() {
+            "keyboard" => Some(DeviceSubclass::KEYBOARD),
+            "mouse" | "pointing" => Some(DeviceSubclass::MOUSE),
+            "combo" => Some(DeviceSubclass::COMBO),
+            "joystick" => Some(DeviceSubclass::JOYSTICK),
+            "gamepad" => Some(DeviceSubclass::GAMEPAD),
+            "remote-control" => Some(DeviceSubclass::REMOTE_CONTROL),
+            "digitizer-tablet" => Some(DeviceSubclass::DIGITIZER_TABLET),
+            _ => None,
+        },
+    }
+}
+
+/// Parse a descriptor type, by name or number.
+fn parse_descriptor_type(value: &NameOrNumber) -> Option<u8> {
+    match value {
+        NameOrNumber::Number(value) => u8::try_from(*value).ok(),
+        NameOrNumber::Name(name) => match name.to_ascii_lowercase().as_str() {
+            "report" => Some(hid::descriptor_type::REPORT),
+            "physical" => Some(hid::descriptor_type::PHYSICAL),
+            _ => None,
+        },
+    }
+}
+
+/// Convert a number of milliseconds to a sniff subrating or supervision parameter.
+fn from_millis<T>(
+    field: &'static str,
+    millis: Option<u64>,
+    convert: fn(Duration) -> Option<T>,
+) -> Result<Option<T>> {
+    millis.map(|millis| convert(Duration::from_millis(millis))
+        .ok_or_else(|| DefinitionError::InvalidValue { field, value: millis.to_string() }))
+        .transpose()
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).map_err(|e| DefinitionError::Io(path.to_path_buf(), e))
+}
+
+impl Definition {
+    /// Convert the definition into a configuration, reading the files it refers to relative to
+    /// the given directory.
+    pub(crate) fn into_configuration(self, base_dir: &Path) -> Result<Configuration> {
+        let subclass = parse_subclass(&self.hid.subclass)
+            .ok_or_else(|| DefinitionError::InvalidValue {
+                field: "hid.subclass",
+                value: self.hid.subclass.to_string_lossy(),
+            })?;
+
+        let mut hid = hid::Configuration::new(subclass)
+            .with_virtual_cable(self.hid.virtual_cable.unwrap_or(true))
+            .with_reconnect_initiate(self.hid.reconnect_initiate.unwrap_or(true))
+            .with_boot_device(self.hid.boot_device
+                .unwrap_or(subclass.is_keyboard() || subclass.is_pointing()));
+        if let Some(country_code) = self.hid.country_code {
+            hid = hid.with_country_code(country_code.into());
+        }
+        hid.battery_power = self.hid.battery_power;
+        hid.remote_wake = self.hid.remote_wake;
+        hid.normally_connectable = self.hid.normally_connectable;
+        hid.supervision_timeout = from_millis(
+            "hid.supervision_timeout_ms", self.hid.supervision_timeout_ms,
+            hid::SupervisionTimeout::from_duration)?;
+        hid.ssr_host_max_latency = from_millis(
+            "hid.ssr_host_max_latency_ms", self.hid.ssr_host_max_latency_ms,
+            hid::SsrHostMaxLatency::from_duration)?;
+        hid.ssr_host_min_timeout = from_millis(
+            "hid.ssr_host_min_timeout_ms", self.hid.ssr_host_min_timeout_ms,
+            hid::SsrHostMinTimeout::from_duration)?;
+
+        for (index, descriptor) in self.descriptors.into_iter().enumerate() {
+            let descriptor_type = match &descriptor.descriptor_type {
+                Some(value) => parse_descriptor_type(value)
+                    .ok_or_else(|| DefinitionError::InvalidValue {
+                        field: "descriptors.type",
+                        value: value.to_string_lossy(),
+                    })?,
+                None => hid::descriptor_type::REPORT,
+            };
+            let data = match (descriptor.hex, descriptor.file, descriptor.source) {
+                (Some(hex), None, None) =>
+                    decode_hex(&hex).ok_or(DefinitionError::InvalidDescriptor(index))?,
+                (None, Some(file), None) => read_file(&base_dir.join(file))?,
+                (None, None, Some(source)) => {
+                    let path = base_dir.join(source);
+                    let text = fs::read_to_string(&path)
+                        .map_err(|e| DefinitionError::Io(path.clone(), e))?;
+                    report::compile(&text).map_err(|e| DefinitionError::Compile(path, e))?
+                },
+                _ => return Err(DefinitionError::InvalidDescriptor(index)),
+            };
+            hid = hid.with_class_descriptor(hid::ClassDescriptor::other(descriptor_type, data));
+        }
+
+        let primary_language = self.language
+            .map_or(language::ENGLISH, |l| LanguageCode::new(l.iso_code, l.hid_code));
+        let mut config = Configuration::new(
+            primary_language,
+            self.encoding.unwrap_or(encoding::UTF_8),
+            ProfileVersion::V1_1,
+            hid);
+        config.service_name = self.strings.service_name.map(Cow::Owned);
+        config.service_description = self.strings.service_description.map(Cow::Owned);
+        config.provider_name = self.strings.provider_name.map(Cow::Owned);
+        if let Some(identity) = self.identity {
+            config.vendor_id_source = identity.vendor_id_source;
+            config.vendor_id = Some(identity.vendor_id);
+            config.product_id = Some(identity.product_id);
+            config.product_version = identity.version;
+        }
+        Ok(config)
+    }
+}
+
+/// Read the contents of a definition file, and the directory its paths are relative to.
+pub(crate) fn read_definition_file(path: &Path) -> Result<(String, PathBuf)> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| DefinitionError::Io(path.to_path_buf(), e))?;
+    let base_dir = path.parent().map_or_else(PathBuf::new, Path::to_path_buf);
+    Ok((contents, base_dir))
+}
+
+#[cfg(feature = "toml")]
+impl Configuration {
+    /// Read a configuration from a TOML definition. Paths of descriptor files are relative to
+    /// the current directory.
+    pub fn from_toml_str(toml: &str) -> Result<Self> {
+        let definition: Definition = toml::from_str(toml)
+            .map_err(|e| DefinitionError::Syntax(e.to_string()))?;
+        definition.into_configuration(Path::new(""))
+    }
+
+    /// Read a configuration from the TOML definition file at the given path. Paths of descriptor
+    /// files are relative to the directory of the definition.
+    pub fn from_toml_path(path: impl AsRef<Path>) -> Result<Self> {
+        let (contents, base_dir) = read_definition_file(path.as_ref())?;
+        let definition: Definition = toml::from_str(&contents)
+            .map_err(|e| DefinitionError::Syntax(e.to_string()))?;
+        definition.into_configuration(&base_dir)
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl Configuration {
+    /// Read a configuration from a YAML definition. Paths of descriptor files are relative to
+    /// the current directory.
+    pub fn from_yaml_str(yaml: &str) -> Result<Self> {
+        let definition: Definition = serde_yaml::from_str(yaml)
+            .map_err(|e| DefinitionError::Syntax(e.to_string()))?;
+        definition.into_configuration(Path::new(""))
+    }
+
+    /// Read a configuration from the YAML definition file at the given path. Paths of descriptor
+    /// files are relative to the directory of the definition.
+    pub fn from_yaml_path(path: impl AsRef<Path>) -> Result<Self> {
+        let (contents, base_dir) = read_definition_file(path.as_ref())?;
+        let definition: Definition = serde_yaml::from_str(&contents)
+            .map_err(|e| DefinitionError::Syntax(e.to_string()))?;
+        definition.into_configuration(&base_dir)
+    }
+}
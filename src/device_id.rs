@@ -0,0 +1,376 @@
+//! Device Identification (PnP Information) records.
+//!
+//! Most HID peripherals publish a Device ID record alongside the HID record, giving the vendor
+//! and product IDs hosts use to select drivers and quirks. The record is described in the
+//! Bluetooth Device ID profile, version 1.3.
+
+use sdp_xml::Tag;
+use sdp_xml_reader::parse_sdp_xml;
+use std::borrow::Cow;
+use std::io::{self, Write};
+use uuid::Uuid;
+
+use crate::Configuration;
+use crate::attribute::*;
+use crate::data_element::{decode_record, encode_element};
+use crate::from_sdp::{Error, ParseOptions, Result, read_attribute};
+use crate::ids::UuidExt;
+use crate::compat::normalize_sdp_xml;
+use crate::to_sdp::SerializeOptions;
+use crate::to_xml;
+
+/// IDs of the attributes specific to Device ID records.
+pub mod attribute_id {
+    pub const SPECIFICATION_ID: u16 = 0x0200;
+    pub const VENDOR_ID: u16 = 0x0201;
+    pub const PRODUCT_ID: u16 = 0x0202;
+    pub const VERSION: u16 = 0x0203;
+    pub const PRIMARY_RECORD: u16 = 0x0204;
+    pub const VENDOR_ID_SOURCE: u16 = 0x0205;
+}
+
+/// Organizations assigning vendor IDs.
+pub mod vendor_id_source {
+    /// Bluetooth SIG assigned company identifier.
+    pub const BLUETOOTH: u16 = 0x0001;
+    /// USB Implementers Forum assigned vendor ID.
+    pub const USB: u16 = 0x0002;
+}
+
+/// Version 1.3 of the Device ID specification.
+pub const SPECIFICATION_VERSION: u16 = 0x0103;
+
+/// UUID of the PnP Information service class and profile.
+const PNP_INFORMATION: u16 = 0x1200;
+/// UUIDs and PSM of the SDP protocol, over which the record is served.
+const L2CAP_PROTOCOL: u16 = 0x0100;
+const SDP_PROTOCOL: u16 = 0x0001;
+const SDP_PSM: u16 = 0x0001;
+
+// Configuration for a Device ID record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DeviceIdConfiguration {
+    /// Handle of the service record, if the SDP server requires it to be present.
+    pub service_record_handle: Option<u32>,
+
+    /// Version of the Device ID specification, such as 0x0103 for 1.3.
+    pub specification_id: u16,
+
+    /// Organization which assigned the vendor ID, such as `vendor_id_source::USB`.
+    pub vendor_id_source: u16,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// Version of the product, as 0xJJMN for version JJ.M.N.
+    pub version: u16,
+
+    /// Whether this is the primary record of a device publishing several Device ID records.
+    pub primary_record: bool,
+
+    pub service_description: Option<Cow<'static, str>>,
+    pub documentation_url: Option<Cow<'static, str>>,
+    pub client_executable_url: Option<Cow<'static, str>>,
+}
+
+impl DeviceIdConfiguration {
+    /// Create a primary record for the given vendor, product and version, implementing the
+    /// current version of the specification.
+    pub fn new(vendor_id_source: u16, vendor_id: u16, product_id: u16, version: u16) -> Self {
+        DeviceIdConfiguration {
+            service_record_handle: None,
+            specification_id: SPECIFICATION_VERSION,
+            vendor_id_source,
+            vendor_id,
+            product_id,
+            version,
+            primary_record: true,
+            service_description: None,
+            documentation_url: None,
+            client_executable_url: None,
+        }
+    }
+
+    /// Create the SDP record for this configuration.
+    pub fn to_sdp_tag(&self) -> Tag {
+        self.clone().into_sdp_tag()
+    }
+
+    /// Create the SDP record for this configuration, moving its strings into the record instead
+    /// of copying them.
+    pub fn into_sdp_tag(self) -> Tag {
+        let pnp_information = Uuid::from_u16(PNP_INFORMATION);
+        let mut attributes = Vec::new();
+
+        // Add the service record handle, if it has been given.
+
+        if let Some(handle) = self.service_record_handle {
+            attributes.push(ServiceRecordHandle(handle).into_attribute());
+        }
+
+        // Add service class ID list attribute
+
+        attributes.push(ServiceClassIdList(vec![pnp_information]).into_attribute());
+
+        // Add protocol descriptor list (L2CAP:SDP -> SDP)
+
+        attributes.push(Tag::attribute(ProtocolDescriptorList::ATTRIBUTE_ID, Tag::Sequence(vec![
+            Tag::Sequence(vec![Tag::Uuid(Uuid::from_u16(L2CAP_PROTOCOL)), Tag::UInt16(SDP_PSM)]),
+            Tag::Sequence(vec![Tag::Uuid(Uuid::from_u16(SDP_PROTOCOL))]),
+        ])));
+
+        // Add browse group list
+
+        attributes.push(BrowseGroupList::public().into_attribute());
+
+        // Add profile descriptor list
+
+        attributes.push(BluetoothProfileDescriptorList(
+            vec![(pnp_information, self.specification_id)]).into_attribute());
+
+        // Add the documentation and client executable URLs, if they have been given.
+
+        if let Some(url) = self.documentation_url {
+            attributes.push(DocumentationUrl(url.into_owned()).into_attribute());
+        }
+
+        if let Some(url) = self.client_executable_url {
+            attributes.push(ClientExecutableUrl(url.into_owned()).into_attribute());
+        }
+
+        // Add the service description, if it has been given.
+
+        if let Some(service_description) = self.service_description {
+            attributes.push(ServiceDescription(service_description.into_owned()).into_attribute());
+        }
+
+        // Add the Device ID attributes.
+
+        attributes.push(DeviceIdSpecificationId(self.specification_id).into_attribute());
+        attributes.push(DeviceIdVendorId(self.vendor_id).into_attribute());
+        attributes.push(DeviceIdProductId(self.product_id).into_attribute());
+        attributes.push(DeviceIdVersion(self.version).into_attribute());
+        attributes.push(DeviceIdPrimaryRecord(self.primary_record).into_attribute());
+        attributes.push(DeviceIdVendorIdSource(self.vendor_id_source).into_attribute());
+
+        Tag::record(attributes)
+    }
+
+    /// Encode the configuration as a binary SDP record.
+    pub fn to_sdp_bytes(&self) -> Vec<u8> {
+        encode_element(&self.to_sdp_tag())
+    }
+
+    /// Return the configuration as an SDP XML document, using the given options.
+    pub fn to_sdp_xml_string_with_options(&self, options: &SerializeOptions) -> String {
+        to_xml::to_sdp_xml_string(&self.to_sdp_tag(), options.pretty)
+    }
+
+    /// Return the configuration as an SDP XML document, as expected by BlueZ.
+    pub fn to_sdp_xml_string(&self) -> String {
+        self.to_sdp_xml_string_with_options(&SerializeOptions::default())
+    }
+
+    /// Write the configuration to the given writer as an SDP XML document.
+    pub fn to_sdp_xml_writer<W: Write>(&self, writer: W) -> io::Result<()> {
+        to_xml::write_sdp_xml(&self.to_sdp_tag(), writer, false)
+    }
+
+    /// Read a configuration from SDP XML.
+    pub fn from_sdp_xml(xml: &[u8]) -> Result<Self> {
+        Self::from_sdp_xml_with_options(xml, ParseOptions::default())
+    }
+
+    /// Read a configuration from SDP XML, using the given options.
+    pub fn from_sdp_xml_with_options(xml: &[u8], options: ParseOptions) -> Result<Self> {
+        let maybe_record = if options.compatibility {
+            parse_sdp_xml(&normalize_sdp_xml(xml))
+        } else {
+            parse_sdp_xml(xml)
+        }.map_err(Error::XmlParseError)?;
+        Self::from_sdp_tag(maybe_record)
+    }
+
+    /// Read a configuration from a binary SDP record.
+    pub fn from_sdp_bytes(data: &[u8]) -> Result<Self> {
+        Self::from_sdp_tag(decode_record(data)?)
+    }
+
+    /// Read a configuration from an already-parsed SDP record.
+    ///
+    /// The record must list the PnP Information service class. Attributes other than those
+    /// stored in the configuration are ignored.
+    pub fn from_sdp_tag(maybe_record: Tag) -> Result<Self> {
+        let maybe_attributes = match maybe_record {
+            Tag::Record(attributes) => attributes,
+            _ => {
+                return Err(Error::ExpectedRecord(maybe_record));
+            },
+        };
+
+        let mut service_record_handle = None;
+        let mut service_class_id_list = None;
+        let mut documentation_url = None;
+        let mut client_executable_url = None;
+        let mut service_description = None;
+        let mut specification_id = None;
+        let mut vendor_id = None;
+        let mut product_id = None;
+        let mut version = None;
+        let mut primary_record = None;
+        let mut vendor_id_source = None;
+
+        for tag in maybe_attributes {
+            let (id, child) = match tag {
+                Tag::Attribute(id, child) => (id, *child),
+                _ => {
+                    return Err(Error::ExpectedAttribute(tag));
+                },
+            };
+            match id {
+                ServiceRecordHandle::ATTRIBUTE_ID =>
+                    read_attribute(&mut service_record_handle, child)?,
+                ServiceClassIdList::ATTRIBUTE_ID =>
+                    read_attribute(&mut service_class_id_list, child)?,
+                DocumentationUrl::ATTRIBUTE_ID =>
+                    read_attribute(&mut documentation_url, child)?,
+                ClientExecutableUrl::ATTRIBUTE_ID =>
+                    read_attribute(&mut client_executable_url, child)?,
+                ServiceDescription::ATTRIBUTE_ID =>
+                    read_attribute(&mut service_description, child)?,
+                DeviceIdSpecificationId::ATTRIBUTE_ID =>
+                    read_attribute(&mut specification_id, child)?,
+                DeviceIdVendorId::ATTRIBUTE_ID =>
+                    read_attribute(&mut vendor_id, child)?,
+                DeviceIdProductId::ATTRIBUTE_ID =>
+                    read_attribute(&mut product_id, child)?,
+                DeviceIdVersion::ATTRIBUTE_ID =>
+                    read_attribute(&mut version, child)?,
+                DeviceIdPrimaryRecord::ATTRIBUTE_ID =>
+                    read_attribute(&mut primary_record, child)?,
+                DeviceIdVendorIdSource::ATTRIBUTE_ID =>
+                    read_attribute(&mut vendor_id_source, child)?,
+                // Ignore other attributes.
+                _ => (),
+            }
+        }
+
+        // The record must be a Device ID record, not any other record using the same IDs.
+        let pnp_information = Uuid::from_u16(PNP_INFORMATION);
+        let ServiceClassIdList(service_classes) = service_class_id_list
+            .ok_or(Error::MissingRecord("service class ID list"))?;
+        if !service_classes.contains(&pnp_information) {
+            return match service_classes.first() {
+                Some(actual) => Err(Error::UnexpectedUuid {
+                    attribute: ServiceClassIdList::ATTRIBUTE_ID,
+                    expected: pnp_information,
+                    actual: *actual,
+                }),
+                None => Err(Error::MissingRecord("PnP information service class")),
+            };
+        }
+
+        Ok(DeviceIdConfiguration {
+            service_record_handle: service_record_handle.map(|a| a.0),
+            specification_id: specification_id
+                .ok_or(Error::MissingRecord("specification ID"))?.0,
+            vendor_id_source: vendor_id_source
+                .ok_or(Error::MissingRecord("vendor ID source"))?.0,
+            vendor_id: vendor_id
+                .ok_or(Error::MissingRecord("vendor ID"))?.0,
+            product_id: product_id
+                .ok_or(Error::MissingRecord("product ID"))?.0,
+            version: version
+                .ok_or(Error::MissingRecord("version"))?.0,
+            primary_record: primary_record
+                .ok_or(Error::MissingRecord("primary record"))?.0,
+            service_description: service_description.map(|a| a.0.into()),
+            documentation_url: documentation_url.map(|a| a.0.into()),
+            client_executable_url: client_executable_url.map(|a| a.0.into()),
+        })
+    }
+}
+
+/// Well-known devices, by vendor ID source, vendor ID and product ID. Hosts apply
+/// device-specific drivers and quirks to these, so using their IDs is usually a mistake unless
+/// the device is deliberately emulated.
+const KNOWN_DEVICES: &[(u16, u16, u16, &str)] = &[
+    (vendor_id_source::USB, 0x045e, 0x02e0, "Microsoft Xbox Wireless Controller"),
+    (vendor_id_source::USB, 0x045e, 0x02fd, "Microsoft Xbox Wireless Controller"),
+    (vendor_id_source::USB, 0x054c, 0x05c4, "Sony DualShock 4"),
+    (vendor_id_source::USB, 0x054c, 0x09cc, "Sony DualShock 4"),
+    (vendor_id_source::USB, 0x054c, 0x0ce6, "Sony DualSense"),
+    (vendor_id_source::USB, 0x057e, 0x2006, "Nintendo Joy-Con (L)"),
+    (vendor_id_source::USB, 0x057e, 0x2007, "Nintendo Joy-Con (R)"),
+    (vendor_id_source::USB, 0x057e, 0x2009, "Nintendo Switch Pro Controller"),
+    (vendor_id_source::USB, 0x05ac, 0x0267, "Apple Magic Keyboard"),
+    (vendor_id_source::USB, 0x05ac, 0x0269, "Apple Magic Mouse 2"),
+    (vendor_id_source::USB, 0x05ac, 0x0265, "Apple Magic Trackpad 2"),
+];
+
+/// Return the name of the well-known device with the given IDs, if any.
+pub fn known_device(vendor_id_source: u16, vendor_id: u16, product_id: u16) -> Option<&'static str> {
+    KNOWN_DEVICES.iter()
+        .find(|(source, vendor, product, _)| {
+            *source == vendor_id_source && *vendor == vendor_id && *product == product_id
+        })
+        .map(|(_, _, _, name)| *name)
+}
+
+/// Warning about the identity of a device which is likely a mistake.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdentityWarning {
+    /// The IDs are those of a well-known device, which hosts may treat specially.
+    KnownDevice(&'static str),
+    /// Only one of the vendor and product IDs is given, so no Device ID record is generated.
+    IncompleteIdentity,
+}
+
+impl std::fmt::Display for IdentityWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::KnownDevice(name) =>
+                write!(f, "vendor and product IDs are those of {}", name),
+            Self::IncompleteIdentity =>
+                write!(f, "vendor and product IDs must be given together"),
+        }
+    }
+}
+
+impl Configuration {
+    /// Create the Device ID record for this configuration, if its vendor and product IDs are
+    /// given.
+    pub fn to_device_id_configuration(&self) -> Option<DeviceIdConfiguration> {
+        let mut device_id = DeviceIdConfiguration::new(
+            self.vendor_id_source.unwrap_or(vendor_id_source::USB),
+            self.vendor_id?,
+            self.product_id?,
+            self.product_version.unwrap_or(0));
+        device_id.service_description = self.service_description.clone();
+        device_id.documentation_url = self.documentation_url.clone();
+        Some(device_id)
+    }
+
+    /// Set the identity of the device from its Device ID record.
+    pub fn apply_device_id(&mut self, device_id: &DeviceIdConfiguration) {
+        self.vendor_id_source = Some(device_id.vendor_id_source);
+        self.vendor_id = Some(device_id.vendor_id);
+        self.product_id = Some(device_id.product_id);
+        self.product_version = Some(device_id.version);
+    }
+
+    /// Return warnings about the identity of the device.
+    pub fn identity_warnings(&self) -> Vec<IdentityWarning> {
+        let mut warnings = Vec::new();
+        match (self.vendor_id, self.product_id) {
+            (Some(vendor_id), Some(product_id)) => {
+                let source = self.vendor_id_source.unwrap_or(vendor_id_source::USB);
+                if let Some(name) = known_device(source, vendor_id, product_id) {
+                    warnings.push(IdentityWarning::KnownDevice(name));
+                }
+            },
+            (None, None) => (),
+            _ => warnings.push(IdentityWarning::IncompleteIdentity),
+        }
+        warnings
+    }
+}
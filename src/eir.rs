@@ -0,0 +1,74 @@
+//! Extended Inquiry Response data for HID devices.
+//!
+//! Devices answer inquiries with an Extended Inquiry Response, which lets hosts show the name
+//! and kind of a device without connecting to it. Most stacks build it themselves, but where the
+//! application supplies it, it should list the same services and class as the SDP records.
+
+use crate::advertising::ad_type;
+use crate::Configuration;
+
+/// Longest Extended Inquiry Response.
+pub const MAX_EIR_LEN: usize = 240;
+
+/// 16-bit UUIDs of the service classes of the records this crate creates.
+pub mod uuid16 {
+    pub const HUMAN_INTERFACE_DEVICE: u16 = 0x1124;
+    pub const PNP_INFORMATION: u16 = 0x1200;
+}
+
+/// Types of Extended Inquiry Response structures besides those shared with advertising data.
+pub mod eir_type {
+    pub const CLASS_OF_DEVICE: u8 = 0x0d;
+    pub const DEVICE_ID: u8 = 0x10;
+}
+
+/// Append a structure of the given type, if it fits.
+fn push_structure(out: &mut Vec<u8>, data_type: u8, data: &[u8]) {
+    if out.len() + 2 + data.len() <= MAX_EIR_LEN {
+        out.push(1 + data.len() as u8);
+        out.push(data_type);
+        out.extend_from_slice(data);
+    }
+}
+
+impl Configuration {
+    /// Return the Extended Inquiry Response of the device, consistent with its records: the
+    /// Class of Device, the service classes of the HID record and of the Device ID record if
+    /// there is one, the Device ID, and the service name as the local name.
+    ///
+    /// A name too long to fit is shortened, and marked so.
+    pub fn eir_data(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        let class = u32::from(self.class_of_device()).to_le_bytes();
+        push_structure(&mut out, eir_type::CLASS_OF_DEVICE, &class[..3]);
+
+        let device_id = self.to_device_id_configuration();
+        let mut uuids = uuid16::HUMAN_INTERFACE_DEVICE.to_le_bytes().to_vec();
+        if device_id.is_some() {
+            uuids.extend_from_slice(&uuid16::PNP_INFORMATION.to_le_bytes());
+        }
+        push_structure(&mut out, ad_type::COMPLETE_UUID16_LIST, &uuids);
+
+        if let Some(device_id) = device_id {
+            let data: Vec<u8> = [
+                device_id.vendor_id_source,
+                device_id.vendor_id,
+                device_id.product_id,
+                device_id.version,
+            ].iter().flat_map(|value| value.to_le_bytes()).collect();
+            push_structure(&mut out, eir_type::DEVICE_ID, &data);
+        }
+
+        if let Some(name) = &self.service_name {
+            let room = MAX_EIR_LEN.saturating_sub(out.len() + 2);
+            if name.len() <= room {
+                push_structure(&mut out, ad_type::COMPLETE_LOCAL_NAME, name.as_bytes());
+            } else {
+                let end = (0..=room).rev().find(|&end| name.is_char_boundary(end)).unwrap_or(0);
+                push_structure(&mut out, ad_type::SHORTENED_LOCAL_NAME, &name.as_bytes()[..end]);
+            }
+        }
+        out
+    }
+}
@@ -1,11 +1,17 @@
-use bluer::{Uuid, UuidExt};
 use sdp_xml::Tag;
 use sdp_xml_reader::{self, parse_sdp_xml};
-use hid_device_id::bluetooth::attribute_id;
+use hid_device_id::bluetooth::psm;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fmt::{self, Display, Formatter};
+use uuid::Uuid;
 
-use crate::{Configuration, LanguageCode};
-use crate::hid::{self, ClassDescriptor, LanguageBase};
+use crate::{Configuration, LanguageCode, ProfileVersion, ServiceStrings, TransportConfiguration};
+use crate::attribute::*;
+use crate::compat::normalize_sdp_xml;
+use crate::data_element::decode_record;
+use crate::hid::{self, DescriptorForm};
+use crate::ids::ServiceClass;
 
 /// Error type for reading configurations
 #[derive(Debug)]
@@ -17,8 +23,10 @@ pub enum Error {
     ExpectedBoolean(u16, Tag),
     ExpectedUInt8(u16, Tag),
     ExpectedUInt16(u16, Tag),
+    ExpectedUInt32(u16, Tag),
     ExpectedText(u16, Tag),
     ExpectedUuid(u16, Tag),
+    ExpectedUrl(u16, Tag),
     UnexpectedSequenceLen { attribute: u16, expected: usize, actual: usize },
     UnexpectedUuid { attribute: u16, expected: Uuid, actual: Uuid },
     DuplicateValue(u16),
@@ -27,6 +35,9 @@ pub enum Error {
     DuplicateDescriptorText,
     MissingRecord(&'static str),
     UnexpectedTag(Tag),
+    InvalidDataElement(usize),
+    UnexpectedEndOfData(usize),
+    TrailingData(usize),
 }
 
 impl Display for Error {
@@ -50,10 +61,15 @@ impl Display for Error {
             Self::ExpectedUInt16(attribute, tag) =>
                 write!(f, "in attribute 0x{:04x}: expected uint16, received {}",
                        attribute, tag.name()),
+            Self::ExpectedUInt32(attribute, tag) =>
+                write!(f, "in attribute 0x{:04x}: expected uint32, received {}",
+                       attribute, tag.name()),
             Self::ExpectedText(attribute, tag) =>
                 write!(f, "in attribute 0x{:04x}: expected text, received {}", attribute, tag.name()),
             Self::ExpectedUuid(attribute, tag) =>
                 write!(f, "in attribute 0x{:04x}: expected uuid, received {}", attribute, tag.name()),
+            Self::ExpectedUrl(attribute, tag) =>
+                write!(f, "in attribute 0x{:04x}: expected url, received {}", attribute, tag.name()),
             Self::UnexpectedSequenceLen { attribute, expected, actual } =>
                 write!(f, "in attribute 0x{:04x}: expected sequence of length {}, received sequence of length {}",
                        attribute, expected, actual),
@@ -72,6 +88,12 @@ impl Display for Error {
                 write!(f, "missing record {}", name),
             Self::UnexpectedTag(tag) =>
                 write!(f, "unexpected tag {}", tag.name()),
+            Self::InvalidDataElement(offset) =>
+                write!(f, "at offset {}: invalid data element", offset),
+            Self::UnexpectedEndOfData(offset) =>
+                write!(f, "at offset {}: unexpected end of data", offset),
+            Self::TrailingData(offset) =>
+                write!(f, "at offset {}: unexpected data after end of element", offset),
         }
     }
 }
@@ -80,7 +102,7 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 
 /// Match the given tag as a sequence, or return an error.
-fn expect_boolean(attribute: u16, tag: Tag) -> Result<bool> {
+pub(crate) fn expect_boolean(attribute: u16, tag: Tag) -> Result<bool> {
     match tag {
         Tag::Boolean(value) => Ok(value),
         _ => Err(Error::ExpectedBoolean(attribute, tag)),
@@ -88,7 +110,7 @@ fn expect_boolean(attribute: u16, tag: Tag) -> Result<bool> {
 }
 
 /// Match the given tag as a UInt8, or return an error.
-fn expect_uint8(attribute: u16, tag: Tag) -> Result<u8> {
+pub(crate) fn expect_uint8(attribute: u16, tag: Tag) -> Result<u8> {
     match tag {
         Tag::UInt8(value) => Ok(value),
         _ => Err(Error::ExpectedUInt8(attribute, tag)),
@@ -96,24 +118,40 @@ fn expect_uint8(attribute: u16, tag: Tag) -> Result<u8> {
 }
 
 /// Match the given tag as a UInt16, or return an error.
-fn expect_uint16(attribute: u16, tag: Tag) -> Result<u16> {
+pub(crate) fn expect_uint16(attribute: u16, tag: Tag) -> Result<u16> {
     match tag {
         Tag::UInt16(value) => Ok(value),
         _ => Err(Error::ExpectedUInt16(attribute, tag)),
     }
 }
 
+/// Match the given tag as a UInt32, or return an error.
+pub(crate) fn expect_uint32(attribute: u16, tag: Tag) -> Result<u32> {
+    match tag {
+        Tag::UInt32(value) => Ok(value),
+        _ => Err(Error::ExpectedUInt32(attribute, tag)),
+    }
+}
+
 /// Match the given tag as Text, or return an error.
-fn expect_text(attribute: u16, tag: Tag) -> Result<String> {
+pub(crate) fn expect_text(attribute: u16, tag: Tag) -> Result<String> {
     match tag {
         Tag::Text(value) => Ok(value),
         _ => Err(Error::ExpectedText(attribute, tag)),
     }
 }
 
+/// Match the given tag as a URL, or return an error.
+pub(crate) fn expect_url(attribute: u16, tag: Tag) -> Result<String> {
+    match tag {
+        Tag::Url(value) => Ok(value),
+        _ => Err(Error::ExpectedUrl(attribute, tag)),
+    }
+}
+
 
 /// Match the given tag as a sequence, or return an error.
-fn expect_sequence(attribute: u16, tag: Tag) -> Result<Vec<Tag>> {
+pub(crate) fn expect_sequence(attribute: u16, tag: Tag) -> Result<Vec<Tag>> {
     match tag {
         Tag::Sequence(children) => Ok(children),
         _ => Err(Error::ExpectedSequence(attribute, tag)),
@@ -121,7 +159,7 @@ fn expect_sequence(attribute: u16, tag: Tag) -> Result<Vec<Tag>> {
 }
 
 /// Return an error if the sequence does not have the given length.
-fn expect_len(attribute: u16, sequence: &Vec<Tag>, len: usize) -> Result<()> {
+pub(crate) fn expect_len(attribute: u16, sequence: &Vec<Tag>, len: usize) -> Result<()> {
     if sequence.len() != len {
         Err(Error::UnexpectedSequenceLen {
             attribute,
@@ -133,24 +171,17 @@ fn expect_len(attribute: u16, sequence: &Vec<Tag>, len: usize) -> Result<()> {
     }
 }
 
-/// Match the given tag as the given Uuid, or return an error.
-fn expect_uuid(attribute: u16, tag: Tag, expected: Uuid) -> Result<()> {
+/// Match the given tag as any Uuid, or return an error.
+pub(crate) fn expect_any_uuid(attribute: u16, tag: Tag) -> Result<Uuid> {
     match tag {
-        Tag::Uuid(actual) => {
-            if expected == actual {
-                Ok(())
-            } else {
-                Err(Error::UnexpectedUuid { attribute, expected, actual })
-            }
-        },
+        Tag::Uuid(uuid) => Ok(uuid),
         _ => Err(Error::ExpectedUuid(attribute, tag)),
     }
 }
 
-
 /// Initialize the given attribute with the given value, if the attribute has no prior value. If
 /// the attribute is already initialized, return an error using the given attribute ID and name.
-fn try_initialize_attribute<T>(
+pub(crate) fn try_initialize_attribute<T>(
     attribute: &mut Option<T>,
     value: T,
     attribute_id: u16,
@@ -164,7 +195,7 @@ fn try_initialize_attribute<T>(
 }
 
 /// Initialize an option with the given value, if it is uninitialized. Return an error otherwise.
-fn try_initialize<T>(attribute: u16, dest: &mut Option<T>, value: T) -> Result<()> {
+pub(crate) fn try_initialize<T>(attribute: u16, dest: &mut Option<T>, value: T) -> Result<()> {
     if dest.is_some() {
         return Err(Error::DuplicateValue(attribute));
     }
@@ -172,36 +203,102 @@ fn try_initialize<T>(attribute: u16, dest: &mut Option<T>, value: T) -> Result<(
     Ok(())
 }
 
+/// Read an attribute from the given tag into an uninitialized option. If the option has already
+/// been initialized, the attribute is duplicated, and an error is returned.
+pub(crate) fn read_attribute<A: SdpAttribute>(dest: &mut Option<A>, tag: Tag) -> Result<()> {
+    let value = A::from_tag(tag)?;
+    try_initialize_attribute(dest, value, A::ATTRIBUTE_ID, A::NAME)
+}
+
+/// Options controlling how SDP XML is read.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParseOptions {
+    /// Normalize the dialects produced by `sdptool` and other tools before parsing, instead of
+    /// requiring the exact format written by BlueZ's sdpd.
+    pub compatibility: bool,
+    /// Record every class descriptor as raw text, discarding the form used in the source
+    /// document.
+    pub normalize_descriptors: bool,
+}
+
+impl ParseOptions {
+    /// Options accepting the dialects of common tools.
+    pub fn compatible() -> Self {
+        ParseOptions { compatibility: true, ..Self::default() }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct PartialConfiguration {
-    primary_language: Option<u16>,
-    encoding: Option<u16>,
-    service_name: Option<String>,
-    service_description: Option<String>,
-    provider_name: Option<String>,
-    version: Option<u16>,
+    service_record_handle: Option<ServiceRecordHandle>,
+    service_class_id_list: Option<ServiceClassIdList>,
+    service_record_state: Option<ServiceRecordState>,
+    protocol_descriptor_list: Option<ProtocolDescriptorList>,
+    browse_group_list: Option<BrowseGroupList>,
+    language_base_attribute_id_list: Option<LanguageBaseAttributeIdList>,
+    service_info_time_to_live: Option<ServiceInfoTimeToLive>,
+    service_availability: Option<ServiceAvailability>,
+    bluetooth_profile_descriptor_list: Option<BluetoothProfileDescriptorList>,
+    documentation_url: Option<DocumentationUrl>,
+    client_executable_url: Option<ClientExecutableUrl>,
+    icon_url: Option<IconUrl>,
+    additional_protocol_descriptor_lists: Option<AdditionalProtocolDescriptorLists>,
+    service_name: Option<ServiceName>,
+    service_description: Option<ServiceDescription>,
+    provider_name: Option<ProviderName>,
+    // Text attributes outside the known attribute ranges, which may be strings for additional
+    // languages.
+    offset_strings: BTreeMap<u16, String>,
+    // Attributes not otherwise understood.
+    raw_attributes: Vec<(u16, Tag)>,
 
-    hid_device_subclass: Option<u8>,
-    hid_country_code: Option<u8>,
-    hid_virtual_cable: Option<bool>,
-    hid_reconnect_initiate: Option<bool>,
-    hid_descriptor_list: Vec<ClassDescriptor>,
-    hid_lang_base_id_list: Vec<LanguageBase>,
-    hid_battery_power: Option<bool>,
-    hid_remote_wake: Option<bool>,
-    hid_supervision_timeout: Option<u16>,
-    hid_normally_connectable: Option<bool>,
-    hid_boot_device: Option<bool>,
-    hid_ssr_host_max_latency: Option<u16>,
-    hid_ssr_host_min_timeout: Option<u16>,
+    hid_device_subclass: Option<HidDeviceSubclass>,
+    hid_country_code: Option<HidCountryCode>,
+    hid_virtual_cable: Option<HidVirtualCable>,
+    hid_reconnect_initiate: Option<HidReconnectInitiate>,
+    hid_descriptor_list: Option<HidDescriptorList>,
+    hid_lang_base_id_list: Option<HidLangBaseAttributeIdList>,
+    hid_battery_power: Option<HidBatteryPower>,
+    hid_remote_wake: Option<HidRemoteWake>,
+    hid_supervision_timeout: Option<HidSupervisionTimeout>,
+    hid_normally_connectable: Option<HidNormallyConnectable>,
+    hid_boot_device: Option<HidBootDevice>,
+    hid_ssr_host_max_latency: Option<HidSsrHostMaxLatency>,
+    hid_ssr_host_min_timeout: Option<HidSsrHostMinTimeout>,
 }
 
 impl PartialConfiguration {
     pub fn from_sdp_xml(xml: &[u8]) -> Result<Self> {
+        Self::from_sdp_xml_with_options(xml, ParseOptions::default())
+    }
+
+    /// Parse a configuration from SDP XML, using the given options.
+    pub fn from_sdp_xml_with_options(xml: &[u8], options: ParseOptions) -> Result<Self> {
+        let maybe_record = if options.compatibility {
+            parse_sdp_xml(&normalize_sdp_xml(xml))
+        } else {
+            parse_sdp_xml(xml)
+        }.map_err(|e| Error::XmlParseError(e))?;
+        let mut partial_configuration = Self::from_sdp_tag(maybe_record)?;
+        if options.normalize_descriptors {
+            if let Some(HidDescriptorList(descriptors)) = &mut partial_configuration.hid_descriptor_list {
+                for descriptor in descriptors {
+                    descriptor.2 = DescriptorForm::RawText;
+                }
+            }
+        }
+        Ok(partial_configuration)
+    }
+
+    /// Parse a configuration from a binary SDP record, as sent in SDP responses.
+    pub fn from_sdp_bytes(data: &[u8]) -> Result<Self> {
+        Self::from_sdp_tag(decode_record(data)?)
+    }
+
+    /// Read a configuration from an already-parsed SDP record.
+    pub fn from_sdp_tag(maybe_record: Tag) -> Result<Self> {
         let mut partial_configuration = Self::default();
 
-        let maybe_record = parse_sdp_xml(xml)
-            .map_err(|e| Error::XmlParseError(e))?;
         let maybe_attributes = match maybe_record {
             Tag::Record(attributes) => attributes,
             _ => {
@@ -222,178 +319,79 @@ impl PartialConfiguration {
                 return Err(e);
             },
         };
+        let p = &mut partial_configuration;
         for (id, child) in attributes {
             match id {
-                attribute_id::LANGUAGE_BASE_ATTRIBUTE_ID_LIST => {
-                    let mut language_base_attribute_id = expect_sequence(id, child)?;
-                    expect_len(id, &language_base_attribute_id, 3)?;
-                    let lang = expect_uint16(id, language_base_attribute_id.remove(0))?;
-                    let encoding = expect_uint16(id, language_base_attribute_id.remove(0))?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.primary_language, lang,
-                        id, "Language Base Attribute ID List")?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.encoding, encoding,
-                        id, "Language Base Attribute ID List")?;
-                },
-                attribute_id::SERVICE_NAME => {
-                    let text = expect_text(id, child)?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.service_name, text,
-                        id, "Service Name")?;
-                    // Duplicate attribute "Service Name" (0x1124)
-                },
-                attribute_id::SERVICE_DESCRIPTION => {
-                    let text = expect_text(id, child)?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.service_description, text,
-                        id, "Service Description")?;
-                },
-                attribute_id::PROVIDER_NAME => {
-                    let text = expect_text(id, child)?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.provider_name, text,
-                        id, "Provider Name")?;
-                },
-                attribute_id::BLUETOOTH_PROFILE_DESCRIPTOR_LIST => {
-                    let mut seq_1_children = expect_sequence(id, child)?;
-                    expect_len(id, &seq_1_children, 1)?;
-                    let mut seq_2_children = expect_sequence(id, seq_1_children.remove(0))?;
-                    expect_len(id, &seq_2_children, 2)?;
-                    // TODO
-                    // Should be a sequence containing a sequence containing
-                    // uuid = 1124
-                    // value = some version, like 0x0101.
-                    expect_uuid(id, seq_2_children.remove(0), Uuid::from_u16(0x1124))?;
-                    let version = expect_uint16(id, seq_2_children.remove(0))?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.version, version,
-                        id, "Profile Descriptor List")?;
-                },
-                attribute_id::hid::HID_DEVICE_SUBCLASS => {
-                    let value = expect_uint8(id, child)?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.hid_device_subclass, value,
-                        id, "HID Device Subclass")?;
-                },
-                attribute_id::hid::HID_COUNTRY_CODE => {
-                    let value = expect_uint8(id, child)?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.hid_country_code, value,
-                        id, "HID Country Code")?;
-                },
-                attribute_id::hid::HID_VIRTUAL_CABLE => {
-                    let value = expect_boolean(id, child)?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.hid_virtual_cable, value,
-                        id, "HID Virtual Cable")?;
-                },
-                attribute_id::hid::HID_RECONNECT_INITIATE => {
-                    let value = expect_boolean(id, child)?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.hid_reconnect_initiate, value,
-                        id, "HID Reconnect Initiate")?;
-                },
-                attribute_id::hid::HID_DESCRIPTOR_LIST => {
-                    let maybe_descriptors = expect_sequence(id, child)?;
-                    for maybe_descriptor in maybe_descriptors {
-                        // Each descriptor is a sequence containing an ID (u8) and text.
-                        let descriptor = expect_sequence(id, maybe_descriptor)?;
-                        let mut descriptor_type = None;
-                        let mut descriptor_value = None;
-                        // Read each element in the descriptor, searching for an ID and descriptor
-                        // text.
-                        for element in descriptor {
-                            match element {
-                                Tag::UInt8(v) => {
-                                    try_initialize(id, &mut descriptor_type, v)
-                                        .map_err(|_| Error::DuplicateDescriptorId)?;
-                                },
-                                Tag::Text(v) => {
-                                    try_initialize(id, &mut descriptor_value, v.into_bytes())
-                                        .map_err(|_| Error::DuplicateDescriptorText)?;
-                                },
-                                Tag::RawText(v) => {
-                                    try_initialize(id, &mut descriptor_value, v)
-                                        .map_err(|_| Error::DuplicateDescriptorText)?;
-                                },
-                                _ => {
-                                    return Err(Error::UnexpectedTag(element));
-                                },
-                            };
-                        }
-                        // Convert the optional descriptor type and value into a concrete class
-                        // descriptor.
-                        let class_descriptor = match (descriptor_type, descriptor_value) {
-                            (Some(t), Some(v)) => ClassDescriptor(t, v),
-                            (None, _) => {
-                                return Err(Error::MissingRecord("descriptor id"));
-                            },
-                            (Some(_), None) => {
-                                return Err(Error::MissingRecord("descriptor value"));
-                            },
-                        };
-                        // Add the new class descriptor.
-                        partial_configuration.hid_descriptor_list.push(class_descriptor);
-                    }
-                },
-                attribute_id::hid::HID_LANG_BASE_ATTRIBUTE => {
-                    let lang_base_id_list = expect_sequence(id, child)?;
-                    for maybe_lang_base_id in lang_base_id_list {
-                        let mut lang_base_id = expect_sequence(id, maybe_lang_base_id)?;
-                        expect_len(id, &lang_base_id, 2)?;
-                        let lang = expect_uint16(id, lang_base_id.remove(0))?;
-                        let base = expect_uint16(id, lang_base_id.remove(0))?;
-                        partial_configuration.hid_lang_base_id_list.push(LanguageBase {
-                            language: lang,
-                            base,
-                        });
-                    }
-                },
-                attribute_id::hid::HID_BATTERY_POWER => {
-                    let value = expect_boolean(id, child)?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.hid_battery_power, value,
-                        id, "HID Battery Power")?;
-                },
-                attribute_id::hid::HID_REMOTE_WAKE => {
-                    let value = expect_boolean(id, child)?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.hid_remote_wake, value,
-                        id, "HID Remote Wake")?;
-                },
-                attribute_id::hid::HID_SUPERVISION_TIMEOUT => {
-                    let value = expect_uint16(id, child)?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.hid_supervision_timeout, value,
-                        id, "HID Supervision Timeout")?;
+                ServiceRecordHandle::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.service_record_handle, child)?,
+                ServiceClassIdList::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.service_class_id_list, child)?,
+                ServiceRecordState::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.service_record_state, child)?,
+                ProtocolDescriptorList::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.protocol_descriptor_list, child)?,
+                BrowseGroupList::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.browse_group_list, child)?,
+                LanguageBaseAttributeIdList::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.language_base_attribute_id_list, child)?,
+                ServiceInfoTimeToLive::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.service_info_time_to_live, child)?,
+                ServiceAvailability::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.service_availability, child)?,
+                BluetoothProfileDescriptorList::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.bluetooth_profile_descriptor_list, child)?,
+                DocumentationUrl::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.documentation_url, child)?,
+                ClientExecutableUrl::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.client_executable_url, child)?,
+                IconUrl::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.icon_url, child)?,
+                AdditionalProtocolDescriptorLists::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.additional_protocol_descriptor_lists, child)?,
+                ServiceName::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.service_name, child)?,
+                ServiceDescription::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.service_description, child)?,
+                ProviderName::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.provider_name, child)?,
+                HidDeviceSubclass::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.hid_device_subclass, child)?,
+                HidCountryCode::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.hid_country_code, child)?,
+                HidVirtualCable::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.hid_virtual_cable, child)?,
+                HidReconnectInitiate::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.hid_reconnect_initiate, child)?,
+                HidDescriptorList::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.hid_descriptor_list, child)?,
+                HidLangBaseAttributeIdList::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.hid_lang_base_id_list, child)?,
+                HidBatteryPower::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.hid_battery_power, child)?,
+                HidRemoteWake::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.hid_remote_wake, child)?,
+                HidSupervisionTimeout::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.hid_supervision_timeout, child)?,
+                HidNormallyConnectable::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.hid_normally_connectable, child)?,
+                HidBootDevice::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.hid_boot_device, child)?,
+                HidSsrHostMaxLatency::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.hid_ssr_host_max_latency, child)?,
+                HidSsrHostMinTimeout::ATTRIBUTE_ID =>
+                    read_attribute(&mut p.hid_ssr_host_min_timeout, child)?,
+                // These attributes are always generated on output.
+                HidParserVersion::ATTRIBUTE_ID | HidProfileVersion::ATTRIBUTE_ID => (),
+                // Keep other text attributes, in case they belong to an additional language, and
+                // keep the rest as raw attributes.
+                _ => match child {
+                    Tag::Text(text) => {
+                        p.offset_strings.insert(id, text);
+                    },
+                    _ => {
+                        p.raw_attributes.push((id, child));
+                    },
                 },
-                attribute_id::hid::HID_NORMALLY_CONNECTABLE => {
-                    let value = expect_boolean(id, child)?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.hid_normally_connectable, value,
-                        id, "HID Normally Connectable")?;
-                },
-                attribute_id::hid::HID_BOOT_DEVICE => {
-                    let value = expect_boolean(id, child)?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.hid_boot_device, value,
-                        id, "HID Boot Device")?;
-                },
-                attribute_id::hid::HID_SSR_HOST_MAX_LATENCY => {
-                    let value = expect_uint16(id, child)?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.hid_ssr_host_max_latency, value,
-                        id, "HID SSR Host Max Latency")?;
-                },
-                attribute_id::hid::HID_SSR_HOST_MIN_TIMEOUT => {
-                    let value = expect_uint16(id, child)?;
-                    try_initialize_attribute( 
-                        &mut partial_configuration.hid_ssr_host_min_timeout, value,
-                        id, "HID SSR Host Min Timeout")?;
-                },
-                // Ignore other attributes.
-                _ => (),
             }
         }
         Ok(partial_configuration)
@@ -405,42 +403,139 @@ impl TryFrom<PartialConfiguration> for Configuration {
 
     fn try_from(partial_configuration: PartialConfiguration) -> Result<Self> {
         // Parse primary language from base and HID configuration data.
-        let iso_code = partial_configuration.primary_language
+        let language_base = partial_configuration.language_base_attribute_id_list
             .ok_or(Error::MissingRecord("primary language"))?;
-        let hid_code = partial_configuration.hid_lang_base_id_list.first()
+        let HidLangBaseAttributeIdList(hid_lang_base_id_list) = partial_configuration.hid_lang_base_id_list
+            .unwrap_or(HidLangBaseAttributeIdList(Vec::new()));
+        let hid_code = hid_lang_base_id_list.first()
             .ok_or(Error::MissingRecord("HID language"))?
             .language;
+        // Collect the strings for each additional language.
+        let mut offset_strings = partial_configuration.offset_strings;
+        let mut localized_strings = BTreeMap::new();
+        for language_base in &hid_lang_base_id_list {
+            // The primary language's strings have already been read.
+            if language_base.base == 0x0100 {
+                continue;
+            }
+            let mut take_string = |offset: u16| {
+                language_base.base.checked_add(offset)
+                    .and_then(|id| offset_strings.remove(&id))
+                    .map(Cow::Owned)
+            };
+            let strings = ServiceStrings {
+                service_name: take_string(ServiceStrings::SERVICE_NAME_OFFSET),
+                service_description: take_string(ServiceStrings::SERVICE_DESCRIPTION_OFFSET),
+                provider_name: take_string(ServiceStrings::PROVIDER_NAME_OFFSET),
+            };
+            if strings.service_name.is_some()
+                    || strings.service_description.is_some()
+                    || strings.provider_name.is_some() {
+                localized_strings.insert(*language_base, strings);
+            }
+        }
+        // Text attributes which do not belong to any language are kept as raw attributes.
+        let mut raw_attributes = partial_configuration.raw_attributes;
+        raw_attributes.extend(offset_strings.into_iter()
+            .map(|(id, text)| (id, Tag::Text(text))));
+        raw_attributes.sort_by_key(|(id, _)| *id);
+        // A missing browse group list means the service is not browsable, and a list of only the
+        // public browse group is the default.
+        let public_browse_group = Uuid::from(ServiceClass::PublicBrowseGroup);
+        let browse_groups = match partial_configuration.browse_group_list {
+            None => Some(Vec::new()),
+            Some(BrowseGroupList(groups)) if groups == [public_browse_group] => None,
+            Some(BrowseGroupList(groups)) => Some(groups),
+        };
+        // The HID service class is generated on output, so only the others are kept.
+        let hid_service_class = Uuid::from(ServiceClass::Hid);
+        let additional_service_classes = partial_configuration.service_class_id_list
+            .map(|ServiceClassIdList(classes)| classes)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|class| *class != hid_service_class)
+            .collect();
+        // The HID profile's version is the configuration's version, and other profiles are kept
+        // separately.
+        let mut version = None;
+        let mut additional_profiles = Vec::new();
+        let profiles = partial_configuration.bluetooth_profile_descriptor_list
+            .map(|BluetoothProfileDescriptorList(profiles)| profiles)
+            .unwrap_or_default();
+        for (uuid, profile_version) in profiles {
+            if uuid == hid_service_class {
+                try_initialize_attribute(
+                    &mut version, profile_version,
+                    BluetoothProfileDescriptorList::ATTRIBUTE_ID,
+                    BluetoothProfileDescriptorList::NAME)?;
+            } else {
+                additional_profiles.push((uuid, profile_version));
+            }
+        }
         // Create Configuration
         Ok(Configuration {
-            primary_language: LanguageCode { iso_code, hid_code },
-            encoding: partial_configuration.encoding
-                .ok_or(Error::MissingRecord("encoding"))?,
-            service_name: partial_configuration.service_name,
-            service_description: partial_configuration.service_description,
-            provider_name: partial_configuration.provider_name,
-            version: partial_configuration.version
+            service_record_handle: partial_configuration.service_record_handle.map(|a| a.0),
+            service_record_state: partial_configuration.service_record_state.map(|a| a.0),
+            service_info_time_to_live: partial_configuration.service_info_time_to_live.map(|a| a.0),
+            service_availability: partial_configuration.service_availability.map(|a| a.0),
+            additional_service_classes,
+            primary_language: LanguageCode { iso_code: language_base.language, hid_code },
+            encoding: language_base.encoding,
+            service_name: partial_configuration.service_name.map(|a| a.0.into()),
+            service_description: partial_configuration.service_description.map(|a| a.0.into()),
+            provider_name: partial_configuration.provider_name.map(|a| a.0.into()),
+            localized_strings,
+            raw_attributes,
+            documentation_url: partial_configuration.documentation_url.map(|a| a.0.into()),
+            client_executable_url: partial_configuration.client_executable_url.map(|a| a.0.into()),
+            icon_url: partial_configuration.icon_url.map(|a| a.0.into()),
+            version: version
+                .map(ProfileVersion::from_packed)
                 .ok_or(Error::MissingRecord("version"))?,
+            additional_profiles,
+            // The device's identity is not part of the HID record.
+            vendor_id: None,
+            product_id: None,
+            product_version: None,
+            vendor_id_source: None,
             hid: hid::Configuration {
                 device_subclass: partial_configuration.hid_device_subclass
-                                 .ok_or(Error::MissingRecord("device subclass"))?,
+                                 .ok_or(Error::MissingRecord("device subclass"))?.0.into(),
                 country_code: partial_configuration.hid_country_code
-                                 .ok_or(Error::MissingRecord("country code"))?,
+                                 .ok_or(Error::MissingRecord("country code"))?.0.into(),
                 virtual_cable: partial_configuration.hid_virtual_cable
-                                 .ok_or(Error::MissingRecord("virtual cable"))?,
+                                 .ok_or(Error::MissingRecord("virtual cable"))?.0,
                 reconnect_initiate: partial_configuration.hid_reconnect_initiate
-                                 .ok_or(Error::MissingRecord("reconnect initiate"))?,
-                class_descriptors: partial_configuration.hid_descriptor_list,
-                additional_languages: partial_configuration.hid_lang_base_id_list,
-                battery_power: partial_configuration.hid_battery_power,
-                remote_wake: partial_configuration.hid_remote_wake,
-                supervision_timeout: partial_configuration.hid_supervision_timeout,
-                normally_connectable: partial_configuration.hid_normally_connectable,
+                                 .ok_or(Error::MissingRecord("reconnect initiate"))?.0,
+                class_descriptors: partial_configuration.hid_descriptor_list
+                                 .map(|a| a.0)
+                                 .unwrap_or_default(),
+                // The primary language is always generated on output.
+                additional_languages: hid_lang_base_id_list.into_iter().skip(1).collect(),
+                battery_power: partial_configuration.hid_battery_power.map(|a| a.0),
+                remote_wake: partial_configuration.hid_remote_wake.map(|a| a.0),
+                supervision_timeout: partial_configuration.hid_supervision_timeout
+                                 .map(|a| hid::SupervisionTimeout::from_slots(a.0)),
+                normally_connectable: partial_configuration.hid_normally_connectable.map(|a| a.0),
                 boot_device: partial_configuration.hid_boot_device
-                                 .ok_or(Error::MissingRecord("boot device"))?,
-                ssr_host_max_latency: partial_configuration.hid_ssr_host_max_latency,
-                ssr_host_min_timeout: partial_configuration.hid_ssr_host_min_timeout,
+                                 .ok_or(Error::MissingRecord("boot device"))?.0,
+                ssr_host_max_latency: partial_configuration.hid_ssr_host_max_latency
+                                 .map(|a| hid::SsrHostMaxLatency::from_slots(a.0)),
+                ssr_host_min_timeout: partial_configuration.hid_ssr_host_min_timeout
+                                 .map(|a| hid::SsrHostMinTimeout::from_slots(a.0)),
+            },
+            transport: TransportConfiguration {
+                // Default PSMs are left unset.
+                control_psm: partial_configuration.protocol_descriptor_list
+                                 .map(|a| a.psm)
+                                 .filter(|p| *p != psm::HID_CONTROL),
+                interrupt_psm: partial_configuration.additional_protocol_descriptor_lists
+                                 .map(|a| a.psm)
+                                 .filter(|p| *p != psm::HID_INTERRUPT),
+                browse_groups,
+                control_qos: None,
+                interrupt_qos: None,
             },
         })
     }
 }
-
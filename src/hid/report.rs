@@ -0,0 +1,591 @@
+//! Report descriptor items.
+//!
+//! A report descriptor is a sequence of short and long items, as described in 6.2.2 of the USB
+//! HID specification. Each short item starts with a prefix byte giving its tag, type and size,
+//! followed by up to four bytes of little-endian data. The parser here decodes the items into
+//! typed values, without interpreting the state they build up.
+
+use std::fmt::{self, Display, Formatter};
+
+mod boot;
+mod builder;
+mod compile;
+mod compose;
+mod inspect;
+mod layout;
+mod listing;
+mod minimize;
+mod report_map;
+mod templates;
+mod validate;
+
+pub use boot::{BOOT_KEYBOARD, BOOT_MOUSE, BOOT_MOUSE_WITH_WHEEL};
+pub use builder::DescriptorBuilder;
+pub use compile::{compile, compile_items, CompileError};
+pub use compose::{compose, ComposeError, ComposedPart, Composite};
+pub use inspect::{report_ids, top_level_usages};
+pub use layout::{report_sizes, ReportLayout};
+pub use listing::{listing, write_listing};
+pub use minimize::{minimize, minimize_items};
+pub use report_map::{
+    report_map_plan, ReportCharacteristic, ReportMapError, ReportMapPlan, ReportReference,
+    MAX_REPORT_MAP_LEN,
+};
+pub use templates::{
+    consumer_control_descriptor, digitizer_descriptor, gamepad_descriptor, pen_descriptor, Axis,
+    MEDIA_KEYS,
+};
+pub use validate::{validate_report_descriptor, DescriptorViolation};
+
+/// Types of items, from bits 3-2 of the prefix.
+pub mod item_type {
+    pub const MAIN: u8 = 0;
+    pub const GLOBAL: u8 = 1;
+    pub const LOCAL: u8 = 2;
+    pub const RESERVED: u8 = 3;
+}
+
+/// Tags of main items.
+pub mod main_tag {
+    pub const INPUT: u8 = 0x8;
+    pub const OUTPUT: u8 = 0x9;
+    pub const COLLECTION: u8 = 0xa;
+    pub const FEATURE: u8 = 0xb;
+    pub const END_COLLECTION: u8 = 0xc;
+}
+
+/// Tags of global items.
+pub mod global_tag {
+    pub const USAGE_PAGE: u8 = 0x0;
+    pub const LOGICAL_MINIMUM: u8 = 0x1;
+    pub const LOGICAL_MAXIMUM: u8 = 0x2;
+    pub const PHYSICAL_MINIMUM: u8 = 0x3;
+    pub const PHYSICAL_MAXIMUM: u8 = 0x4;
+    pub const UNIT_EXPONENT: u8 = 0x5;
+    pub const UNIT: u8 = 0x6;
+    pub const REPORT_SIZE: u8 = 0x7;
+    pub const REPORT_ID: u8 = 0x8;
+    pub const REPORT_COUNT: u8 = 0x9;
+    pub const PUSH: u8 = 0xa;
+    pub const POP: u8 = 0xb;
+}
+
+/// Tags of local items.
+pub mod local_tag {
+    pub const USAGE: u8 = 0x0;
+    pub const USAGE_MINIMUM: u8 = 0x1;
+    pub const USAGE_MAXIMUM: u8 = 0x2;
+    pub const DESIGNATOR_INDEX: u8 = 0x3;
+    pub const DESIGNATOR_MINIMUM: u8 = 0x4;
+    pub const DESIGNATOR_MAXIMUM: u8 = 0x5;
+    pub const STRING_INDEX: u8 = 0x7;
+    pub const STRING_MINIMUM: u8 = 0x8;
+    pub const STRING_MAXIMUM: u8 = 0x9;
+    pub const DELIMITER: u8 = 0xa;
+}
+
+/// Types of collections.
+pub mod collection {
+    pub const PHYSICAL: u8 = 0x00;
+    pub const APPLICATION: u8 = 0x01;
+    pub const LOGICAL: u8 = 0x02;
+    pub const REPORT: u8 = 0x03;
+    pub const NAMED_ARRAY: u8 = 0x04;
+    pub const USAGE_SWITCH: u8 = 0x05;
+    pub const USAGE_MODIFIER: u8 = 0x06;
+}
+
+/// Prefix of long items.
+pub const LONG_ITEM_PREFIX: u8 = 0xfe;
+
+/// Flags of an input, output or feature item.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MainFlags(pub u32);
+
+impl MainFlags {
+    pub const CONSTANT: u32 = 1 << 0;
+    pub const VARIABLE: u32 = 1 << 1;
+    pub const RELATIVE: u32 = 1 << 2;
+    pub const WRAP: u32 = 1 << 3;
+    pub const NON_LINEAR: u32 = 1 << 4;
+    pub const NO_PREFERRED: u32 = 1 << 5;
+    pub const NULL_STATE: u32 = 1 << 6;
+    pub const VOLATILE: u32 = 1 << 7;
+    pub const BUFFERED_BYTES: u32 = 1 << 8;
+
+    /// Data, variable, absolute: the flags of most fields.
+    pub const DATA_VARIABLE_ABSOLUTE: Self = MainFlags(Self::VARIABLE);
+    /// Data, array, absolute: the flags of key arrays.
+    pub const DATA_ARRAY_ABSOLUTE: Self = MainFlags(0);
+    /// Constant, array, absolute: the flags of padding.
+    pub const CONSTANT_PADDING: Self = MainFlags(Self::CONSTANT);
+
+    pub fn is_constant(&self) -> bool {
+        self.0 & Self::CONSTANT != 0
+    }
+
+    pub fn is_variable(&self) -> bool {
+        self.0 & Self::VARIABLE != 0
+    }
+
+    pub fn is_relative(&self) -> bool {
+        self.0 & Self::RELATIVE != 0
+    }
+
+    pub fn is_buffered_bytes(&self) -> bool {
+        self.0 & Self::BUFFERED_BYTES != 0
+    }
+}
+
+/// An item of a report descriptor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Item {
+    // Main items
+    Input(MainFlags),
+    Output(MainFlags),
+    Feature(MainFlags),
+    Collection(u8),
+    EndCollection,
+
+    // Global items
+    UsagePage(u16),
+    LogicalMinimum(i32),
+    LogicalMaximum(i32),
+    PhysicalMinimum(i32),
+    PhysicalMaximum(i32),
+    UnitExponent(i32),
+    Unit(u32),
+    ReportSize(u32),
+    ReportId(u8),
+    ReportCount(u32),
+    Push,
+    Pop,
+
+    // Local items
+    /// A usage, either a usage ID on the current usage page, or, if given in four bytes, an
+    /// extended usage with the page in the high 16 bits.
+    Usage(u32),
+    UsageMinimum(u32),
+    UsageMaximum(u32),
+    DesignatorIndex(u32),
+    DesignatorMinimum(u32),
+    DesignatorMaximum(u32),
+    StringIndex(u32),
+    StringMinimum(u32),
+    StringMaximum(u32),
+    Delimiter(u32),
+
+    /// A long item, with its tag and data.
+    Long { tag: u8, data: Vec<u8> },
+    /// A short item with a reserved tag or type, with its unsigned data.
+    Unknown { item_type: u8, tag: u8, data: u32 },
+}
+
+impl Item {
+    /// Return the type of the item, such as `item_type::MAIN`.
+    pub fn item_type(&self) -> u8 {
+        match self {
+            Item::Input(_) | Item::Output(_) | Item::Feature(_)
+                | Item::Collection(_) | Item::EndCollection => item_type::MAIN,
+            Item::UsagePage(_) | Item::LogicalMinimum(_) | Item::LogicalMaximum(_)
+                | Item::PhysicalMinimum(_) | Item::PhysicalMaximum(_) | Item::UnitExponent(_)
+                | Item::Unit(_) | Item::ReportSize(_) | Item::ReportId(_)
+                | Item::ReportCount(_) | Item::Push | Item::Pop => item_type::GLOBAL,
+            Item::Usage(_) | Item::UsageMinimum(_) | Item::UsageMaximum(_)
+                | Item::DesignatorIndex(_) | Item::DesignatorMinimum(_)
+                | Item::DesignatorMaximum(_) | Item::StringIndex(_) | Item::StringMinimum(_)
+                | Item::StringMaximum(_) | Item::Delimiter(_) => item_type::LOCAL,
+            Item::Long { .. } => item_type::RESERVED,
+            Item::Unknown { item_type, .. } => *item_type,
+        }
+    }
+
+    /// Return true if the item is a main item.
+    pub fn is_main(&self) -> bool {
+        self.item_type() == item_type::MAIN
+    }
+
+    /// Return true if the item is a global item.
+    pub fn is_global(&self) -> bool {
+        self.item_type() == item_type::GLOBAL
+    }
+
+    /// Return true if the item is a local item.
+    pub fn is_local(&self) -> bool {
+        self.item_type() == item_type::LOCAL
+    }
+
+    /// Return the type, tag and data of a short item, with the data as signed if the item
+    /// holds a signed value. Long items give `None`.
+    fn short_parts(&self) -> Option<(u8, u8, Data)> {
+        use Data::{Empty, Signed, Unsigned};
+        let parts = match *self {
+            Item::Input(flags) => (item_type::MAIN, main_tag::INPUT, Unsigned(flags.0)),
+            Item::Output(flags) => (item_type::MAIN, main_tag::OUTPUT, Unsigned(flags.0)),
+            Item::Feature(flags) => (item_type::MAIN, main_tag::FEATURE, Unsigned(flags.0)),
+            Item::Collection(v) => (item_type::MAIN, main_tag::COLLECTION, Unsigned(v.into())),
+            Item::EndCollection => (item_type::MAIN, main_tag::END_COLLECTION, Empty),
+
+            Item::UsagePage(v) => (item_type::GLOBAL, global_tag::USAGE_PAGE, Unsigned(v.into())),
+            Item::LogicalMinimum(v) => (item_type::GLOBAL, global_tag::LOGICAL_MINIMUM, Signed(v)),
+            Item::LogicalMaximum(v) => (item_type::GLOBAL, global_tag::LOGICAL_MAXIMUM, Signed(v)),
+            Item::PhysicalMinimum(v) => (item_type::GLOBAL, global_tag::PHYSICAL_MINIMUM, Signed(v)),
+            Item::PhysicalMaximum(v) => (item_type::GLOBAL, global_tag::PHYSICAL_MAXIMUM, Signed(v)),
+            Item::UnitExponent(v) => (item_type::GLOBAL, global_tag::UNIT_EXPONENT, Signed(v)),
+            Item::Unit(v) => (item_type::GLOBAL, global_tag::UNIT, Unsigned(v)),
+            Item::ReportSize(v) => (item_type::GLOBAL, global_tag::REPORT_SIZE, Unsigned(v)),
+            Item::ReportId(v) => (item_type::GLOBAL, global_tag::REPORT_ID, Unsigned(v.into())),
+            Item::ReportCount(v) => (item_type::GLOBAL, global_tag::REPORT_COUNT, Unsigned(v)),
+            Item::Push => (item_type::GLOBAL, global_tag::PUSH, Empty),
+            Item::Pop => (item_type::GLOBAL, global_tag::POP, Empty),
+
+            Item::Usage(v) => (item_type::LOCAL, local_tag::USAGE, Unsigned(v)),
+            Item::UsageMinimum(v) => (item_type::LOCAL, local_tag::USAGE_MINIMUM, Unsigned(v)),
+            Item::UsageMaximum(v) => (item_type::LOCAL, local_tag::USAGE_MAXIMUM, Unsigned(v)),
+            Item::DesignatorIndex(v) => (item_type::LOCAL, local_tag::DESIGNATOR_INDEX, Unsigned(v)),
+            Item::DesignatorMinimum(v) => (item_type::LOCAL, local_tag::DESIGNATOR_MINIMUM, Unsigned(v)),
+            Item::DesignatorMaximum(v) => (item_type::LOCAL, local_tag::DESIGNATOR_MAXIMUM, Unsigned(v)),
+            Item::StringIndex(v) => (item_type::LOCAL, local_tag::STRING_INDEX, Unsigned(v)),
+            Item::StringMinimum(v) => (item_type::LOCAL, local_tag::STRING_MINIMUM, Unsigned(v)),
+            Item::StringMaximum(v) => (item_type::LOCAL, local_tag::STRING_MAXIMUM, Unsigned(v)),
+            Item::Delimiter(v) => (item_type::LOCAL, local_tag::DELIMITER, Unsigned(v)),
+
+            Item::Long { .. } => return None,
+            Item::Unknown { item_type, tag, data } => (item_type, tag, Unsigned(data)),
+        };
+        Some(parts)
+    }
+
+    /// Append the encoding of the item, using the fewest data bytes which hold its value.
+    ///
+    /// Items with values always get at least one byte of data, as some hosts mishandle
+    /// zero-length data. Long items hold at most 255 bytes, and longer data is truncated.
+    pub fn encode_into(&self, output: &mut Vec<u8>) {
+        let (item_type, tag, data) = match self.short_parts() {
+            Some(parts) => parts,
+            None => {
+                if let Item::Long { tag, data } = self {
+                    let len = data.len().min(u8::MAX.into());
+                    output.extend_from_slice(&[LONG_ITEM_PREFIX, len as u8, *tag]);
+                    output.extend_from_slice(&data[..len]);
+                }
+                return;
+            },
+        };
+        let (bytes, len) = match data {
+            Data::Empty => ([0; 4], 0),
+            Data::Unsigned(v) if v <= u8::MAX.into() => (v.to_le_bytes(), 1),
+            Data::Unsigned(v) if v <= u16::MAX.into() => (v.to_le_bytes(), 2),
+            Data::Unsigned(v) => (v.to_le_bytes(), 4),
+            Data::Signed(v) if i8::try_from(v).is_ok() => (v.to_le_bytes(), 1),
+            Data::Signed(v) if i16::try_from(v).is_ok() => (v.to_le_bytes(), 2),
+            Data::Signed(v) => (v.to_le_bytes(), 4),
+        };
+        let size_code = if len == 4 { 3 } else { len as u8 };
+        output.push(tag << 4 | item_type << 2 | size_code);
+        output.extend_from_slice(&bytes[..len]);
+    }
+
+    /// Return the encoding of the item.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut output = Vec::new();
+        self.encode_into(&mut output);
+        output
+    }
+
+    /// Decode a short item from its type, tag and data bytes.
+    fn from_short(item_type: u8, tag: u8, data: &[u8]) -> Self {
+        let mut unsigned_bytes = [0; 4];
+        unsigned_bytes[..data.len()].copy_from_slice(data);
+        let unsigned = u32::from_le_bytes(unsigned_bytes);
+        let signed = match data.len() {
+            0 => 0,
+            1 => data[0] as i8 as i32,
+            2 => i16::from_le_bytes([data[0], data[1]]) as i32,
+            _ => unsigned as i32,
+        };
+        match (item_type, tag) {
+            (item_type::MAIN, main_tag::INPUT) => Item::Input(MainFlags(unsigned)),
+            (item_type::MAIN, main_tag::OUTPUT) => Item::Output(MainFlags(unsigned)),
+            (item_type::MAIN, main_tag::FEATURE) => Item::Feature(MainFlags(unsigned)),
+            (item_type::MAIN, main_tag::COLLECTION) => Item::Collection(unsigned as u8),
+            (item_type::MAIN, main_tag::END_COLLECTION) => Item::EndCollection,
+
+            (item_type::GLOBAL, global_tag::USAGE_PAGE) => Item::UsagePage(unsigned as u16),
+            (item_type::GLOBAL, global_tag::LOGICAL_MINIMUM) => Item::LogicalMinimum(signed),
+            (item_type::GLOBAL, global_tag::LOGICAL_MAXIMUM) => Item::LogicalMaximum(signed),
+            (item_type::GLOBAL, global_tag::PHYSICAL_MINIMUM) => Item::PhysicalMinimum(signed),
+            (item_type::GLOBAL, global_tag::PHYSICAL_MAXIMUM) => Item::PhysicalMaximum(signed),
+            (item_type::GLOBAL, global_tag::UNIT_EXPONENT) => Item::UnitExponent(signed),
+            (item_type::GLOBAL, global_tag::UNIT) => Item::Unit(unsigned),
+            (item_type::GLOBAL, global_tag::REPORT_SIZE) => Item::ReportSize(unsigned),
+            (item_type::GLOBAL, global_tag::REPORT_ID) => Item::ReportId(unsigned as u8),
+            (item_type::GLOBAL, global_tag::REPORT_COUNT) => Item::ReportCount(unsigned),
+            (item_type::GLOBAL, global_tag::PUSH) => Item::Push,
+            (item_type::GLOBAL, global_tag::POP) => Item::Pop,
+
+            (item_type::LOCAL, local_tag::USAGE) => Item::Usage(unsigned),
+            (item_type::LOCAL, local_tag::USAGE_MINIMUM) => Item::UsageMinimum(unsigned),
+            (item_type::LOCAL, local_tag::USAGE_MAXIMUM) => Item::UsageMaximum(unsigned),
+            (item_type::LOCAL, local_tag::DESIGNATOR_INDEX) => Item::DesignatorIndex(unsigned),
+            (item_type::LOCAL, local_tag::DESIGNATOR_MINIMUM) => Item::DesignatorMinimum(unsigned),
+            (item_type::LOCAL, local_tag::DESIGNATOR_MAXIMUM) => Item::DesignatorMaximum(unsigned),
+            (item_type::LOCAL, local_tag::STRING_INDEX) => Item::StringIndex(unsigned),
+            (item_type::LOCAL, local_tag::STRING_MINIMUM) => Item::StringMinimum(unsigned),
+            (item_type::LOCAL, local_tag::STRING_MAXIMUM) => Item::StringMaximum(unsigned),
+            (item_type::LOCAL, local_tag::DELIMITER) => Item::Delimiter(unsigned),
+
+            _ => Item::Unknown { item_type, tag, data: unsigned },
+        }
+    }
+}
+
+impl Display for MainFlags {
+    /// Write the flags as abbreviated in the HID specification, such as `Data,Var,Abs`.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let bits = [
+            (Self::CONSTANT, "Const", "Data"),
+            (Self::VARIABLE, "Var", "Array"),
+            (Self::RELATIVE, "Rel", "Abs"),
+            (Self::WRAP, "Wrap", ""),
+            (Self::NON_LINEAR, "NonLin", ""),
+            (Self::NO_PREFERRED, "NoPref", ""),
+            (Self::NULL_STATE, "Null", ""),
+            (Self::VOLATILE, "Vol", ""),
+            (Self::BUFFERED_BYTES, "Buf", ""),
+        ];
+        let names: Vec<&str> = bits.iter()
+            .map(|&(bit, set, unset)| if self.0 & bit != 0 { set } else { unset })
+            .filter(|name| !name.is_empty())
+            .collect();
+        write!(f, "{}", names.join(","))
+    }
+}
+
+/// Return the name of a collection type.
+fn collection_name(collection_type: u8) -> Option<&'static str> {
+    match collection_type {
+        collection::PHYSICAL => Some("Physical"),
+        collection::APPLICATION => Some("Application"),
+        collection::LOGICAL => Some("Logical"),
+        collection::REPORT => Some("Report"),
+        collection::NAMED_ARRAY => Some("Named Array"),
+        collection::USAGE_SWITCH => Some("Usage Switch"),
+        collection::USAGE_MODIFIER => Some("Usage Modifier"),
+        _ => None,
+    }
+}
+
+impl Display for Item {
+    /// Write the item as listed by descriptor tools, such as `Usage Page (0x01)`.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Item::Input(flags) => write!(f, "Input ({})", flags),
+            Item::Output(flags) => write!(f, "Output ({})", flags),
+            Item::Feature(flags) => write!(f, "Feature ({})", flags),
+            Item::Collection(v) => match collection_name(*v) {
+                Some(name) => write!(f, "Collection ({})", name),
+                None => write!(f, "Collection (0x{:02x})", v),
+            },
+            Item::EndCollection => write!(f, "End Collection"),
+
+            Item::UsagePage(v) => write!(f, "Usage Page (0x{:02x})", v),
+            Item::LogicalMinimum(v) => write!(f, "Logical Minimum ({})", v),
+            Item::LogicalMaximum(v) => write!(f, "Logical Maximum ({})", v),
+            Item::PhysicalMinimum(v) => write!(f, "Physical Minimum ({})", v),
+            Item::PhysicalMaximum(v) => write!(f, "Physical Maximum ({})", v),
+            Item::UnitExponent(v) => write!(f, "Unit Exponent ({})", v),
+            Item::Unit(v) => write!(f, "Unit (0x{:x})", v),
+            Item::ReportSize(v) => write!(f, "Report Size ({})", v),
+            Item::ReportId(v) => write!(f, "Report ID ({})", v),
+            Item::ReportCount(v) => write!(f, "Report Count ({})", v),
+            Item::Push => write!(f, "Push"),
+            Item::Pop => write!(f, "Pop"),
+
+            Item::Usage(v) => write!(f, "Usage (0x{:02x})", v),
+            Item::UsageMinimum(v) => write!(f, "Usage Minimum (0x{:02x})", v),
+            Item::UsageMaximum(v) => write!(f, "Usage Maximum (0x{:02x})", v),
+            Item::DesignatorIndex(v) => write!(f, "Designator Index ({})", v),
+            Item::DesignatorMinimum(v) => write!(f, "Designator Minimum ({})", v),
+            Item::DesignatorMaximum(v) => write!(f, "Designator Maximum ({})", v),
+            Item::StringIndex(v) => write!(f, "String Index ({})", v),
+            Item::StringMinimum(v) => write!(f, "String Minimum ({})", v),
+            Item::StringMaximum(v) => write!(f, "String Maximum ({})", v),
+            Item::Delimiter(v) => write!(f, "Delimiter ({})", v),
+
+            Item::Long { tag, data } => write!(f, "Long Item (0x{:02x}, {} bytes)", tag, data.len()),
+            Item::Unknown { item_type, tag, data } =>
+                write!(f, "Unknown (type {}, tag 0x{:x}, 0x{:x})", item_type, tag, data),
+        }
+    }
+}
+
+/// Data of a short item, for encoding.
+#[derive(Clone, Copy)]
+enum Data {
+    Empty,
+    Unsigned(u32),
+    Signed(i32),
+}
+
+/// Error type for malformed report descriptors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The item starting at the given offset runs past the end of the descriptor.
+    UnexpectedEnd(usize),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd(offset) =>
+                write!(f, "at offset {}: item runs past the end of the descriptor", offset),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Iterator over the items of a report descriptor.
+///
+/// Iteration stops after the first error.
+pub struct Items<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Items<'a> {
+    /// Return the offset of the next item in the descriptor.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a> Iterator for Items<'a> {
+    type Item = Result<Item, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.offset;
+        let prefix = *self.data.get(start)?;
+        let truncated = || {
+            Some(Err(ParseError::UnexpectedEnd(start)))
+        };
+
+        if prefix == LONG_ITEM_PREFIX {
+            // Long items give their size and tag in the next two bytes.
+            let (size, tag) = match self.data.get(start + 1..start + 3) {
+                Some(header) => (header[0] as usize, header[1]),
+                None => {
+                    self.offset = self.data.len();
+                    return truncated();
+                },
+            };
+            let data = match self.data.get(start + 3..start + 3 + size) {
+                Some(data) => data.to_vec(),
+                None => {
+                    self.offset = self.data.len();
+                    return truncated();
+                },
+            };
+            self.offset = start + 3 + size;
+            return Some(Ok(Item::Long { tag, data }));
+        }
+
+        let size = match prefix & 0x03 {
+            3 => 4,
+            size => size as usize,
+        };
+        let item_type = (prefix >> 2) & 0x03;
+        let tag = prefix >> 4;
+        match self.data.get(start + 1..start + 1 + size) {
+            Some(data) => {
+                self.offset = start + 1 + size;
+                Some(Ok(Item::from_short(item_type, tag, data)))
+            },
+            None => {
+                self.offset = self.data.len();
+                truncated()
+            },
+        }
+    }
+}
+
+/// Iterate over the items of a report descriptor.
+pub fn items(data: &[u8]) -> Items<'_> {
+    Items { data, offset: 0 }
+}
+
+/// Parse every item of a report descriptor.
+pub fn parse(data: &[u8]) -> Result<Vec<Item>, ParseError> {
+    items(data).collect()
+}
+
+/// Encode items as a report descriptor.
+pub fn encode(items: &[Item]) -> Vec<u8> {
+    let mut output = Vec::new();
+    for item in items {
+        item.encode_into(&mut output);
+    }
+    output
+}
+
+/// Types of reports, from the main items declaring their fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ReportType {
+    Input,
+    Output,
+    Feature,
+}
+
+impl Display for ReportType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Input => write!(f, "input"),
+            Self::Output => write!(f, "output"),
+            Self::Feature => write!(f, "feature"),
+        }
+    }
+}
+
+impl Item {
+    /// Return the report type and flags of the item, if it is an input, output or feature item.
+    pub fn report_field(&self) -> Option<(ReportType, MainFlags)> {
+        match self {
+            Item::Input(flags) => Some((ReportType::Input, *flags)),
+            Item::Output(flags) => Some((ReportType::Output, *flags)),
+            Item::Feature(flags) => Some((ReportType::Feature, *flags)),
+            _ => None,
+        }
+    }
+}
+
+/// Global items in effect at some point of a descriptor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct GlobalState {
+    pub(crate) usage_page: Option<u16>,
+    pub(crate) logical_minimum: Option<i32>,
+    pub(crate) logical_maximum: Option<i32>,
+    pub(crate) report_size: Option<u32>,
+    pub(crate) report_id: Option<u8>,
+    pub(crate) report_count: Option<u32>,
+}
+
+impl GlobalState {
+    /// Update the state with a global item. Push and pop are left to the caller.
+    pub(crate) fn apply(&mut self, item: &Item) {
+        match *item {
+            Item::UsagePage(page) => self.usage_page = Some(page),
+            Item::LogicalMinimum(v) => self.logical_minimum = Some(v),
+            Item::LogicalMaximum(v) => self.logical_maximum = Some(v),
+            Item::ReportSize(v) => self.report_size = Some(v),
+            Item::ReportId(v) => self.report_id = Some(v),
+            Item::ReportCount(v) => self.report_count = Some(v),
+            _ => (),
+        }
+    }
+
+    /// Return the number of bits the current field occupies in its report.
+    pub(crate) fn field_bits(&self) -> u32 {
+        self.report_size.unwrap_or(0).saturating_mul(self.report_count.unwrap_or(0))
+    }
+}
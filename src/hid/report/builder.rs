@@ -0,0 +1,145 @@
+//! Building report descriptors item by item.
+
+use super::{encode, Item, MainFlags, ReportType};
+
+/// Builder appending items to a report descriptor.
+///
+/// Each method appends the items it names, so global items it sets stay in effect for the items
+/// after them, as in a hand-written descriptor.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DescriptorBuilder {
+    items: Vec<Item>,
+}
+
+impl DescriptorBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append any item.
+    pub fn item(mut self, item: Item) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    pub fn usage_page(self, page: u16) -> Self {
+        self.item(Item::UsagePage(page))
+    }
+
+    /// Append a usage on the current usage page.
+    pub fn usage(self, id: u16) -> Self {
+        self.item(Item::Usage(id.into()))
+    }
+
+    /// Append a usage for each of the given IDs, in order.
+    pub fn usages(self, ids: &[u16]) -> Self {
+        ids.iter().fold(self, |builder, &id| builder.usage(id))
+    }
+
+    /// Append a range of usages, from `minimum` to `maximum` inclusive.
+    pub fn usage_range(self, minimum: u16, maximum: u16) -> Self {
+        self.item(Item::UsageMinimum(minimum.into()))
+            .item(Item::UsageMaximum(maximum.into()))
+    }
+
+    pub fn logical_range(self, minimum: i32, maximum: i32) -> Self {
+        self.item(Item::LogicalMinimum(minimum))
+            .item(Item::LogicalMaximum(maximum))
+    }
+
+    pub fn physical_range(self, minimum: i32, maximum: i32) -> Self {
+        self.item(Item::PhysicalMinimum(minimum))
+            .item(Item::PhysicalMaximum(maximum))
+    }
+
+    pub fn report_id(self, report_id: u8) -> Self {
+        self.item(Item::ReportId(report_id))
+    }
+
+    /// Append the size of each field, in bits.
+    pub fn report_size(self, bits: u32) -> Self {
+        self.item(Item::ReportSize(bits))
+    }
+
+    /// Append the number of fields declared by the next main item.
+    pub fn report_count(self, count: u32) -> Self {
+        self.item(Item::ReportCount(count))
+    }
+
+    /// Append a collection holding the items appended by `contents`.
+    pub fn collection(self, collection_type: u8, contents: impl FnOnce(Self) -> Self) -> Self {
+        contents(self.item(Item::Collection(collection_type)))
+            .item(Item::EndCollection)
+    }
+
+    pub fn input(self, flags: MainFlags) -> Self {
+        self.item(Item::Input(flags))
+    }
+
+    pub fn output(self, flags: MainFlags) -> Self {
+        self.item(Item::Output(flags))
+    }
+
+    pub fn feature(self, flags: MainFlags) -> Self {
+        self.item(Item::Feature(flags))
+    }
+
+    /// Append a main item of the given report type.
+    pub fn field(self, report_type: ReportType, flags: MainFlags) -> Self {
+        match report_type {
+            ReportType::Input => self.input(flags),
+            ReportType::Output => self.output(flags),
+            ReportType::Feature => self.feature(flags),
+        }
+    }
+
+    /// Append `count` variable fields of `bits` bits each, with values from `minimum` to
+    /// `maximum`, all with the given usage. This changes the logical range, report size and
+    /// count.
+    pub fn values(
+        self,
+        report_type: ReportType,
+        usage: u16,
+        minimum: i32,
+        maximum: i32,
+        bits: u32,
+        count: u32,
+    ) -> Self {
+        self.usage(usage)
+            .logical_range(minimum, maximum)
+            .report_size(bits)
+            .report_count(count)
+            .field(report_type, MainFlags::DATA_VARIABLE_ABSOLUTE)
+    }
+
+    /// Append a feature field of `bits` bits with the given usage, for a setting a host reads and
+    /// writes, padded to a whole number of bytes. This changes the logical range, report size
+    /// and count.
+    ///
+    /// Feature reports are often declared in their own report, after a report ID.
+    pub fn feature_value(self, usage: u16, minimum: i32, maximum: i32, bits: u32) -> Self {
+        self.values(ReportType::Feature, usage, minimum, maximum, bits, 1)
+            .padding(ReportType::Feature, (8 - bits % 8) % 8)
+    }
+
+    /// Append a constant field of the given number of bits, to align the fields after it. This
+    /// changes the report size and count.
+    pub fn padding(self, report_type: ReportType, bits: u32) -> Self {
+        if bits == 0 {
+            return self;
+        }
+        self.report_size(bits)
+            .report_count(1)
+            .field(report_type, MainFlags::CONSTANT_PADDING)
+    }
+
+    /// Return the items appended so far.
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// Return the encoded descriptor.
+    pub fn build(&self) -> Vec<u8> {
+        encode(&self.items)
+    }
+}
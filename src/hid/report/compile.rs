@@ -0,0 +1,234 @@
+//! Compiling report descriptors from a textual source.
+//!
+//! The source has one item per line, giving the item name followed by its value, optionally in
+//! parentheses, as in the listing:
+//!
+//! ```text
+//! # A three-button mouse
+//! Usage Page (0x01)     // Generic Desktop
+//! Usage 0x02            // Mouse
+//! Collection Application
+//!   Usage Page 0x09     // Buttons
+//!   Usage Minimum 1
+//!   Usage Maximum 3
+//!   Logical Minimum 0
+//!   Logical Maximum 1
+//!   Report Size 1
+//!   Report Count 3
+//!   Input Data,Var,Abs
+//!   Report Count 5
+//!   Input Const
+//! End Collection
+//! ```
+//!
+//! Names are case-insensitive, and may use underscores for spaces. Numbers may be decimal or
+//! hex with a `0x` prefix. Comments start with `#` or `//` and run to the end of the line.
+
+use super::{collection, encode, Item, MainFlags};
+use std::fmt::{self, Display, Formatter};
+
+/// Error type for invalid descriptor sources, with the line number where it occurred, from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompileError {
+    /// The line does not start with the name of an item.
+    UnknownItem { line: usize, text: String },
+    /// The item requires a value, but none was given.
+    MissingValue { line: usize, item: &'static str },
+    /// The value is not valid for the item.
+    InvalidValue { line: usize, item: &'static str, value: String },
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownItem { line, text } =>
+                write!(f, "line {}: unknown item {:?}", line, text),
+            Self::MissingValue { line, item } =>
+                write!(f, "line {}: {} requires a value", line, item),
+            Self::InvalidValue { line, item, value } =>
+                write!(f, "line {}: invalid value {:?} for {}", line, value, item),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+/// Names of items, as they appear in the listing.
+const ITEM_NAMES: [&str; 27] = [
+    "Input", "Output", "Feature", "Collection", "End Collection",
+    "Usage Page", "Logical Minimum", "Logical Maximum", "Physical Minimum", "Physical Maximum",
+    "Unit Exponent", "Unit", "Report Size", "Report ID", "Report Count", "Push", "Pop",
+    "Usage", "Usage Minimum", "Usage Maximum", "Designator Index", "Designator Minimum",
+    "Designator Maximum", "String Index", "String Minimum", "String Maximum", "Delimiter",
+];
+
+/// Remove a comment from a line.
+fn strip_comment(line: &str) -> &str {
+    let end = [line.find('#'), line.find("//")].into_iter().flatten().min();
+    match end {
+        Some(end) => &line[..end],
+        None => line,
+    }
+}
+
+/// Split a line into the longest item name it starts with and the rest of the line.
+fn split_name(line: &str) -> Option<(&'static str, &str)> {
+    let words: Vec<(usize, &str)> = line.split_whitespace()
+        .map(|word| (word.as_ptr() as usize - line.as_ptr() as usize, word))
+        .collect();
+    let mut found = None;
+    let mut name = String::new();
+    for (i, (start, word)) in words.iter().enumerate() {
+        // Parentheses may follow the name without a space.
+        let (word, paren) = match word.find('(') {
+            Some(paren) => (&word[..paren], true),
+            None => (*word, false),
+        };
+        if !name.is_empty() && !word.is_empty() {
+            name.push(' ');
+        }
+        name.push_str(&word.replace('_', " "));
+        if let Some(item) = ITEM_NAMES.iter().find(|item| item.eq_ignore_ascii_case(&name)) {
+            let rest = if paren {
+                &line[start + word.len()..]
+            } else {
+                words.get(i + 1).map_or("", |&(next, _)| &line[next..])
+            };
+            found = Some((*item, rest));
+        }
+        if paren {
+            break;
+        }
+    }
+    found
+}
+
+/// Parse an integer, in decimal or hex.
+fn parse_int(value: &str) -> Option<i64> {
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value),
+    };
+    let magnitude = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => digits.parse().ok()?,
+    };
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// Parse the flags of a main item, as a number or abbreviations like `Data,Var,Abs`.
+fn parse_flags(value: &str) -> Option<MainFlags> {
+    if let Some(bits) = parse_int(value) {
+        return u32::try_from(bits).ok().map(MainFlags);
+    }
+    let mut flags = 0;
+    for name in value.split(',').map(str::trim) {
+        let bit = match name.to_ascii_lowercase().as_str() {
+            "data" | "array" | "abs" | "nowrap" | "lin" | "pref" | "nonull" | "nonvol"
+                | "bitfield" => 0,
+            "const" | "cnst" => MainFlags::CONSTANT,
+            "var" => MainFlags::VARIABLE,
+            "rel" => MainFlags::RELATIVE,
+            "wrap" => MainFlags::WRAP,
+            "nonlin" => MainFlags::NON_LINEAR,
+            "nopref" => MainFlags::NO_PREFERRED,
+            "null" => MainFlags::NULL_STATE,
+            "vol" => MainFlags::VOLATILE,
+            "buf" => MainFlags::BUFFERED_BYTES,
+            _ => return None,
+        };
+        flags |= bit;
+    }
+    Some(MainFlags(flags))
+}
+
+/// Parse the type of a collection, as a number or name.
+fn parse_collection(value: &str) -> Option<u8> {
+    if let Some(v) = parse_int(value) {
+        return u8::try_from(v).ok();
+    }
+    match value.to_ascii_lowercase().replace('_', " ").as_str() {
+        "physical" => Some(collection::PHYSICAL),
+        "application" => Some(collection::APPLICATION),
+        "logical" => Some(collection::LOGICAL),
+        "report" => Some(collection::REPORT),
+        "named array" => Some(collection::NAMED_ARRAY),
+        "usage switch" => Some(collection::USAGE_SWITCH),
+        "usage modifier" => Some(collection::USAGE_MODIFIER),
+        _ => None,
+    }
+}
+
+/// Compile a single item from its name and value.
+fn compile_item(line: usize, name: &'static str, value: &str) -> Result<Item, CompileError> {
+    if value.is_empty() && !matches!(name, "End Collection" | "Push" | "Pop") {
+        return Err(CompileError::MissingValue { line, item: name });
+    }
+    let invalid = || CompileError::InvalidValue { line, item: name, value: value.to_string() };
+    let unsigned = || parse_int(value).and_then(|v| u32::try_from(v).ok()).ok_or_else(invalid);
+    let signed = || parse_int(value).and_then(|v| i32::try_from(v).ok()).ok_or_else(invalid);
+    let flags = || parse_flags(value).ok_or_else(invalid);
+
+    let item = match name {
+        "Input" => Item::Input(flags()?),
+        "Output" => Item::Output(flags()?),
+        "Feature" => Item::Feature(flags()?),
+        "Collection" => Item::Collection(parse_collection(value).ok_or_else(invalid)?),
+        "End Collection" => Item::EndCollection,
+
+        "Usage Page" => Item::UsagePage(u16::try_from(unsigned()?).map_err(|_| invalid())?),
+        "Logical Minimum" => Item::LogicalMinimum(signed()?),
+        "Logical Maximum" => Item::LogicalMaximum(signed()?),
+        "Physical Minimum" => Item::PhysicalMinimum(signed()?),
+        "Physical Maximum" => Item::PhysicalMaximum(signed()?),
+        "Unit Exponent" => Item::UnitExponent(signed()?),
+        "Unit" => Item::Unit(unsigned()?),
+        "Report Size" => Item::ReportSize(unsigned()?),
+        "Report ID" => Item::ReportId(u8::try_from(unsigned()?).map_err(|_| invalid())?),
+        "Report Count" => Item::ReportCount(unsigned()?),
+        "Push" => Item::Push,
+        "Pop" => Item::Pop,
+
+        "Usage" => Item::Usage(unsigned()?),
+        "Usage Minimum" => Item::UsageMinimum(unsigned()?),
+        "Usage Maximum" => Item::UsageMaximum(unsigned()?),
+        "Designator Index" => Item::DesignatorIndex(unsigned()?),
+        "Designator Minimum" => Item::DesignatorMinimum(unsigned()?),
+        "Designator Maximum" => Item::DesignatorMaximum(unsigned()?),
+        "String Index" => Item::StringIndex(unsigned()?),
+        "String Minimum" => Item::StringMinimum(unsigned()?),
+        "String Maximum" => Item::StringMaximum(unsigned()?),
+        "Delimiter" => Item::Delimiter(unsigned()?),
+
+        _ => unreachable!("every item name is handled"),
+    };
+    Ok(item)
+}
+
+/// Compile a textual report descriptor into its items.
+pub fn compile_items(source: &str) -> Result<Vec<Item>, CompileError> {
+    let mut items = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let line_number = i + 1;
+        let text = strip_comment(line).trim();
+        if text.is_empty() {
+            continue;
+        }
+        let (name, rest) = split_name(text).ok_or_else(|| CompileError::UnknownItem {
+            line: line_number,
+            text: text.to_string(),
+        })?;
+        let value = rest.trim();
+        let value = value.strip_prefix('(')
+            .and_then(|value| value.strip_suffix(')'))
+            .unwrap_or(value)
+            .trim();
+        items.push(compile_item(line_number, name, value)?);
+    }
+    Ok(items)
+}
+
+/// Compile a textual report descriptor into bytes.
+pub fn compile(source: &str) -> Result<Vec<u8>, CompileError> {
+    compile_items(source).map(|items| encode(&items))
+}
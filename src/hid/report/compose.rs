@@ -0,0 +1,122 @@
+//! Composing several report descriptors into one.
+//!
+//! A device with several functions, such as a keyboard with media keys and a touchpad, declares
+//! all of them in one report descriptor, telling their reports apart by report ID. Composition
+//! concatenates the descriptors of each function, giving every report a report ID unique in the
+//! result.
+
+use super::{encode, parse, Item, ParseError};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+/// Error type for descriptors which cannot be composed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ComposeError {
+    /// The descriptor of the named part is malformed.
+    Malformed(String, ParseError),
+    /// The parts declare more reports than there are report IDs.
+    TooManyReports,
+}
+
+impl Display for ComposeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Malformed(name, e) => write!(f, "descriptor of {}: {}", name, e),
+            Self::TooManyReports => write!(f, "more than 255 reports"),
+        }
+    }
+}
+
+impl std::error::Error for ComposeError {}
+
+/// A part of a composite descriptor, with its report IDs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComposedPart {
+    pub name: String,
+    /// The report IDs of the part before and after composition. A part which declared no report
+    /// IDs has one report ID, with `None` before composition.
+    pub report_ids: Vec<(Option<u8>, u8)>,
+}
+
+/// A descriptor composed of several parts, with the report IDs given to each part.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Composite {
+    /// The composed report descriptor.
+    pub descriptor: Vec<u8>,
+    /// The parts, in the order they were composed.
+    pub parts: Vec<ComposedPart>,
+}
+
+impl Composite {
+    /// Return the report IDs given to the named part.
+    pub fn report_ids_of(&self, name: &str) -> Vec<u8> {
+        self.parts.iter()
+            .filter(|part| part.name == name)
+            .flat_map(|part| part.report_ids.iter().map(|&(_, id)| id))
+            .collect()
+    }
+
+    /// Return the first report ID given to the named part, which is its only report ID if it
+    /// declared none of its own.
+    pub fn report_id(&self, name: &str) -> Option<u8> {
+        self.report_ids_of(name).first().copied()
+    }
+}
+
+/// Compose named report descriptors into one, in the given order.
+///
+/// Report IDs are assigned from 1 in order of appearance. A part which declares report IDs has
+/// each renumbered, and a part which declares none gets a report ID item after its first
+/// collection item, or at its start if it has no collections.
+pub fn compose(parts: &[(&str, &[u8])]) -> Result<Composite, ComposeError> {
+    let mut items = Vec::new();
+    let mut composed_parts = Vec::new();
+    let mut next_id: u16 = 1;
+    let mut assign = || -> Result<u8, ComposeError> {
+        let id = u8::try_from(next_id).map_err(|_| ComposeError::TooManyReports)?;
+        next_id += 1;
+        Ok(id)
+    };
+
+    for &(name, data) in parts {
+        let part = parse(data).map_err(|e| ComposeError::Malformed(name.to_string(), e))?;
+        let mut mapping = BTreeMap::new();
+        let mut ids = Vec::new();
+
+        if part.iter().any(|item| matches!(item, Item::ReportId(_))) {
+            for item in part {
+                match item {
+                    Item::ReportId(old) => {
+                        let new = match mapping.get(&old) {
+                            Some(&new) => new,
+                            None => {
+                                let new = assign()?;
+                                mapping.insert(old, new);
+                                ids.push((Some(old), new));
+                                new
+                            },
+                        };
+                        items.push(Item::ReportId(new));
+                    },
+                    item => items.push(item),
+                }
+            }
+        } else {
+            let id = assign()?;
+            ids.push((None, id));
+            let position = part.iter()
+                .position(|item| matches!(item, Item::Collection(_)))
+                .map_or(0, |collection| collection + 1);
+            items.extend_from_slice(&part[..position]);
+            items.push(Item::ReportId(id));
+            items.extend_from_slice(&part[position..]);
+        }
+
+        composed_parts.push(ComposedPart { name: name.to_string(), report_ids: ids });
+    }
+
+    Ok(Composite {
+        descriptor: encode(&items),
+        parts: composed_parts,
+    })
+}
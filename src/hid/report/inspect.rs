@@ -0,0 +1,58 @@
+//! Inspecting what a report descriptor declares.
+//!
+//! These functions read as much of a descriptor as can be parsed, ignoring any malformed tail.
+
+use super::{items, GlobalState, Item};
+use crate::hid::usage;
+
+/// Return the distinct report IDs declared by a report descriptor, in ascending order.
+pub fn report_ids(data: &[u8]) -> Vec<u8> {
+    let mut ids: Vec<u8> = items(data)
+        .map_while(Result::ok)
+        .filter_map(|item| match item {
+            Item::ReportId(id) => Some(id),
+            _ => None,
+        })
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+/// Return the usage page and usage ID of each top-level collection of a report descriptor, such
+/// as `(usage::page::GENERIC_DESKTOP, usage::generic_desktop::MOUSE)` for a mouse.
+///
+/// Hosts pick drivers by these usages, so they tell what kind of device a descriptor describes.
+pub fn top_level_usages(data: &[u8]) -> Vec<(u16, u16)> {
+    let mut usages = Vec::new();
+    let mut state = GlobalState::default();
+    let mut stack = Vec::new();
+    let mut depth = 0usize;
+    let mut usage = None;
+
+    for item in items(data).map_while(Result::ok) {
+        match item {
+            Item::Usage(id) => {
+                // Usages given in four bytes carry their own page.
+                usage = Some(if id > 0xffff {
+                    usage::split_extended(id)
+                } else {
+                    (state.usage_page.unwrap_or(0), id as u16)
+                });
+            },
+            Item::Collection(_) => {
+                if depth == 0 {
+                    usages.push(usage.unwrap_or((state.usage_page.unwrap_or(0), 0)));
+                }
+                depth += 1;
+                usage = None;
+            },
+            Item::EndCollection => depth = depth.saturating_sub(1),
+            Item::Push => stack.push(state),
+            Item::Pop => state = stack.pop().unwrap_or_default(),
+            item if item.is_main() => usage = None,
+            item => state.apply(&item),
+        }
+    }
+    usages
+}
@@ -0,0 +1,104 @@
+//! Sizes of the reports declared by a report descriptor.
+
+use super::{items, GlobalState, Item, ParseError, ReportType};
+use std::collections::BTreeMap;
+
+/// Sizes of the reports a descriptor declares, by type and report ID.
+///
+/// Reports are keyed by `None` when the descriptor declares no report IDs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReportLayout {
+    bits: BTreeMap<(ReportType, Option<u8>), u32>,
+}
+
+impl ReportLayout {
+    /// Return the length of a report in bits, excluding its report ID.
+    pub fn bits(&self, report_type: ReportType, report_id: Option<u8>) -> Option<u32> {
+        self.bits.get(&(report_type, report_id)).copied()
+    }
+
+    /// Return the length of a report in bytes, excluding its report ID.
+    pub fn len(&self, report_type: ReportType, report_id: Option<u8>) -> Option<usize> {
+        self.bits(report_type, report_id).map(|bits| bits.div_ceil(8) as usize)
+    }
+
+    /// Return the length of a report as sent on the interrupt channel, including the report ID
+    /// byte if it has one.
+    pub fn payload_len(&self, report_type: ReportType, report_id: Option<u8>) -> Option<usize> {
+        self.len(report_type, report_id)
+            .map(|len| len + usize::from(report_id.is_some()))
+    }
+
+    /// Return true if the descriptor declares no reports.
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// Return true if the reports are prefixed by report IDs.
+    pub fn uses_report_ids(&self) -> bool {
+        self.bits.keys().any(|(_, report_id)| report_id.is_some())
+    }
+
+    /// Iterate over the reports, with their lengths in bytes excluding report IDs.
+    pub fn iter(&self) -> impl Iterator<Item = (ReportType, Option<u8>, usize)> + '_ {
+        self.bits.iter()
+            .map(|(&(report_type, report_id), bits)| (report_type, report_id, bits.div_ceil(8) as usize))
+    }
+
+    /// Return the report IDs of the reports of the given type, or `None` for a report without
+    /// an ID.
+    pub fn report_ids(&self, report_type: ReportType) -> Vec<Option<u8>> {
+        self.bits.keys()
+            .filter(|(t, _)| *t == report_type)
+            .map(|&(_, report_id)| report_id)
+            .collect()
+    }
+
+    /// Iterate over the feature reports, with their lengths in bytes excluding report IDs.
+    ///
+    /// Hosts read and write feature reports on the control channel with GET_REPORT and
+    /// SET_REPORT, which name the report by its ID.
+    pub fn feature_reports(&self) -> impl Iterator<Item = (Option<u8>, usize)> + '_ {
+        self.iter()
+            .filter(|(report_type, _, _)| *report_type == ReportType::Feature)
+            .map(|(_, report_id, len)| (report_id, len))
+    }
+
+    /// Return the length of a feature report in bytes, excluding its report ID.
+    pub fn feature_len(&self, report_id: Option<u8>) -> Option<usize> {
+        self.len(ReportType::Feature, report_id)
+    }
+
+    /// Return the length of the longest report of the given type as sent on the interrupt
+    /// channel, which bounds the buffers a device needs.
+    pub fn max_payload_len(&self, report_type: ReportType) -> Option<usize> {
+        self.bits.keys()
+            .filter(|(t, _)| *t == report_type)
+            .filter_map(|&(t, report_id)| self.payload_len(t, report_id))
+            .max()
+    }
+}
+
+/// Compute the length of each report declared by a report descriptor.
+pub fn report_sizes(data: &[u8]) -> Result<ReportLayout, ParseError> {
+    let mut layout = ReportLayout::default();
+    let mut state = GlobalState::default();
+    let mut stack = Vec::new();
+
+    for item in items(data) {
+        let item = item?;
+        match item.report_field() {
+            Some((report_type, _)) => {
+                let bits = layout.bits.entry((report_type, state.report_id)).or_default();
+                *bits = bits.saturating_add(state.field_bits());
+            },
+            None => match item {
+                Item::Push => stack.push(state),
+                Item::Pop => state = stack.pop().unwrap_or_default(),
+                _ => state.apply(&item),
+            },
+        }
+    }
+
+    Ok(layout)
+}
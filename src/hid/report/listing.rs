@@ -0,0 +1,87 @@
+//! Human-readable listing of report descriptors.
+//!
+//! Each item is listed on its own line with its bytes, indented by the collections containing it,
+//! like the output of `hid-decode`. With the `usage-names` feature, usage pages and usages are
+//! named in comments after their items.
+
+use super::{items, Item};
+#[cfg(feature = "usage-names")]
+use crate::hid::usage;
+use std::fmt::{self, Write};
+
+/// Width of the column of item bytes, enough for a short item with four bytes of data.
+const BYTES_WIDTH: usize = 14;
+
+/// Format bytes as space-separated hex.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ")
+}
+
+/// Return the name of the usage page or usage an item declares, if known.
+#[cfg(feature = "usage-names")]
+fn annotation(item: &Item, usage_page: u16) -> Option<&'static str> {
+    match *item {
+        Item::UsagePage(page) => usage::page_name(page),
+        Item::Usage(id) | Item::UsageMinimum(id) | Item::UsageMaximum(id) => {
+            let (page, id) = match id {
+                0..=0xffff => (usage_page, id as u16),
+                _ => usage::split_extended(id),
+            };
+            usage::name(page, id)
+        },
+        _ => None,
+    }
+}
+
+#[cfg(not(feature = "usage-names"))]
+fn annotation(_item: &Item, _usage_page: u16) -> Option<&'static str> {
+    None
+}
+
+/// Write a listing of the items of a report descriptor, with every line indented by `indent`
+/// spaces.
+///
+/// Bytes which cannot be parsed are listed as malformed, rather than failing.
+pub fn write_listing<W: Write>(f: &mut W, data: &[u8], indent: usize) -> fmt::Result {
+    let mut depth = 0usize;
+    let mut usage_page = 0;
+    let mut pushed_pages = Vec::new();
+    let mut iter = items(data);
+    loop {
+        let start = iter.offset();
+        match iter.next() {
+            Some(Ok(item)) => {
+                match item {
+                    Item::EndCollection => depth = depth.saturating_sub(1),
+                    Item::UsagePage(page) => usage_page = page,
+                    Item::Push => pushed_pages.push(usage_page),
+                    Item::Pop => usage_page = pushed_pages.pop().unwrap_or_default(),
+                    _ => (),
+                }
+                write!(f, "{:indent$}{:width$}  {:depth$}{}", "", hex(&data[start..iter.offset()]),
+                       "", item, width = BYTES_WIDTH, depth = depth * 2)?;
+                match annotation(&item, usage_page) {
+                    Some(name) => writeln!(f, "  // {}", name)?,
+                    None => writeln!(f)?,
+                }
+                if let Item::Collection(_) = item {
+                    depth += 1;
+                }
+            },
+            Some(Err(e)) => {
+                writeln!(f, "{:indent$}{:width$}  Malformed ({})", "", hex(&data[start..]), e,
+                         width = BYTES_WIDTH)?;
+                return Ok(());
+            },
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Return a listing of the items of a report descriptor.
+pub fn listing(data: &[u8]) -> String {
+    let mut listing = String::new();
+    write_listing(&mut listing, data, 0)
+        .expect("writing to a String cannot fail");
+    listing
+}
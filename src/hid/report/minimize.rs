@@ -0,0 +1,81 @@
+//! Shrinking report descriptors without changing the reports they declare.
+//!
+//! Record sizes are limited by SDP servers and by the buffers of some hosts, and descriptors
+//! written by hand or composed from parts often repeat themselves.
+
+use super::{encode, item_type, parse, Item, ParseError};
+use std::collections::BTreeMap;
+
+/// Return the tag of a global item, if the item is one which sets a value.
+fn global_tag(item: &Item) -> Option<u8> {
+    match item {
+        Item::Push | Item::Pop => None,
+        item => item.short_parts()
+            .filter(|(t, _, _)| *t == item_type::GLOBAL)
+            .map(|(_, tag, _)| tag),
+    }
+}
+
+/// Drop the last usages of the run of items since the last main item, while they repeat the usage before
+/// them.
+///
+/// Once the usages run out, the last usage applies to every remaining field, so repeating it
+/// changes nothing. Usages are only dropped when no other local item follows them and no usage
+/// page lies between them, since some hosts resolve usage pages as usages are declared.
+fn trim_usages(output: &mut Vec<Item>, run_start: usize) {
+    loop {
+        let mut locals = output.iter().enumerate().skip(run_start).rev()
+            .filter(|(_, item)| item.is_local());
+        let (last, previous) = match (locals.next(), locals.next()) {
+            (Some((last, Item::Usage(a))), Some((previous, Item::Usage(b)))) if a == b =>
+                (last, previous),
+            _ => return,
+        };
+        if output[previous..last].iter().any(|item| matches!(item, Item::UsagePage(_))) {
+            return;
+        }
+        output.remove(last);
+    }
+}
+
+/// Minimize a list of items, dropping global items which set the value already in effect and
+/// usages which repeat the one before them.
+pub fn minimize_items(items: &[Item]) -> Vec<Item> {
+    let mut output = Vec::with_capacity(items.len());
+    let mut globals = BTreeMap::<u8, Item>::new();
+    let mut stack = Vec::new();
+    let mut run_start = 0;
+
+    for item in items {
+        if let Some(tag) = global_tag(item) {
+            if globals.get(&tag) != Some(item) {
+                globals.insert(tag, item.clone());
+                output.push(item.clone());
+            }
+            continue;
+        }
+        match item {
+            Item::Push => {
+                stack.push(globals.clone());
+                output.push(item.clone());
+            },
+            Item::Pop => {
+                globals = stack.pop().unwrap_or_default();
+                output.push(item.clone());
+            },
+            item => {
+                output.push(item.clone());
+                if item.is_main() {
+                    trim_usages(&mut output, run_start);
+                    run_start = output.len();
+                }
+            },
+        }
+    }
+    output
+}
+
+/// Minimize a report descriptor, also encoding every item in as few bytes as its value needs.
+pub fn minimize(data: &[u8]) -> Result<Vec<u8>, ParseError> {
+    parse(data).map(|items| encode(&minimize_items(&items)))
+}
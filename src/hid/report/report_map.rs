@@ -0,0 +1,86 @@
+//! Planning the Report Map of HID over GATT.
+//!
+//! HID over GATT carries the report descriptor unchanged in its Report Map characteristic, but
+//! gives each report its own Report characteristic, labelled by a Report Reference descriptor with
+//! the report ID and type. The plan lists the characteristics a GATT server needs to declare.
+
+use super::{report_sizes, ParseError, ReportType};
+use std::fmt::{self, Display, Formatter};
+
+/// Longest value of the Report Map characteristic, as for any GATT attribute value.
+pub const MAX_REPORT_MAP_LEN: usize = 512;
+
+/// Error type for descriptors which cannot be carried over GATT.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReportMapError {
+    Malformed(ParseError),
+    /// The descriptor is longer than `MAX_REPORT_MAP_LEN`.
+    TooLong(usize),
+}
+
+impl Display for ReportMapError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Malformed(e) => write!(f, "{}", e),
+            Self::TooLong(len) =>
+                write!(f, "report map is {} bytes long, longer than {} bytes", len, MAX_REPORT_MAP_LEN),
+        }
+    }
+}
+
+impl std::error::Error for ReportMapError {}
+
+/// Value of a Report Reference descriptor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ReportReference {
+    /// Report ID, or 0 if the descriptor declares no report IDs.
+    pub report_id: u8,
+    pub report_type: ReportType,
+}
+
+impl ReportReference {
+    /// Return the encoding of the descriptor value.
+    pub fn to_bytes(&self) -> [u8; 2] {
+        let report_type = match self.report_type {
+            ReportType::Input => 1,
+            ReportType::Output => 2,
+            ReportType::Feature => 3,
+        };
+        [self.report_id, report_type]
+    }
+}
+
+/// A Report characteristic to declare.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReportCharacteristic {
+    pub reference: ReportReference,
+    /// Length of the characteristic value, which never includes the report ID.
+    pub len: usize,
+}
+
+/// The Report Map and Report characteristics declaring a report descriptor over GATT.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReportMapPlan {
+    /// Value of the Report Map characteristic.
+    pub report_map: Vec<u8>,
+    /// Report characteristics, ordered by type and report ID.
+    pub reports: Vec<ReportCharacteristic>,
+}
+
+/// Plan the GATT characteristics carrying a report descriptor.
+pub fn report_map_plan(data: &[u8]) -> Result<ReportMapPlan, ReportMapError> {
+    if data.len() > MAX_REPORT_MAP_LEN {
+        return Err(ReportMapError::TooLong(data.len()));
+    }
+    let layout = report_sizes(data).map_err(ReportMapError::Malformed)?;
+    let reports = layout.iter()
+        .map(|(report_type, report_id, len)| ReportCharacteristic {
+            reference: ReportReference { report_id: report_id.unwrap_or(0), report_type },
+            len,
+        })
+        .collect();
+    Ok(ReportMapPlan {
+        report_map: data.to_vec(),
+        reports,
+    })
+}
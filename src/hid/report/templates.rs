@@ -0,0 +1,254 @@
+//! Report descriptors for common kinds of devices.
+
+use super::{
+    collection, encode, minimize_items, report_sizes, DescriptorBuilder, Item, MainFlags,
+    ReportLayout, ReportType,
+};
+use crate::hid::usage::{consumer, digitizer, generic_desktop, page};
+
+/// Media keys of a typical remote: play/pause, next and previous track, mute, and volume.
+pub const MEDIA_KEYS: [u16; 6] = [
+    consumer::PLAY_PAUSE,
+    consumer::SCAN_NEXT_TRACK,
+    consumer::SCAN_PREVIOUS_TRACK,
+    consumer::MUTE,
+    consumer::VOLUME_INCREMENT,
+    consumer::VOLUME_DECREMENT,
+];
+
+/// Create a consumer control descriptor with one bit per key, for the given usages on the Consumer
+/// page, such as `MEDIA_KEYS`.
+///
+/// The input report holds bit `i` for `keys[i]`, set while the key is pressed, padded to a whole
+/// number of bytes. It is prefixed by `report_id`, if given.
+pub fn consumer_control_descriptor(keys: &[u16], report_id: Option<u8>) -> Vec<u8> {
+    let key_bits = keys.len() as u32;
+    DescriptorBuilder::new()
+        .usage_page(page::CONSUMER)
+        .usage(consumer::CONSUMER_CONTROL)
+        .collection(collection::APPLICATION, |builder| {
+            let builder = match report_id {
+                Some(report_id) => builder.report_id(report_id),
+                None => builder,
+            };
+            builder
+                .logical_range(0, 1)
+                .report_size(1)
+                .report_count(key_bits)
+                .usages(keys)
+                .input(MainFlags::DATA_VARIABLE_ABSOLUTE)
+                .padding(ReportType::Input, (8 - key_bits % 8) % 8)
+        })
+        .build()
+}
+
+/// An axis of a gamepad, reported as one field of the input report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Axis {
+    /// Usage page of the axis, such as `usage::page::GENERIC_DESKTOP`.
+    pub usage_page: u16,
+    /// Usage of the axis, such as `usage::generic_desktop::X`.
+    pub usage: u16,
+    pub minimum: i32,
+    pub maximum: i32,
+    /// Size of the field, in bits.
+    pub bits: u32,
+}
+
+impl Axis {
+    pub const fn new(usage_page: u16, usage: u16, minimum: i32, maximum: i32, bits: u32) -> Self {
+        Axis { usage_page, usage, minimum, maximum, bits }
+    }
+
+    /// Create an 8-bit stick axis on the Generic Desktop page, centered at 0.
+    pub const fn stick(usage: u16) -> Self {
+        Self::new(page::GENERIC_DESKTOP, usage, -127, 127, 8)
+    }
+
+    /// Create an 8-bit trigger axis, from 0 when released to 255 when fully pressed.
+    pub const fn trigger(usage_page: u16, usage: u16) -> Self {
+        Self::new(usage_page, usage, 0, 255, 8)
+    }
+}
+
+/// Create a gamepad descriptor with the given number of buttons, axes, and optionally a hat
+/// switch, with the layout of its input report.
+///
+/// The input report holds one bit per button, padded to a whole byte; then, if `hat` is set, a
+/// 4-bit hat switch from 0 (north) clockwise to 7, with 8 when centered, padded to a whole byte;
+/// then each axis in order, padded to a whole byte at the end.
+pub fn gamepad_descriptor(buttons: u8, axes: &[Axis], hat: bool) -> (Vec<u8>, ReportLayout) {
+    let buttons = u32::from(buttons);
+    let mut builder = DescriptorBuilder::new()
+        .usage_page(page::GENERIC_DESKTOP)
+        .usage(generic_desktop::GAMEPAD)
+        .item(Item::Collection(collection::APPLICATION));
+
+    if buttons > 0 {
+        builder = builder
+            .usage_page(page::BUTTON)
+            .usage_range(1, buttons as u16)
+            .logical_range(0, 1)
+            .report_size(1)
+            .report_count(buttons)
+            .input(MainFlags::DATA_VARIABLE_ABSOLUTE)
+            .padding(ReportType::Input, (8 - buttons % 8) % 8);
+    }
+
+    if hat {
+        builder = builder
+            .usage_page(page::GENERIC_DESKTOP)
+            .usage(generic_desktop::HAT_SWITCH)
+            .logical_range(0, 7)
+            .physical_range(0, 315)
+            // Degrees, in the English rotation system.
+            .item(Item::Unit(0x14))
+            .report_size(4)
+            .report_count(1)
+            .input(MainFlags(MainFlags::VARIABLE | MainFlags::NULL_STATE))
+            // Leave units and physical values unset for the fields after the hat switch.
+            .item(Item::Unit(0))
+            .physical_range(0, 0)
+            .padding(ReportType::Input, 4);
+    }
+
+    let mut axis_bits = 0;
+    for axis in axes {
+        builder = builder
+            .usage_page(axis.usage_page)
+            .usage(axis.usage)
+            .logical_range(axis.minimum, axis.maximum)
+            .report_size(axis.bits)
+            .report_count(1)
+            .input(MainFlags::DATA_VARIABLE_ABSOLUTE);
+        axis_bits += axis.bits;
+    }
+    builder = builder
+        .padding(ReportType::Input, (8 - axis_bits % 8) % 8)
+        .item(Item::EndCollection);
+
+    let descriptor = encode(&minimize_items(builder.items()));
+    let layout = report_sizes(&descriptor)
+        .expect("generated descriptors are well-formed");
+    (descriptor, layout)
+}
+
+/// Create a multi-touch digitizer descriptor reporting up to `contacts` contacts at once, with
+/// the layout of its reports.
+///
+/// The input report holds, for each contact, a byte whose lowest bit is set while the contact
+/// touches, a byte of contact identifier, 16-bit X and Y positions from 0 to `x_maximum` and
+/// `y_maximum`, and if `pressure_maximum` is given, a 16-bit pressure from 0 to that maximum. A
+/// final byte gives the number of valid contacts. The feature report holds a byte giving the
+/// maximum number of contacts, as some hosts require.
+pub fn digitizer_descriptor(
+    contacts: u8,
+    x_maximum: u16,
+    y_maximum: u16,
+    pressure_maximum: Option<u16>,
+) -> (Vec<u8>, ReportLayout) {
+    let mut builder = DescriptorBuilder::new()
+        .usage_page(page::DIGITIZER)
+        .usage(digitizer::TOUCH_SCREEN)
+        .item(Item::Collection(collection::APPLICATION));
+
+    for _ in 0..contacts {
+        builder = builder
+            .usage_page(page::DIGITIZER)
+            .usage(digitizer::FINGER)
+            .collection(collection::LOGICAL, |builder| {
+                let builder = builder
+                    .usage(digitizer::TIP_SWITCH)
+                    .logical_range(0, 1)
+                    .report_size(1)
+                    .report_count(1)
+                    .input(MainFlags::DATA_VARIABLE_ABSOLUTE)
+                    .padding(ReportType::Input, 7)
+                    .usage(digitizer::CONTACT_IDENTIFIER)
+                    .logical_range(0, i32::from(contacts.saturating_sub(1)))
+                    .report_size(8)
+                    .report_count(1)
+                    .input(MainFlags::DATA_VARIABLE_ABSOLUTE)
+                    .usage_page(page::GENERIC_DESKTOP)
+                    .usage(generic_desktop::X)
+                    .logical_range(0, x_maximum.into())
+                    .report_size(16)
+                    .input(MainFlags::DATA_VARIABLE_ABSOLUTE)
+                    .usage(generic_desktop::Y)
+                    .logical_range(0, y_maximum.into())
+                    .input(MainFlags::DATA_VARIABLE_ABSOLUTE);
+                match pressure_maximum {
+                    Some(pressure_maximum) => builder
+                        .usage_page(page::DIGITIZER)
+                        .usage(digitizer::TIP_PRESSURE)
+                        .logical_range(0, pressure_maximum.into())
+                        .input(MainFlags::DATA_VARIABLE_ABSOLUTE),
+                    None => builder,
+                }
+            });
+    }
+
+    builder = builder
+        .usage_page(page::DIGITIZER)
+        .usage(digitizer::CONTACT_COUNT)
+        .logical_range(0, contacts.into())
+        .report_size(8)
+        .report_count(1)
+        .input(MainFlags::DATA_VARIABLE_ABSOLUTE)
+        .usage(digitizer::CONTACT_COUNT_MAXIMUM)
+        .feature(MainFlags::DATA_VARIABLE_ABSOLUTE)
+        .item(Item::EndCollection);
+
+    let descriptor = encode(&minimize_items(builder.items()));
+    let layout = report_sizes(&descriptor)
+        .expect("generated descriptors are well-formed");
+    (descriptor, layout)
+}
+
+/// Create a pen digitizer descriptor, as of a graphics tablet, with the layout of its report.
+///
+/// The input report holds a byte whose lowest bits are the tip switch, the barrel switch and
+/// in-range, set while the pen is near enough to be tracked, then 16-bit X and Y positions from
+/// 0 to `x_maximum` and `y_maximum`, and a 16-bit tip pressure from 0 to `pressure_maximum`.
+pub fn pen_descriptor(
+    x_maximum: u16,
+    y_maximum: u16,
+    pressure_maximum: u16,
+) -> (Vec<u8>, ReportLayout) {
+    let builder = DescriptorBuilder::new()
+        .usage_page(page::DIGITIZER)
+        .usage(digitizer::PEN)
+        .collection(collection::APPLICATION, |builder| {
+            builder
+                .usage(digitizer::STYLUS)
+                .collection(collection::PHYSICAL, |builder| {
+                    builder
+                        .usage(digitizer::TIP_SWITCH)
+                        .usage(digitizer::BARREL_SWITCH)
+                        .usage(digitizer::IN_RANGE)
+                        .logical_range(0, 1)
+                        .report_size(1)
+                        .report_count(3)
+                        .input(MainFlags::DATA_VARIABLE_ABSOLUTE)
+                        .padding(ReportType::Input, 5)
+                        .usage_page(page::GENERIC_DESKTOP)
+                        .usage(generic_desktop::X)
+                        .logical_range(0, x_maximum.into())
+                        .report_size(16)
+                        .report_count(1)
+                        .input(MainFlags::DATA_VARIABLE_ABSOLUTE)
+                        .usage(generic_desktop::Y)
+                        .logical_range(0, y_maximum.into())
+                        .input(MainFlags::DATA_VARIABLE_ABSOLUTE)
+                        .usage_page(page::DIGITIZER)
+                        .usage(digitizer::TIP_PRESSURE)
+                        .logical_range(0, pressure_maximum.into())
+                        .input(MainFlags::DATA_VARIABLE_ABSOLUTE)
+                })
+        });
+
+    let descriptor = encode(&minimize_items(builder.items()));
+    let layout = report_sizes(&descriptor)
+        .expect("generated descriptors are well-formed");
+    (descriptor, layout)
+}
@@ -0,0 +1,140 @@
+//! Checking report descriptors against the rules of 6.2.2 of the USB HID specification.
+
+use super::{items, GlobalState, Item, ParseError, ReportType};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+/// A rule broken by a report descriptor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DescriptorViolation {
+    /// The descriptor could not be split into items.
+    Malformed(ParseError),
+    /// An end collection item at the given offset closes no collection.
+    UnmatchedEndCollection(usize),
+    /// The descriptor ends with the given number of collections still open.
+    UnclosedCollections(usize),
+    /// A pop item at the given offset has no matching push.
+    UnmatchedPop(usize),
+    /// The main item at the given offset is declared before a global item it requires.
+    MissingGlobalItem { offset: usize, item: &'static str },
+    /// A report ID item at the given offset declares the reserved ID 0.
+    ReportIdZero(usize),
+    /// The main item at the given offset has no report ID, but other reports do.
+    MissingReportId(usize),
+    /// A report is not a whole number of bytes long.
+    UnalignedReport { report_type: ReportType, report_id: Option<u8>, bits: u32 },
+}
+
+impl Display for DescriptorViolation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Malformed(e) => write!(f, "{}", e),
+            Self::UnmatchedEndCollection(offset) =>
+                write!(f, "at offset {}: end collection without a collection", offset),
+            Self::UnclosedCollections(count) =>
+                write!(f, "{} collections are never closed", count),
+            Self::UnmatchedPop(offset) =>
+                write!(f, "at offset {}: pop without a push", offset),
+            Self::MissingGlobalItem { offset, item } =>
+                write!(f, "at offset {}: main item declared before {}", offset, item),
+            Self::ReportIdZero(offset) =>
+                write!(f, "at offset {}: report ID 0 is reserved", offset),
+            Self::MissingReportId(offset) =>
+                write!(f, "at offset {}: main item without a report ID, but other reports have IDs", offset),
+            Self::UnalignedReport { report_type, report_id: Some(id), bits } =>
+                write!(f, "{} report {} is {} bits long, not a whole number of bytes", report_type, id, bits),
+            Self::UnalignedReport { report_type, report_id: None, bits } =>
+                write!(f, "{} report is {} bits long, not a whole number of bytes", report_type, bits),
+        }
+    }
+}
+
+impl std::error::Error for DescriptorViolation {}
+
+/// Check a report descriptor for balanced collections, global items declared before the main
+/// items which need them, consistent report IDs, and reports of whole bytes.
+pub fn validate_report_descriptor(data: &[u8]) -> Result<(), Vec<DescriptorViolation>> {
+    let mut violations = Vec::new();
+
+    // Report IDs apply to every report once any are declared, so look ahead for them.
+    let uses_report_ids = items(data).any(|item| matches!(item, Ok(Item::ReportId(_))));
+
+    let mut state = GlobalState::default();
+    let mut stack = Vec::new();
+    let mut open_collections = 0usize;
+    let mut report_bits = BTreeMap::<(ReportType, Option<u8>), u32>::new();
+
+    let mut iter = items(data);
+    loop {
+        let offset = iter.offset();
+        let item = match iter.next() {
+            Some(Ok(item)) => item,
+            Some(Err(e)) => {
+                violations.push(DescriptorViolation::Malformed(e));
+                break;
+            },
+            None => break,
+        };
+
+        match item {
+            Item::Collection(_) => open_collections += 1,
+            Item::EndCollection => match open_collections.checked_sub(1) {
+                Some(remaining) => open_collections = remaining,
+                None => violations.push(DescriptorViolation::UnmatchedEndCollection(offset)),
+            },
+            Item::Push => stack.push(state),
+            Item::Pop => match stack.pop() {
+                Some(pushed) => state = pushed,
+                None => violations.push(DescriptorViolation::UnmatchedPop(offset)),
+            },
+            Item::ReportId(0) => {
+                violations.push(DescriptorViolation::ReportIdZero(offset));
+                state.apply(&item);
+            },
+            Item::Input(_) | Item::Output(_) | Item::Feature(_) => {
+                let (report_type, flags) = item.report_field()
+                    .expect("input, output and feature items are report fields");
+
+                let mut required = vec![
+                    ("report size", state.report_size.is_some()),
+                    ("report count", state.report_count.is_some()),
+                ];
+                // Constant fields are padding, and need no values or usages.
+                if !flags.is_constant() {
+                    required.push(("usage page", state.usage_page.is_some()));
+                    required.push(("logical minimum", state.logical_minimum.is_some()));
+                    required.push(("logical maximum", state.logical_maximum.is_some()));
+                }
+                for (name, present) in required {
+                    if !present {
+                        violations.push(DescriptorViolation::MissingGlobalItem { offset, item: name });
+                    }
+                }
+
+                if uses_report_ids && state.report_id.is_none() {
+                    violations.push(DescriptorViolation::MissingReportId(offset));
+                }
+
+                let bits = report_bits.entry((report_type, state.report_id)).or_default();
+                *bits = bits.saturating_add(state.field_bits());
+            },
+            _ => state.apply(&item),
+        }
+    }
+
+    if open_collections > 0 {
+        violations.push(DescriptorViolation::UnclosedCollections(open_collections));
+    }
+
+    for ((report_type, report_id), bits) in report_bits {
+        if bits % 8 != 0 {
+            violations.push(DescriptorViolation::UnalignedReport { report_type, report_id, bits });
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
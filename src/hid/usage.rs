@@ -0,0 +1,213 @@
+//! Usage pages and usage IDs, from the HID Usage Tables.
+//!
+//! Usage pages are given by `page`, and each module after it holds the common usages of one page.
+//! Only the usages most devices need are listed; the HID Usage Tables define many more.
+//!
+//! With the `usage-names` feature, `name` and `page_name` look up the names of usages, for
+//! diagnostics.
+
+#[cfg(feature = "usage-names")]
+mod names;
+
+#[cfg(feature = "usage-names")]
+pub use names::{name, page_name};
+
+/// Usage pages.
+pub mod page {
+    pub const GENERIC_DESKTOP: u16 = 0x01;
+    pub const SIMULATION: u16 = 0x02;
+    pub const VR: u16 = 0x03;
+    pub const SPORT: u16 = 0x04;
+    pub const GAME: u16 = 0x05;
+    pub const GENERIC_DEVICE: u16 = 0x06;
+    pub const KEYBOARD: u16 = 0x07;
+    pub const LED: u16 = 0x08;
+    pub const BUTTON: u16 = 0x09;
+    pub const ORDINAL: u16 = 0x0a;
+    pub const TELEPHONY: u16 = 0x0b;
+    pub const CONSUMER: u16 = 0x0c;
+    pub const DIGITIZER: u16 = 0x0d;
+    pub const HAPTICS: u16 = 0x0e;
+    pub const PHYSICAL_INPUT_DEVICE: u16 = 0x0f;
+    pub const UNICODE: u16 = 0x10;
+    pub const ALPHANUMERIC_DISPLAY: u16 = 0x14;
+    pub const SENSORS: u16 = 0x20;
+    pub const BATTERY_SYSTEM: u16 = 0x85;
+    /// First of the pages reserved for vendors, which run to 0xffff.
+    pub const VENDOR_DEFINED: u16 = 0xff00;
+}
+
+/// Usages on the Generic Desktop page.
+pub mod generic_desktop {
+    pub const POINTER: u16 = 0x01;
+    pub const MOUSE: u16 = 0x02;
+    pub const JOYSTICK: u16 = 0x04;
+    pub const GAMEPAD: u16 = 0x05;
+    pub const KEYBOARD: u16 = 0x06;
+    pub const KEYPAD: u16 = 0x07;
+    pub const MULTI_AXIS_CONTROLLER: u16 = 0x08;
+    pub const X: u16 = 0x30;
+    pub const Y: u16 = 0x31;
+    pub const Z: u16 = 0x32;
+    pub const RX: u16 = 0x33;
+    pub const RY: u16 = 0x34;
+    pub const RZ: u16 = 0x35;
+    pub const SLIDER: u16 = 0x36;
+    pub const DIAL: u16 = 0x37;
+    pub const WHEEL: u16 = 0x38;
+    pub const HAT_SWITCH: u16 = 0x39;
+    pub const SYSTEM_CONTROL: u16 = 0x80;
+    pub const SYSTEM_POWER_DOWN: u16 = 0x81;
+    pub const SYSTEM_SLEEP: u16 = 0x82;
+    pub const SYSTEM_WAKE_UP: u16 = 0x83;
+    pub const DPAD_UP: u16 = 0x90;
+    pub const DPAD_DOWN: u16 = 0x91;
+    pub const DPAD_RIGHT: u16 = 0x92;
+    pub const DPAD_LEFT: u16 = 0x93;
+}
+
+/// Usages on the Simulation Controls page.
+pub mod simulation {
+    pub const RUDDER: u16 = 0xba;
+    pub const THROTTLE: u16 = 0xbb;
+    pub const ACCELERATOR: u16 = 0xc4;
+    pub const BRAKE: u16 = 0xc5;
+    pub const STEERING: u16 = 0xc8;
+}
+
+/// Usages on the Keyboard/Keypad page, which are the key codes of keyboard reports.
+pub mod keyboard {
+    pub const NO_EVENT: u16 = 0x00;
+    pub const ERROR_ROLL_OVER: u16 = 0x01;
+    pub const A: u16 = 0x04;
+    pub const Z: u16 = 0x1d;
+    pub const KEY_1: u16 = 0x1e;
+    pub const KEY_0: u16 = 0x27;
+    pub const ENTER: u16 = 0x28;
+    pub const ESCAPE: u16 = 0x29;
+    pub const BACKSPACE: u16 = 0x2a;
+    pub const TAB: u16 = 0x2b;
+    pub const SPACE: u16 = 0x2c;
+    pub const CAPS_LOCK: u16 = 0x39;
+    pub const F1: u16 = 0x3a;
+    pub const F12: u16 = 0x45;
+    pub const PRINT_SCREEN: u16 = 0x46;
+    pub const SCROLL_LOCK: u16 = 0x47;
+    pub const PAUSE: u16 = 0x48;
+    pub const INSERT: u16 = 0x49;
+    pub const HOME: u16 = 0x4a;
+    pub const PAGE_UP: u16 = 0x4b;
+    pub const DELETE: u16 = 0x4c;
+    pub const END: u16 = 0x4d;
+    pub const PAGE_DOWN: u16 = 0x4e;
+    pub const RIGHT_ARROW: u16 = 0x4f;
+    pub const LEFT_ARROW: u16 = 0x50;
+    pub const DOWN_ARROW: u16 = 0x51;
+    pub const UP_ARROW: u16 = 0x52;
+    pub const NUM_LOCK: u16 = 0x53;
+    pub const APPLICATION: u16 = 0x65;
+    pub const LEFT_CONTROL: u16 = 0xe0;
+    pub const LEFT_SHIFT: u16 = 0xe1;
+    pub const LEFT_ALT: u16 = 0xe2;
+    pub const LEFT_GUI: u16 = 0xe3;
+    pub const RIGHT_CONTROL: u16 = 0xe4;
+    pub const RIGHT_SHIFT: u16 = 0xe5;
+    pub const RIGHT_ALT: u16 = 0xe6;
+    pub const RIGHT_GUI: u16 = 0xe7;
+}
+
+/// Usages on the LED page.
+pub mod led {
+    pub const NUM_LOCK: u16 = 0x01;
+    pub const CAPS_LOCK: u16 = 0x02;
+    pub const SCROLL_LOCK: u16 = 0x03;
+    pub const COMPOSE: u16 = 0x04;
+    pub const KANA: u16 = 0x05;
+}
+
+/// Usages on the Button page. Button n has usage ID n.
+pub mod button {
+    pub const NO_BUTTON: u16 = 0x00;
+    pub const PRIMARY: u16 = 0x01;
+    pub const SECONDARY: u16 = 0x02;
+    pub const TERTIARY: u16 = 0x03;
+}
+
+/// Usages on the Consumer page.
+pub mod consumer {
+    pub const CONSUMER_CONTROL: u16 = 0x01;
+    pub const POWER: u16 = 0x30;
+    pub const MENU: u16 = 0x40;
+    pub const PLAY: u16 = 0xb0;
+    pub const PAUSE: u16 = 0xb1;
+    pub const RECORD: u16 = 0xb2;
+    pub const FAST_FORWARD: u16 = 0xb3;
+    pub const REWIND: u16 = 0xb4;
+    pub const SCAN_NEXT_TRACK: u16 = 0xb5;
+    pub const SCAN_PREVIOUS_TRACK: u16 = 0xb6;
+    pub const STOP: u16 = 0xb7;
+    pub const EJECT: u16 = 0xb8;
+    pub const PLAY_PAUSE: u16 = 0xcd;
+    pub const MUTE: u16 = 0xe2;
+    pub const VOLUME_INCREMENT: u16 = 0xe9;
+    pub const VOLUME_DECREMENT: u16 = 0xea;
+    pub const AL_CALCULATOR: u16 = 0x192;
+    pub const AC_SEARCH: u16 = 0x221;
+    pub const AC_HOME: u16 = 0x223;
+    pub const AC_BACK: u16 = 0x224;
+    pub const AC_FORWARD: u16 = 0x225;
+}
+
+/// Usages on the Digitizers page.
+pub mod digitizer {
+    pub const DIGITIZER: u16 = 0x01;
+    pub const PEN: u16 = 0x02;
+    pub const TOUCH_SCREEN: u16 = 0x04;
+    pub const TOUCH_PAD: u16 = 0x05;
+    pub const STYLUS: u16 = 0x20;
+    pub const FINGER: u16 = 0x22;
+    pub const DEVICE_CONFIGURATION: u16 = 0x0e;
+    pub const TIP_PRESSURE: u16 = 0x30;
+    pub const IN_RANGE: u16 = 0x32;
+    pub const INVERT: u16 = 0x3c;
+    pub const TIP_SWITCH: u16 = 0x42;
+    pub const BARREL_SWITCH: u16 = 0x44;
+    pub const ERASER: u16 = 0x45;
+    pub const CONFIDENCE: u16 = 0x47;
+    pub const WIDTH: u16 = 0x48;
+    pub const HEIGHT: u16 = 0x49;
+    pub const CONTACT_IDENTIFIER: u16 = 0x51;
+    pub const DEVICE_MODE: u16 = 0x52;
+    pub const CONTACT_COUNT: u16 = 0x54;
+    pub const CONTACT_COUNT_MAXIMUM: u16 = 0x55;
+    pub const SCAN_TIME: u16 = 0x56;
+}
+
+/// Return the extended usage of a usage ID on a page, as given by a four-byte usage item.
+pub const fn extended(page: u16, id: u16) -> u32 {
+    (page as u32) << 16 | id as u32
+}
+
+/// Split an extended usage into its page and usage ID.
+pub const fn split_extended(usage: u32) -> (u16, u16) {
+    ((usage >> 16) as u16, usage as u16)
+}
+
+/// Describe a usage for diagnostics, such as "Generic Desktop / Mouse", or "0x01 / 0x02" for
+/// usages without known names.
+///
+/// Names are only known with the `usage-names` feature.
+pub fn describe(usage_page: u16, id: u16) -> String {
+    #[cfg(feature = "usage-names")]
+    {
+        let page = page_name(usage_page)
+            .map_or_else(|| format!("0x{:02x}", usage_page), String::from);
+        let usage = name(usage_page, id)
+            .map_or_else(|| format!("0x{:02x}", id), String::from);
+        format!("{} / {}", page, usage)
+    }
+    #[cfg(not(feature = "usage-names"))]
+    {
+        format!("0x{:02x} / 0x{:02x}", usage_page, id)
+    }
+}
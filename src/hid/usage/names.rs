@@ -0,0 +1,277 @@
+//! Names of usage pages and usages, from the HID Usage Tables.
+
+use super::page;
+
+/// Names of usage pages.
+const PAGES: &[(u16, &str)] = &[
+    (page::GENERIC_DESKTOP, "Generic Desktop"),
+    (page::SIMULATION, "Simulation Controls"),
+    (page::VR, "VR Controls"),
+    (page::SPORT, "Sport Controls"),
+    (page::GAME, "Game Controls"),
+    (page::GENERIC_DEVICE, "Generic Device Controls"),
+    (page::KEYBOARD, "Keyboard/Keypad"),
+    (page::LED, "LED"),
+    (page::BUTTON, "Button"),
+    (page::ORDINAL, "Ordinal"),
+    (page::TELEPHONY, "Telephony Device"),
+    (page::CONSUMER, "Consumer"),
+    (page::DIGITIZER, "Digitizers"),
+    (page::HAPTICS, "Haptics"),
+    (page::PHYSICAL_INPUT_DEVICE, "Physical Input Device"),
+    (page::UNICODE, "Unicode"),
+    (page::ALPHANUMERIC_DISPLAY, "Alphanumeric Display"),
+    (page::SENSORS, "Sensors"),
+    (page::BATTERY_SYSTEM, "Battery System"),
+];
+
+const GENERIC_DESKTOP: &[(u16, &str)] = &[
+    (0x01, "Pointer"),
+    (0x02, "Mouse"),
+    (0x04, "Joystick"),
+    (0x05, "Gamepad"),
+    (0x06, "Keyboard"),
+    (0x07, "Keypad"),
+    (0x08, "Multi-axis Controller"),
+    (0x30, "X"),
+    (0x31, "Y"),
+    (0x32, "Z"),
+    (0x33, "Rx"),
+    (0x34, "Ry"),
+    (0x35, "Rz"),
+    (0x36, "Slider"),
+    (0x37, "Dial"),
+    (0x38, "Wheel"),
+    (0x39, "Hat Switch"),
+    (0x3a, "Counted Buffer"),
+    (0x3b, "Byte Count"),
+    (0x3c, "Motion Wakeup"),
+    (0x3d, "Start"),
+    (0x3e, "Select"),
+    (0x80, "System Control"),
+    (0x81, "System Power Down"),
+    (0x82, "System Sleep"),
+    (0x83, "System Wake Up"),
+    (0x90, "D-pad Up"),
+    (0x91, "D-pad Down"),
+    (0x92, "D-pad Right"),
+    (0x93, "D-pad Left"),
+];
+
+const SIMULATION: &[(u16, &str)] = &[
+    (0xba, "Rudder"),
+    (0xbb, "Throttle"),
+    (0xc4, "Accelerator"),
+    (0xc5, "Brake"),
+    (0xc8, "Steering"),
+];
+
+const KEYBOARD: &[(u16, &str)] = &[
+    (0x00, "No Event"),
+    (0x01, "Error Roll Over"),
+    (0x02, "POST Fail"),
+    (0x03, "Error Undefined"),
+    (0x04, "A"),
+    (0x05, "B"),
+    (0x06, "C"),
+    (0x07, "D"),
+    (0x08, "E"),
+    (0x09, "F"),
+    (0x0a, "G"),
+    (0x0b, "H"),
+    (0x0c, "I"),
+    (0x0d, "J"),
+    (0x0e, "K"),
+    (0x0f, "L"),
+    (0x10, "M"),
+    (0x11, "N"),
+    (0x12, "O"),
+    (0x13, "P"),
+    (0x14, "Q"),
+    (0x15, "R"),
+    (0x16, "S"),
+    (0x17, "T"),
+    (0x18, "U"),
+    (0x19, "V"),
+    (0x1a, "W"),
+    (0x1b, "X"),
+    (0x1c, "Y"),
+    (0x1d, "Z"),
+    (0x1e, "1"),
+    (0x1f, "2"),
+    (0x20, "3"),
+    (0x21, "4"),
+    (0x22, "5"),
+    (0x23, "6"),
+    (0x24, "7"),
+    (0x25, "8"),
+    (0x26, "9"),
+    (0x27, "0"),
+    (0x28, "Enter"),
+    (0x29, "Escape"),
+    (0x2a, "Backspace"),
+    (0x2b, "Tab"),
+    (0x2c, "Space"),
+    (0x2d, "Minus"),
+    (0x2e, "Equals"),
+    (0x2f, "Left Bracket"),
+    (0x30, "Right Bracket"),
+    (0x31, "Backslash"),
+    (0x32, "Non-US Hash"),
+    (0x33, "Semicolon"),
+    (0x34, "Apostrophe"),
+    (0x35, "Grave Accent"),
+    (0x36, "Comma"),
+    (0x37, "Period"),
+    (0x38, "Slash"),
+    (0x39, "Caps Lock"),
+    (0x3a, "F1"),
+    (0x3b, "F2"),
+    (0x3c, "F3"),
+    (0x3d, "F4"),
+    (0x3e, "F5"),
+    (0x3f, "F6"),
+    (0x40, "F7"),
+    (0x41, "F8"),
+    (0x42, "F9"),
+    (0x43, "F10"),
+    (0x44, "F11"),
+    (0x45, "F12"),
+    (0x46, "Print Screen"),
+    (0x47, "Scroll Lock"),
+    (0x48, "Pause"),
+    (0x49, "Insert"),
+    (0x4a, "Home"),
+    (0x4b, "Page Up"),
+    (0x4c, "Delete"),
+    (0x4d, "End"),
+    (0x4e, "Page Down"),
+    (0x4f, "Right Arrow"),
+    (0x50, "Left Arrow"),
+    (0x51, "Down Arrow"),
+    (0x52, "Up Arrow"),
+    (0x53, "Num Lock"),
+    (0x54, "Keypad Slash"),
+    (0x55, "Keypad Asterisk"),
+    (0x56, "Keypad Minus"),
+    (0x57, "Keypad Plus"),
+    (0x58, "Keypad Enter"),
+    (0x59, "Keypad 1"),
+    (0x5a, "Keypad 2"),
+    (0x5b, "Keypad 3"),
+    (0x5c, "Keypad 4"),
+    (0x5d, "Keypad 5"),
+    (0x5e, "Keypad 6"),
+    (0x5f, "Keypad 7"),
+    (0x60, "Keypad 8"),
+    (0x61, "Keypad 9"),
+    (0x62, "Keypad 0"),
+    (0x63, "Keypad Period"),
+    (0x64, "Non-US Backslash"),
+    (0x65, "Application"),
+    (0xe0, "Left Control"),
+    (0xe1, "Left Shift"),
+    (0xe2, "Left Alt"),
+    (0xe3, "Left GUI"),
+    (0xe4, "Right Control"),
+    (0xe5, "Right Shift"),
+    (0xe6, "Right Alt"),
+    (0xe7, "Right GUI"),
+];
+
+const LED: &[(u16, &str)] = &[
+    (0x01, "Num Lock"),
+    (0x02, "Caps Lock"),
+    (0x03, "Scroll Lock"),
+    (0x04, "Compose"),
+    (0x05, "Kana"),
+];
+
+const CONSUMER: &[(u16, &str)] = &[
+    (0x01, "Consumer Control"),
+    (0x30, "Power"),
+    (0x40, "Menu"),
+    (0xb0, "Play"),
+    (0xb1, "Pause"),
+    (0xb2, "Record"),
+    (0xb3, "Fast Forward"),
+    (0xb4, "Rewind"),
+    (0xb5, "Scan Next Track"),
+    (0xb6, "Scan Previous Track"),
+    (0xb7, "Stop"),
+    (0xb8, "Eject"),
+    (0xcd, "Play/Pause"),
+    (0xe2, "Mute"),
+    (0xe9, "Volume Increment"),
+    (0xea, "Volume Decrement"),
+    (0x192, "AL Calculator"),
+    (0x221, "AC Search"),
+    (0x223, "AC Home"),
+    (0x224, "AC Back"),
+    (0x225, "AC Forward"),
+];
+
+const DIGITIZER: &[(u16, &str)] = &[
+    (0x01, "Digitizer"),
+    (0x02, "Pen"),
+    (0x04, "Touch Screen"),
+    (0x05, "Touch Pad"),
+    (0x0e, "Device Configuration"),
+    (0x20, "Stylus"),
+    (0x22, "Finger"),
+    (0x30, "Tip Pressure"),
+    (0x32, "In Range"),
+    (0x3c, "Invert"),
+    (0x42, "Tip Switch"),
+    (0x44, "Barrel Switch"),
+    (0x45, "Eraser"),
+    (0x47, "Confidence"),
+    (0x48, "Width"),
+    (0x49, "Height"),
+    (0x51, "Contact Identifier"),
+    (0x52, "Device Mode"),
+    (0x54, "Contact Count"),
+    (0x55, "Contact Count Maximum"),
+    (0x56, "Scan Time"),
+];
+
+/// Names of buttons, from button 1.
+const BUTTONS: [&str; 32] = [
+    "Button 1", "Button 2", "Button 3", "Button 4", "Button 5", "Button 6", "Button 7", "Button 8",
+    "Button 9", "Button 10", "Button 11", "Button 12", "Button 13", "Button 14", "Button 15",
+    "Button 16", "Button 17", "Button 18", "Button 19", "Button 20", "Button 21", "Button 22",
+    "Button 23", "Button 24", "Button 25", "Button 26", "Button 27", "Button 28", "Button 29",
+    "Button 30", "Button 31", "Button 32",
+];
+
+/// Look up a name in a table.
+fn find(table: &[(u16, &'static str)], id: u16) -> Option<&'static str> {
+    table.iter().find(|(key, _)| *key == id).map(|(_, name)| *name)
+}
+
+/// Return the name of a usage page, such as "Generic Desktop".
+pub fn page_name(usage_page: u16) -> Option<&'static str> {
+    match usage_page {
+        page::VENDOR_DEFINED..=0xffff => Some("Vendor Defined"),
+        usage_page => find(PAGES, usage_page),
+    }
+}
+
+/// Return the name of a usage on a page, such as "Mouse" for usage 0x02 on the Generic Desktop
+/// page.
+///
+/// Only the first 32 buttons and the common usages of other pages are known.
+pub fn name(usage_page: u16, id: u16) -> Option<&'static str> {
+    let table = match usage_page {
+        page::GENERIC_DESKTOP => GENERIC_DESKTOP,
+        page::SIMULATION => SIMULATION,
+        page::KEYBOARD => KEYBOARD,
+        page::LED => LED,
+        page::CONSUMER => CONSUMER,
+        page::DIGITIZER => DIGITIZER,
+        page::BUTTON if id == 0 => return Some("No Button"),
+        page::BUTTON => return BUTTONS.get(usize::from(id) - 1).copied(),
+        _ => return None,
+    };
+    find(table, id)
+}
@@ -0,0 +1,98 @@
+//! Building configurations from devices opened with the `hidapi` crate.
+//!
+//! hidapi reports the vendor and product IDs, release number and strings of a device, and
+//! fetches its report descriptor, on every platform it supports. Mirroring a device over
+//! Bluetooth keeps all of them, taking the device subclass from the top-level usages of the
+//! report descriptor, as for USB interfaces.
+
+use std::fmt::{self, Display, Formatter};
+
+use ::hidapi::{BusType, DeviceInfo, HidApi, HidDevice, HidError};
+
+use crate::device_id::vendor_id_source;
+use crate::hid::report::{self, ParseError};
+use crate::hid::{self, ClassDescriptor};
+use crate::usb::infer_subclass;
+use crate::{encoding, language, Configuration, ProfileVersion};
+
+/// Longest report descriptor hidapi fetches.
+pub const MAX_REPORT_DESCRIPTOR_SIZE: usize = 4096;
+
+/// Error type for devices which cannot be mirrored
+#[derive(Debug)]
+pub enum HidapiError {
+    Hid(HidError),
+    /// The report descriptor is malformed.
+    Malformed(ParseError),
+}
+
+impl Display for HidapiError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Hid(e) =>
+                write!(f, "hidapi: {}", e),
+            Self::Malformed(e) =>
+                write!(f, "report descriptor: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for HidapiError {}
+
+impl From<HidError> for HidapiError {
+    fn from(e: HidError) -> Self {
+        Self::Hid(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, HidapiError>;
+
+impl Configuration {
+    /// Create a configuration for a device from its information and report descriptor, with
+    /// its product and manufacturer strings as the service name and provider name and, for USB
+    /// devices, its IDs and release number as the device identity.
+    ///
+    /// The boot protocol support and country code of the device are not reported by hidapi,
+    /// so the configuration is not a boot device and is not localized. Every device supports
+    /// virtual cables and reconnection, as with `Configuration::minimal`.
+    pub fn from_hidapi_info(info: &DeviceInfo, report_descriptor: Vec<u8>) -> Result<Self> {
+        report::parse(&report_descriptor).map_err(HidapiError::Malformed)?;
+        let subclass = infer_subclass(&report_descriptor);
+        let hid = hid::Configuration::new(subclass)
+            .with_virtual_cable(true)
+            .with_reconnect_initiate(true)
+            .with_class_descriptor(ClassDescriptor::report(report_descriptor));
+        let mut config =
+            Configuration::new(language::ENGLISH, encoding::UTF_8, ProfileVersion::V1_1, hid);
+        if let Some(name) = info.product_string().filter(|name| !name.is_empty()) {
+            config = config.with_service_name(name.to_string());
+        }
+        if let Some(provider) = info.manufacturer_string().filter(|name| !name.is_empty()) {
+            config = config.with_provider_name(provider.to_string());
+        }
+        if matches!(info.bus_type(), BusType::Usb) {
+            config = config.with_device_identity(
+                vendor_id_source::USB,
+                info.vendor_id(),
+                info.product_id(),
+                info.release_number(),
+            );
+        }
+        Ok(config)
+    }
+
+    /// Create a configuration for an open device, fetching its information and report
+    /// descriptor; see `from_hidapi_info`.
+    pub fn from_hidapi_device(device: &HidDevice) -> Result<Self> {
+        let info = device.get_device_info()?;
+        let mut buffer = [0; MAX_REPORT_DESCRIPTOR_SIZE];
+        let len = device.get_report_descriptor(&mut buffer)?;
+        Self::from_hidapi_info(&info, buffer[..len].to_vec())
+    }
+
+    /// Open the device described by `info` and create a configuration for it; see
+    /// `from_hidapi_info`.
+    pub fn from_hidapi(api: &HidApi, info: &DeviceInfo) -> Result<Self> {
+        Self::from_hidapi_device(&info.open_device(api)?)
+    }
+}
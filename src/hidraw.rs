@@ -0,0 +1,167 @@
+//! Building configurations from HID devices attached to a Linux host.
+//!
+//! Linux exposes every HID device it has bound under `/sys/class/hidraw/hidrawN`, whose
+//! `device` directory holds the raw report descriptor and a `uevent` file naming the device, for
+//! example:
+//!
+//! ```text
+//! HID_ID=0003:0000046D:0000C52B
+//! HID_NAME=Logitech USB Receiver
+//! ```
+//!
+//! Cloning a peripheral over Bluetooth keeps its report descriptor, and takes the device
+//! subclass from its top-level usages, as for USB interfaces.
+
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::device_id::vendor_id_source;
+use crate::hid::report::{self, ParseError};
+use crate::hid::{self, ClassDescriptor};
+use crate::usb::infer_subclass;
+use crate::{encoding, language, Configuration, ProfileVersion};
+
+/// Directory in which Linux lists hidraw devices.
+pub const SYSFS_DIR: &str = "/sys/class/hidraw";
+
+/// Buses of HID devices, as in the `HID_ID` of their uevent.
+pub mod bus {
+    pub const USB: u16 = 0x0003;
+    pub const BLUETOOTH: u16 = 0x0005;
+}
+
+/// Error type for reading hidraw devices
+#[derive(Debug)]
+pub enum HidrawError {
+    Io(PathBuf, io::Error),
+    /// The uevent of the device has no `HID_ID`, or it is malformed.
+    MalformedUevent(PathBuf),
+    /// The report descriptor is malformed.
+    Malformed(ParseError),
+}
+
+impl Display for HidrawError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Io(path, e) =>
+                write!(f, "{}: {}", path.display(), e),
+            Self::MalformedUevent(path) =>
+                write!(f, "{}: malformed HID_ID", path.display()),
+            Self::Malformed(e) =>
+                write!(f, "report descriptor: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for HidrawError {}
+
+pub type Result<T> = std::result::Result<T, HidrawError>;
+
+/// The identity of a HID device, from the `HID_ID` of its uevent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HidId {
+    /// Bus the device is attached to, such as `bus::USB`.
+    pub bus: u16,
+    pub vendor_id: u16,
+    pub product_id: u16,
+}
+
+/// A HID device attached to the host.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HidrawDevice {
+    pub id: Option<HidId>,
+    pub name: Option<String>,
+    pub report_descriptor: Vec<u8>,
+}
+
+/// Parse a `HID_ID` value, such as `0003:0000046D:0000C52B`. Vendor and product IDs are
+/// written with 32 bits, but HID devices only have 16.
+fn parse_hid_id(value: &str) -> Option<HidId> {
+    let mut fields = value.split(':');
+    let bus = u16::from_str_radix(fields.next()?, 16).ok()?;
+    let vendor_id = u32::from_str_radix(fields.next()?, 16).ok()?;
+    let product_id = u32::from_str_radix(fields.next()?, 16).ok()?;
+    Some(HidId {
+        bus,
+        vendor_id: u16::try_from(vendor_id).ok()?,
+        product_id: u16::try_from(product_id).ok()?,
+    })
+}
+
+impl HidrawDevice {
+    /// Create a device from its report descriptor alone, as read from the device by other
+    /// means.
+    pub fn new(report_descriptor: Vec<u8>) -> Self {
+        HidrawDevice { report_descriptor, ..Self::default() }
+    }
+
+    /// Read the device with the given hidraw number, such as 0 for `/dev/hidraw0`.
+    pub fn from_index(index: u32) -> Result<Self> {
+        Self::read_path(Path::new(SYSFS_DIR).join(format!("hidraw{}", index)))
+    }
+
+    /// Read the device in the given sysfs directory, such as `/sys/class/hidraw/hidraw0`.
+    pub fn read_path(path: impl AsRef<Path>) -> Result<Self> {
+        let device_dir = path.as_ref().join("device");
+        let read = |name: &str| {
+            let path = device_dir.join(name);
+            fs::read(&path).map_err(|e| HidrawError::Io(path, e))
+        };
+        let report_descriptor = read("report_descriptor")?;
+        let uevent = String::from_utf8_lossy(&read("uevent")?).into_owned();
+
+        let mut device = HidrawDevice::new(report_descriptor);
+        for line in uevent.lines() {
+            match line.split_once('=') {
+                Some(("HID_ID", value)) => {
+                    let id = parse_hid_id(value)
+                        .ok_or_else(|| HidrawError::MalformedUevent(device_dir.join("uevent")))?;
+                    device.id = Some(id);
+                },
+                Some(("HID_NAME", value)) if !value.is_empty() => {
+                    device.name = Some(value.to_string());
+                },
+                _ => {},
+            }
+        }
+        if device.id.is_none() {
+            return Err(HidrawError::MalformedUevent(device_dir.join("uevent")));
+        }
+        Ok(device)
+    }
+
+    /// Create a configuration for the device, with its report descriptor, its name as the
+    /// service name and, for USB devices, its vendor and product IDs as the device identity.
+    ///
+    /// The boot protocol support and country code of the device are not visible through hidraw,
+    /// so the configuration is not a boot device and is not localized. Every device supports
+    /// virtual cables and reconnection, as with `Configuration::minimal`.
+    pub fn to_configuration(&self) -> Result<Configuration> {
+        report::parse(&self.report_descriptor).map_err(HidrawError::Malformed)?;
+        let subclass = infer_subclass(&self.report_descriptor);
+        let hid = hid::Configuration::new(subclass)
+            .with_virtual_cable(true)
+            .with_reconnect_initiate(true)
+            .with_class_descriptor(ClassDescriptor::report(self.report_descriptor.clone()));
+        let mut config =
+            Configuration::new(language::ENGLISH, encoding::UTF_8, ProfileVersion::V1_1, hid);
+        if let Some(name) = &self.name {
+            config = config.with_service_name(name.clone());
+        }
+        if let Some(id) = self.id.filter(|id| id.bus == bus::USB) {
+            config = config
+                .with_device_identity(vendor_id_source::USB, id.vendor_id, id.product_id, 0);
+        }
+        Ok(config)
+    }
+}
+
+impl Configuration {
+    /// Create a configuration for the attached HID device with the given hidraw number; see
+    /// `HidrawDevice::to_configuration`.
+    pub fn from_hidraw(index: u32) -> Result<Self> {
+        HidrawDevice::from_index(index)?.to_configuration()
+    }
+}
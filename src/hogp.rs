@@ -0,0 +1,165 @@
+//! GATT definitions for the HID over GATT Profile.
+//!
+//! Dual-mode devices offer HID over both BR/EDR, described by the SDP record, and Bluetooth Low
+//! Energy, described by a HID Service in GATT. The HID Service carries the same report
+//! descriptor and flags, so its characteristic values follow from the configuration.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::hid::report::{self, ReportCharacteristic, ReportMapError, ReportMapPlan};
+use crate::hid::{self, DescriptorKind};
+use crate::Configuration;
+
+/// 16-bit UUIDs of the HID Service and its characteristics and descriptors, from Bluetooth
+/// Assigned Numbers.
+pub mod uuid16 {
+    pub const HID_SERVICE: u16 = 0x1812;
+    pub const BOOT_KEYBOARD_INPUT_REPORT: u16 = 0x2a22;
+    pub const BOOT_KEYBOARD_OUTPUT_REPORT: u16 = 0x2a32;
+    pub const BOOT_MOUSE_INPUT_REPORT: u16 = 0x2a33;
+    pub const HID_INFORMATION: u16 = 0x2a4a;
+    pub const REPORT_MAP: u16 = 0x2a4b;
+    pub const HID_CONTROL_POINT: u16 = 0x2a4c;
+    pub const REPORT: u16 = 0x2a4d;
+    pub const PROTOCOL_MODE: u16 = 0x2a4e;
+    pub const REPORT_REFERENCE: u16 = 0x2908;
+}
+
+/// Version of the HID specification given in the HID Information characteristic, 1.11.
+pub const BCD_HID: u16 = 0x0111;
+
+/// Error type for configurations which cannot be offered over GATT.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HogpError {
+    /// The configuration has no report descriptor to carry in the Report Map.
+    MissingReportDescriptor,
+    ReportMap(ReportMapError),
+}
+
+impl Display for HogpError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::MissingReportDescriptor => write!(f, "no report descriptor"),
+            Self::ReportMap(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for HogpError {}
+
+/// Value of the HID Information characteristic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HidInformation {
+    /// Version of the HID specification, as binary-coded decimal.
+    pub bcd_hid: u16,
+    pub country_code: u8,
+    pub remote_wake: bool,
+    pub normally_connectable: bool,
+}
+
+impl HidInformation {
+    /// Return the encoding of the characteristic value: the version, little-endian, the country
+    /// code, and a flags byte of remote wake and normally connectable.
+    pub fn to_bytes(&self) -> [u8; 4] {
+        let [low, high] = self.bcd_hid.to_le_bytes();
+        let flags = u8::from(self.remote_wake) | u8::from(self.normally_connectable) << 1;
+        [low, high, self.country_code, flags]
+    }
+}
+
+/// Value of the Protocol Mode characteristic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProtocolMode {
+    Boot,
+    /// The mode a HID Service enters when a host connects.
+    #[default]
+    Report,
+}
+
+impl ProtocolMode {
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            Self::Boot => 0x00,
+            Self::Report => 0x01,
+        }
+    }
+}
+
+/// The characteristics of a HID Service offering a configuration over GATT.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HogpService {
+    pub hid_information: HidInformation,
+    /// The Report Map and the Report characteristics with their Report Reference descriptors.
+    pub report_map: ReportMapPlan,
+    /// Initial value of the Protocol Mode characteristic, which is only declared by boot
+    /// devices.
+    pub protocol_mode: Option<ProtocolMode>,
+    /// Whether to declare the Boot Keyboard Input and Output Report characteristics.
+    pub boot_keyboard: bool,
+    /// Whether to declare the Boot Mouse Input Report characteristic.
+    pub boot_mouse: bool,
+}
+
+impl HogpService {
+    /// Return the Report characteristics, each with the value of its Report Reference
+    /// descriptor.
+    pub fn reports(&self) -> impl Iterator<Item = (ReportCharacteristic, [u8; 2])> + '_ {
+        self.report_map.reports.iter()
+            .map(|report| (*report, report.reference.to_bytes()))
+    }
+
+    /// Return the UUIDs of the characteristics to declare, in the order of the HID Service
+    /// specification, with the Report characteristic repeated for each report.
+    pub fn characteristic_uuids(&self) -> Vec<u16> {
+        let mut uuids = Vec::new();
+        if self.protocol_mode.is_some() {
+            uuids.push(uuid16::PROTOCOL_MODE);
+        }
+        uuids.extend(self.report_map.reports.iter().map(|_| uuid16::REPORT));
+        uuids.push(uuid16::REPORT_MAP);
+        if self.boot_keyboard {
+            uuids.push(uuid16::BOOT_KEYBOARD_INPUT_REPORT);
+            uuids.push(uuid16::BOOT_KEYBOARD_OUTPUT_REPORT);
+        }
+        if self.boot_mouse {
+            uuids.push(uuid16::BOOT_MOUSE_INPUT_REPORT);
+        }
+        uuids.push(uuid16::HID_INFORMATION);
+        uuids.push(uuid16::HID_CONTROL_POINT);
+        uuids
+    }
+}
+
+impl Configuration {
+    /// Return the HID Information characteristic value matching the record. Unset flags take
+    /// the values hosts assume when the attributes are absent.
+    pub fn hid_information(&self) -> HidInformation {
+        HidInformation {
+            bcd_hid: BCD_HID,
+            country_code: self.hid.country_code.into(),
+            remote_wake: self.hid.remote_wake.unwrap_or(hid::defaults::REMOTE_WAKE),
+            normally_connectable: self.hid.normally_connectable
+                .unwrap_or(hid::defaults::NORMALLY_CONNECTABLE),
+        }
+    }
+
+    /// Plan the HID Service offering this configuration over GATT, from its first report
+    /// descriptor.
+    pub fn to_hogp(&self) -> Result<HogpService, HogpError> {
+        let data = self.hid.class_descriptors.iter()
+            .find_map(|descriptor| match descriptor.kind() {
+                DescriptorKind::Report(data) => Some(data),
+                _ => None,
+            })
+            .ok_or(HogpError::MissingReportDescriptor)?;
+        let report_map = report::report_map_plan(data).map_err(HogpError::ReportMap)?;
+        let boot_device = self.hid.boot_device;
+        Ok(HogpService {
+            hid_information: self.hid_information(),
+            report_map,
+            protocol_mode: boot_device.then_some(ProtocolMode::Report),
+            boot_keyboard: boot_device && self.hid.device_subclass.is_keyboard(),
+            boot_mouse: boot_device && self.hid.device_subclass.is_pointing(),
+        })
+    }
+}
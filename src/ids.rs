@@ -0,0 +1,59 @@
+//! Bluetooth UUIDs, without depending on bluer.
+//!
+//! Records only need the conversions between short and full UUIDs and the few service classes
+//! the crate writes itself, which are defined here on the plain `uuid` type, so the core model
+//! builds without bluer and its D-Bus stack, and for targets bluer does not support, such as
+//! WebAssembly.
+
+use uuid::Uuid;
+
+/// The Bluetooth Base UUID, 00000000-0000-1000-8000-00805F9B34FB, which short UUIDs extend.
+const BASE_UUID: u128 = 0x00000000_0000_1000_8000_00805f9b34fb;
+
+/// Mask of the bits short UUIDs leave from the Base UUID.
+const BASE_MASK: u128 = (1 << 96) - 1;
+
+/// Conversions between short and full UUIDs, as in bluer's trait of the same name.
+pub(crate) trait UuidExt {
+    fn from_u16(uuid: u16) -> Self;
+    fn from_u32(uuid: u32) -> Self;
+    /// Return the 16-bit UUID this is an alias of, if any.
+    fn as_u16(&self) -> Option<u16>;
+    /// Return the 32-bit UUID this is an alias of, if any, including 16-bit UUIDs.
+    fn as_u32(&self) -> Option<u32>;
+}
+
+impl UuidExt for Uuid {
+    fn from_u16(uuid: u16) -> Self {
+        Self::from_u32(uuid.into())
+    }
+
+    fn from_u32(uuid: u32) -> Self {
+        Uuid::from_u128(BASE_UUID | u128::from(uuid) << 96)
+    }
+
+    fn as_u16(&self) -> Option<u16> {
+        self.as_u32().and_then(|uuid| u16::try_from(uuid).ok())
+    }
+
+    fn as_u32(&self) -> Option<u32> {
+        let value = self.as_u128();
+        (value & BASE_MASK == BASE_UUID).then(|| (value >> 96) as u32)
+    }
+}
+
+/// Service classes written in records, from Bluetooth Assigned Numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ServiceClass {
+    PublicBrowseGroup,
+    Hid,
+}
+
+impl From<ServiceClass> for Uuid {
+    fn from(class: ServiceClass) -> Uuid {
+        match class {
+            ServiceClass::PublicBrowseGroup => Uuid::from_u16(0x1002),
+            ServiceClass::Hid => Uuid::from_u16(0x1124),
+        }
+    }
+}
@@ -1,65 +1,713 @@
+pub mod advertising;
+pub mod android;
+#[cfg(feature = "mmap")]
+pub mod archive;
+pub mod attribute;
+#[cfg(feature = "bluer")]
+pub mod bluer;
+pub mod builder;
+pub mod cache;
+pub mod canonical;
+pub mod class_of_device;
+pub mod codegen;
+pub mod compat;
+pub mod conformance;
+pub mod data_element;
+#[cfg(any(feature = "toml", feature = "yaml"))]
+pub mod definition;
+pub mod device_id;
+pub mod eir;
 pub mod from_sdp;
+#[cfg(feature = "hidapi")]
+pub mod hidapi;
+#[cfg(target_os = "linux")]
+pub mod hidraw;
+pub mod hogp;
+mod ids;
+#[cfg(feature = "profile")]
+pub mod profile;
+pub mod presets;
+pub mod qos;
+pub mod sdptool;
+pub mod summary;
+pub mod timing;
 pub mod to_sdp;
+pub mod to_xml;
+#[cfg(all(target_os = "linux", feature = "transport"))]
+pub mod transport;
+pub mod usb;
+pub mod validate;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "watch")]
+pub mod watch;
+#[cfg(feature = "zbus")]
+pub mod zbus;
 
-pub use from_sdp::{Error, PartialConfiguration};
+pub use class_of_device::ClassOfDevice;
+pub use device_id::DeviceIdConfiguration;
+pub use from_sdp::{Error, ParseOptions, PartialConfiguration};
+pub use to_sdp::{DescriptorEncoding, SerializeError, SerializeOptions};
+pub use validate::{ConformanceLevel, Severity, ValidateOptions, Violation};
+
+use sdp_xml::Tag;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use uuid::Uuid;
 
 /// Struct for language ID data.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct LanguageCode {
     pub iso_code: u16, // ISO 639:1988 (E/F)
     pub hid_code: u16, // Defined by HID, difficult to know.
 }
 
+impl LanguageCode {
+    pub const fn new(iso_code: u16, hid_code: u16) -> Self {
+        LanguageCode { iso_code, hid_code }
+    }
+}
+
 pub mod language {
     use super::LanguageCode;
 
-    pub const ENGLISH: LanguageCode = LanguageCode { iso_code: 0x656e, hid_code: 0x0409 };
+    pub const ENGLISH: LanguageCode = LanguageCode::new(0x656e, 0x0409);
+}
+
+/// Service strings for a single language.
+///
+/// Each string is stored at a fixed offset from the language's base attribute ID. Strings may be
+/// borrowed from statics, so that configurations defined as constants need no allocations.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ServiceStrings {
+    pub service_name: Option<Cow<'static, str>>,
+    pub service_description: Option<Cow<'static, str>>,
+    pub provider_name: Option<Cow<'static, str>>,
+}
+
+impl ServiceStrings {
+    /// Offset of the service name from the language base.
+    pub const SERVICE_NAME_OFFSET: u16 = 0x0000;
+    /// Offset of the service description from the language base.
+    pub const SERVICE_DESCRIPTION_OFFSET: u16 = 0x0001;
+    /// Offset of the provider name from the language base.
+    pub const PROVIDER_NAME_OFFSET: u16 = 0x0002;
+}
+
+/// Version of a profile, packed as 0xMMmm in records, such as 0x0101 for 1.1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProfileVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl ProfileVersion {
+    /// Version 1.0 of the HID profile.
+    pub const V1_0: Self = Self::new(1, 0);
+    /// Version 1.1 of the HID profile.
+    pub const V1_1: Self = Self::new(1, 1);
+
+    pub const fn new(major: u8, minor: u8) -> Self {
+        ProfileVersion { major, minor }
+    }
+
+    /// Create a version from its packed form, as written in records.
+    pub const fn from_packed(packed: u16) -> Self {
+        ProfileVersion { major: (packed >> 8) as u8, minor: packed as u8 }
+    }
+
+    /// Return the packed form of the version, as written in records.
+    pub const fn packed(&self) -> u16 {
+        (self.major as u16) << 8 | self.minor as u16
+    }
+}
+
+impl From<u16> for ProfileVersion {
+    fn from(packed: u16) -> Self {
+        Self::from_packed(packed)
+    }
+}
+
+impl From<ProfileVersion> for u16 {
+    fn from(version: ProfileVersion) -> u16 {
+        version.packed()
+    }
+}
+
+impl Display for ProfileVersion {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Error for strings which are not a version of the form "1.1".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseProfileVersionError(String);
+
+impl Display for ParseProfileVersionError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "invalid profile version {:?}, expected major.minor", self.0)
+    }
+}
+
+impl std::error::Error for ParseProfileVersionError {}
+
+impl FromStr for ProfileVersion {
+    type Err = ParseProfileVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseProfileVersionError(s.to_owned());
+        let (major, minor) = s.trim().split_once('.').ok_or_else(error)?;
+        Ok(ProfileVersion {
+            major: major.parse().map_err(|_| error())?,
+            minor: minor.parse().map_err(|_| error())?,
+        })
+    }
 }
 
 // MIBEnum value for UTF-8, from IANA's database.
+/// Character encodings of service strings, as MIBEnum values from IANA's Character Sets
+/// registry.
 pub mod encoding {
+    pub const US_ASCII: u16 = 3;
+    pub const ISO_8859_1: u16 = 4;
+    pub const SHIFT_JIS: u16 = 17;
+    pub const EUC_KR: u16 = 38;
     pub const UTF_8: u16 = 0x006a;
+    pub const UTF_16BE: u16 = 1013;
+    pub const UTF_16LE: u16 = 1014;
+    pub const UTF_16: u16 = 1015;
+    pub const GB2312: u16 = 2025;
+    pub const BIG5: u16 = 2026;
+    pub const WINDOWS_1252: u16 = 2252;
+
+    /// Encodings hosts are known to decode, with their names in the registry.
+    const KNOWN: &[(u16, &str)] = &[
+        (3, "US-ASCII"),
+        (4, "ISO-8859-1"),
+        (5, "ISO-8859-2"),
+        (6, "ISO-8859-3"),
+        (7, "ISO-8859-4"),
+        (8, "ISO-8859-5"),
+        (9, "ISO-8859-6"),
+        (10, "ISO-8859-7"),
+        (11, "ISO-8859-8"),
+        (12, "ISO-8859-9"),
+        (13, "ISO-8859-10"),
+        (17, "Shift_JIS"),
+        (18, "EUC-JP"),
+        (37, "ISO-2022-KR"),
+        (38, "EUC-KR"),
+        (39, "ISO-2022-JP"),
+        (106, "UTF-8"),
+        (109, "ISO-8859-13"),
+        (110, "ISO-8859-14"),
+        (111, "ISO-8859-15"),
+        (112, "ISO-8859-16"),
+        (113, "GBK"),
+        (114, "GB18030"),
+        (1000, "ISO-10646-UCS-2"),
+        (1001, "ISO-10646-UCS-4"),
+        (1013, "UTF-16BE"),
+        (1014, "UTF-16LE"),
+        (1015, "UTF-16"),
+        (1017, "UTF-32"),
+        (1018, "UTF-32BE"),
+        (1019, "UTF-32LE"),
+        (2025, "GB2312"),
+        (2026, "Big5"),
+        (2027, "macintosh"),
+        (2084, "KOI8-R"),
+        (2088, "KOI8-U"),
+        (2250, "windows-1250"),
+        (2251, "windows-1251"),
+        (2252, "windows-1252"),
+        (2253, "windows-1253"),
+        (2254, "windows-1254"),
+        (2255, "windows-1255"),
+        (2256, "windows-1256"),
+        (2257, "windows-1257"),
+        (2258, "windows-1258"),
+        (2259, "TIS-620"),
+    ];
+
+    /// Return the name of a known encoding, such as "UTF-8".
+    pub fn name(mib_enum: u16) -> Option<&'static str> {
+        KNOWN.iter().find(|(value, _)| *value == mib_enum).map(|(_, name)| *name)
+    }
+
+    /// Return true if the encoding is one hosts are known to decode.
+    pub fn is_known(mib_enum: u16) -> bool {
+        name(mib_enum).is_some()
+    }
 }
 
 
 pub mod hid {
+    use std::ops::Deref;
+    use std::sync::Arc;
+
+    pub use crate::timing::{SsrHostMaxLatency, SsrHostMinTimeout, SupervisionTimeout};
+
+    pub mod report;
+    pub mod usage;
+
     /// Struct for representing language base IDs.
-    #[derive(Clone, Copy, Debug)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
     pub struct LanguageBase {
         pub language: u16,
         pub base: u16,
     }
 
+    impl LanguageBase {
+        pub const fn new(language: u16, base: u16) -> Self {
+            LanguageBase { language, base }
+        }
+    }
+
+    /// Values assumed by hosts for optional attributes which are absent from a record.
+    pub mod defaults {
+        pub const BATTERY_POWER: bool = false;
+        pub const REMOTE_WAKE: bool = false;
+        /// Two seconds.
+        pub const SUPERVISION_TIMEOUT: super::SupervisionTimeout = super::SupervisionTimeout::DEFAULT;
+        pub const NORMALLY_CONNECTABLE: bool = false;
+    }
+
     pub mod descriptor_type {
         pub const REPORT: u8 = 0x22;
         pub const PHYSICAL: u8 = 0x23;
     }
 
-    // ID and data for a class descriptor
+    /// Keyboard and pointing device bits of the device subclass, bits 7-6 of the minor device
+    /// class.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum Peripheral {
+        /// Neither a keyboard nor a pointing device.
+        #[default]
+        None,
+        Keyboard,
+        Pointing,
+        /// Combined keyboard and pointing device.
+        Combo,
+    }
+
+    /// Device type bits of the device subclass, bits 5-2 of the minor device class.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum DeviceType {
+        #[default]
+        Uncategorized,
+        Joystick,
+        Gamepad,
+        RemoteControl,
+        SensingDevice,
+        DigitizerTablet,
+        CardReader,
+        DigitalPen,
+        HandheldScanner,
+        HandheldGesturalInput,
+        /// A value reserved by the Bluetooth Assigned Numbers, from 0x0a to 0x0f.
+        Reserved(u8),
+    }
+
+    /// Device subclass, encoded as the minor device class of a peripheral in Bluetooth Assigned
+    /// Numbers.
+    ///
+    /// The lowest two bits of the minor device class are reserved and are not stored, so they
+    /// are always written as zero.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct DeviceSubclass {
+        pub peripheral: Peripheral,
+        pub device_type: DeviceType,
+    }
+
+    impl DeviceSubclass {
+        pub const KEYBOARD: Self = Self::new(Peripheral::Keyboard, DeviceType::Uncategorized);
+        pub const MOUSE: Self = Self::new(Peripheral::Pointing, DeviceType::Uncategorized);
+        pub const COMBO: Self = Self::new(Peripheral::Combo, DeviceType::Uncategorized);
+        pub const JOYSTICK: Self = Self::new(Peripheral::None, DeviceType::Joystick);
+        pub const GAMEPAD: Self = Self::new(Peripheral::None, DeviceType::Gamepad);
+        pub const REMOTE_CONTROL: Self = Self::new(Peripheral::None, DeviceType::RemoteControl);
+        pub const DIGITIZER_TABLET: Self = Self::new(Peripheral::None, DeviceType::DigitizerTablet);
+
+        pub const fn new(peripheral: Peripheral, device_type: DeviceType) -> Self {
+            DeviceSubclass { peripheral, device_type }
+        }
+
+        /// Return true if the device is a keyboard, including combo devices.
+        pub fn is_keyboard(&self) -> bool {
+            matches!(self.peripheral, Peripheral::Keyboard | Peripheral::Combo)
+        }
+
+        /// Return true if the device is a pointing device, including combo devices.
+        pub fn is_pointing(&self) -> bool {
+            matches!(self.peripheral, Peripheral::Pointing | Peripheral::Combo)
+        }
+    }
+
+    impl From<u8> for DeviceSubclass {
+        fn from(value: u8) -> Self {
+            let peripheral = match value >> 6 {
+                0b00 => Peripheral::None,
+                0b01 => Peripheral::Keyboard,
+                0b10 => Peripheral::Pointing,
+                _ => Peripheral::Combo,
+            };
+            let device_type = match (value >> 2) & 0x0f {
+                0x00 => DeviceType::Uncategorized,
+                0x01 => DeviceType::Joystick,
+                0x02 => DeviceType::Gamepad,
+                0x03 => DeviceType::RemoteControl,
+                0x04 => DeviceType::SensingDevice,
+                0x05 => DeviceType::DigitizerTablet,
+                0x06 => DeviceType::CardReader,
+                0x07 => DeviceType::DigitalPen,
+                0x08 => DeviceType::HandheldScanner,
+                0x09 => DeviceType::HandheldGesturalInput,
+                other => DeviceType::Reserved(other),
+            };
+            DeviceSubclass { peripheral, device_type }
+        }
+    }
+
+    impl From<DeviceSubclass> for u8 {
+        fn from(subclass: DeviceSubclass) -> u8 {
+            let peripheral = match subclass.peripheral {
+                Peripheral::None => 0b00,
+                Peripheral::Keyboard => 0b01,
+                Peripheral::Pointing => 0b10,
+                Peripheral::Combo => 0b11,
+            };
+            let device_type = match subclass.device_type {
+                DeviceType::Uncategorized => 0x00,
+                DeviceType::Joystick => 0x01,
+                DeviceType::Gamepad => 0x02,
+                DeviceType::RemoteControl => 0x03,
+                DeviceType::SensingDevice => 0x04,
+                DeviceType::DigitizerTablet => 0x05,
+                DeviceType::CardReader => 0x06,
+                DeviceType::DigitalPen => 0x07,
+                DeviceType::HandheldScanner => 0x08,
+                DeviceType::HandheldGesturalInput => 0x09,
+                DeviceType::Reserved(other) => other & 0x0f,
+            };
+            peripheral << 6 | device_type << 2
+        }
+    }
+
+    /// Define the country code enum, with conversions from and to its value.
+    macro_rules! country_codes {
+        ($($(#[$meta:meta])* $name:ident = $value:literal,)*) => {
+            /// Country code of localized hardware, as defined in 6.2.1 of the USB HID
+            /// specification.
+            #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+            pub enum CountryCode {
+                /// The hardware is not localized.
+                #[default]
+                NotLocalized,
+                $($(#[$meta])* $name,)*
+                /// A value reserved by the USB HID specification.
+                Other(u8),
+            }
+
+            impl From<u8> for CountryCode {
+                fn from(value: u8) -> Self {
+                    match value {
+                        0 => CountryCode::NotLocalized,
+                        $($value => CountryCode::$name,)*
+                        other => CountryCode::Other(other),
+                    }
+                }
+            }
+
+            impl From<CountryCode> for u8 {
+                fn from(country_code: CountryCode) -> u8 {
+                    match country_code {
+                        CountryCode::NotLocalized => 0,
+                        $(CountryCode::$name => $value,)*
+                        CountryCode::Other(other) => other,
+                    }
+                }
+            }
+        };
+    }
+
+    country_codes! {
+        Arabic = 1,
+        Belgian = 2,
+        CanadianBilingual = 3,
+        CanadianFrench = 4,
+        CzechRepublic = 5,
+        Danish = 6,
+        Finnish = 7,
+        French = 8,
+        German = 9,
+        Greek = 10,
+        Hebrew = 11,
+        Hungary = 12,
+        /// International (ISO).
+        International = 13,
+        Italian = 14,
+        /// Japan (Katakana).
+        Japan = 15,
+        Korean = 16,
+        LatinAmerican = 17,
+        /// Netherlands/Dutch.
+        Netherlands = 18,
+        Norwegian = 19,
+        /// Persian (Farsi).
+        Persian = 20,
+        Poland = 21,
+        Portuguese = 22,
+        Russia = 23,
+        Slovakia = 24,
+        Spanish = 25,
+        Swedish = 26,
+        SwissFrench = 27,
+        SwissGerman = 28,
+        Switzerland = 29,
+        Taiwan = 30,
+        TurkishQ = 31,
+        UK = 32,
+        US = 33,
+        Yugoslavia = 34,
+        TurkishF = 35,
+    }
+
+    impl CountryCode {
+        /// Return true if the code is reserved by the USB HID specification, and so is likely
+        /// a mistake.
+        pub fn is_reserved(&self) -> bool {
+            matches!(self, CountryCode::Other(value) if *value > 35)
+        }
+    }
+
+    /// Element used to carry the payload of a class descriptor.
+    ///
+    /// Descriptor data may be written either as a plain text element or as a raw (hex-encoded)
+    /// text element. Both forms carry the same bytes, but records must use the original form to
+    /// round-trip byte-for-byte.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum DescriptorForm {
+        /// Raw bytes, written as a hex-encoded text element.
+        #[default]
+        RawText,
+        /// A plain text element. Only used if the data is valid UTF-8 and may appear in XML.
+        Text,
+    }
+
+    /// Data of a class descriptor, either borrowed from a static or shared between clones, so
+    /// that cloning a configuration never copies descriptors.
     #[derive(Clone, Debug)]
-    pub struct ClassDescriptor(pub u8, pub Vec<u8>);
+    pub enum DescriptorData {
+        Static(&'static [u8]),
+        Shared(Arc<[u8]>),
+    }
+
+    impl Deref for DescriptorData {
+        type Target = [u8];
+
+        fn deref(&self) -> &[u8] {
+            match self {
+                DescriptorData::Static(data) => data,
+                DescriptorData::Shared(data) => data,
+            }
+        }
+    }
+
+    impl PartialEq for DescriptorData {
+        fn eq(&self, other: &Self) -> bool {
+            **self == **other
+        }
+    }
+
+    impl Eq for DescriptorData {}
+
+    impl From<Vec<u8>> for DescriptorData {
+        fn from(data: Vec<u8>) -> Self {
+            DescriptorData::Shared(data.into())
+        }
+    }
+
+    impl From<Arc<[u8]>> for DescriptorData {
+        fn from(data: Arc<[u8]>) -> Self {
+            DescriptorData::Shared(data)
+        }
+    }
+
+    impl From<&'static [u8]> for DescriptorData {
+        fn from(data: &'static [u8]) -> Self {
+            DescriptorData::Static(data)
+        }
+    }
+
+    // ID, data and encoding form for a class descriptor
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct ClassDescriptor(pub u8, pub DescriptorData, pub DescriptorForm);
+
+    /// Largest descriptor which can be written in a record. SDP responses carry at most 0xffff
+    /// bytes of attributes, so larger text elements could never be sent.
+    pub const MAX_DESCRIPTOR_LEN: usize = 0xffff;
+
+    /// Warning about a descriptor which can be written, but is likely a mistake.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum DescriptorWarning {
+        /// The report descriptor is shorter than any single item, with the given length.
+        ShortReportDescriptor(usize),
+    }
+
+    impl std::fmt::Display for DescriptorWarning {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            match self {
+                Self::ShortReportDescriptor(len) =>
+                    write!(f, "report descriptor is only {} bytes long", len),
+            }
+        }
+    }
+
+    /// Kind of a class descriptor, with its data, for matching on descriptors without their
+    /// type constants.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum DescriptorKind<'a> {
+        Report(&'a [u8]),
+        Physical(&'a [u8]),
+        /// A descriptor of another type, such as a vendor-defined type.
+        Other(u8, &'a [u8]),
+    }
 
     impl ClassDescriptor {
         /// Create a new report descriptor
         pub fn report(data: Vec<u8>) -> Self {
-            ClassDescriptor(descriptor_type::REPORT, data)
+            ClassDescriptor(descriptor_type::REPORT, DescriptorData::from(data), DescriptorForm::RawText)
+        }
+
+        /// Create a new report descriptor from static data, such as a constant.
+        pub const fn report_static(data: &'static [u8]) -> Self {
+            ClassDescriptor(descriptor_type::REPORT, DescriptorData::Static(data), DescriptorForm::RawText)
+        }
+
+        /// Create the report descriptor of a boot keyboard, `report::BOOT_KEYBOARD`.
+        pub const fn boot_keyboard() -> Self {
+            Self::report_static(&report::BOOT_KEYBOARD)
+        }
+
+        /// Create the report descriptor of a boot mouse, `report::BOOT_MOUSE`.
+        pub const fn boot_mouse() -> Self {
+            Self::report_static(&report::BOOT_MOUSE)
         }
 
         /// Create a new physical descriptor
         pub fn physical(data: Vec<u8>) -> Self {
-            ClassDescriptor(descriptor_type::PHYSICAL, data)
+            ClassDescriptor(descriptor_type::PHYSICAL, DescriptorData::from(data), DescriptorForm::RawText)
+        }
+
+        /// Create a new physical descriptor from static data, such as a constant.
+        pub const fn physical_static(data: &'static [u8]) -> Self {
+            ClassDescriptor(descriptor_type::PHYSICAL, DescriptorData::Static(data), DescriptorForm::RawText)
+        }
+
+        /// Create a new descriptor of any type.
+        pub fn other(descriptor_type: u8, data: Vec<u8>) -> Self {
+            ClassDescriptor(descriptor_type, DescriptorData::from(data), DescriptorForm::RawText)
+        }
+
+        /// Create a new descriptor of any type from static data, such as a constant.
+        pub const fn other_static(descriptor_type: u8, data: &'static [u8]) -> Self {
+            ClassDescriptor(descriptor_type, DescriptorData::Static(data), DescriptorForm::RawText)
+        }
+
+        /// Return the kind of this descriptor, with its data.
+        pub fn kind(&self) -> DescriptorKind<'_> {
+            match self.0 {
+                descriptor_type::REPORT => DescriptorKind::Report(&self.1),
+                descriptor_type::PHYSICAL => DescriptorKind::Physical(&self.1),
+                other => DescriptorKind::Other(other, &self.1),
+            }
+        }
+
+        /// Return this descriptor, written with the given form.
+        pub fn with_form(self, form: DescriptorForm) -> Self {
+            ClassDescriptor(self.0, self.1, form)
+        }
+
+        /// Return the type of this descriptor, such as `descriptor_type::REPORT`.
+        pub fn descriptor_type(&self) -> u8 {
+            self.0
+        }
+
+        /// Return the data of this descriptor.
+        pub fn data(&self) -> &[u8] {
+            &self.1
+        }
+
+        /// Return the length of the data of this descriptor, in bytes.
+        pub fn len(&self) -> usize {
+            self.1.len()
+        }
+
+        /// Return true if this descriptor has no data.
+        pub fn is_empty(&self) -> bool {
+            self.1.is_empty()
+        }
+
+        /// Return true if the data fits in the text element of a record.
+        pub fn fits_in_record(&self) -> bool {
+            self.len() <= MAX_DESCRIPTOR_LEN
+        }
+
+        /// Parse the items of this descriptor, if it is a report descriptor.
+        pub fn items(&self) -> Option<Result<Vec<report::Item>, report::ParseError>> {
+            match self.kind() {
+                DescriptorKind::Report(data) => Some(report::parse(data)),
+                _ => None,
+            }
+        }
+
+        /// Return warnings about descriptors which can be written, but are likely mistakes.
+        pub fn warnings(&self) -> Vec<DescriptorWarning> {
+            let mut warnings = Vec::new();
+            // The shortest useful report descriptor is a single two-byte item.
+            if self.0 == descriptor_type::REPORT && self.len() < 2 {
+                warnings.push(DescriptorWarning::ShortReportDescriptor(self.len()));
+            }
+            warnings
         }
     }
     
-    #[derive(Clone, Debug, Default)]
+    impl std::fmt::Display for ClassDescriptor {
+        /// Write the kind and length of the descriptor. The alternate form, `{:#}`, also lists
+        /// the items of report descriptors.
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            match self.kind() {
+                DescriptorKind::Report(data) => {
+                    write!(f, "Report descriptor, {} bytes", data.len())?;
+                    if f.alternate() {
+                        writeln!(f)?;
+                        report::write_listing(f, data, 2)?;
+                    }
+                    Ok(())
+                },
+                DescriptorKind::Physical(data) =>
+                    write!(f, "Physical descriptor, {} bytes", data.len()),
+                DescriptorKind::Other(descriptor_type, data) =>
+                    write!(f, "Descriptor of type 0x{:02x}, {} bytes", descriptor_type, data.len()),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    #[non_exhaustive]
     pub struct Configuration {
         /// Device subclass, such as mouse, keyboard, etc.
         /// Required.
-        pub device_subclass: u8,
+        pub device_subclass: DeviceSubclass,
         
-        /// 8-bit country code, as defined in the USB HID specification.
-        /// May be zero if the device is not localized.
-        pub country_code: u8,
+        /// Country code, as defined in the USB HID specification.
+        /// `CountryCode::NotLocalized` if the device is not localized.
+        pub country_code: CountryCode,
     
         /// Shall be true if the device is a boot device.
         /// Required.
@@ -80,7 +728,8 @@ pub mod hid {
     
         pub remote_wake: Option<bool>,
     
-        pub supervision_timeout: Option<u16>,
+        /// Link supervision timeout. Hosts assume two seconds if unset.
+        pub supervision_timeout: Option<SupervisionTimeout>,
     
         pub normally_connectable: Option<bool>,
         /// Boolean indicating whether this device is a boot device.
@@ -89,14 +738,85 @@ pub mod hid {
         /// Required.
         pub boot_device: bool, // True for keyboards and pointing devices
     
-        pub ssr_host_max_latency: Option<u16>,
-        pub ssr_host_min_timeout: Option<u16>,
+        /// Maximum sniff subrating latency.
+        pub ssr_host_max_latency: Option<SsrHostMaxLatency>,
+        /// Minimum sniff subrating timeout.
+        pub ssr_host_min_timeout: Option<SsrHostMinTimeout>,
+    }
+
+    impl Configuration {
+        /// Make this a boot device, which also requires virtual cables and reconnection, HID
+        /// 5.3.4.12.
+        fn set_boot(&mut self) {
+            self.boot_device = true;
+            self.virtual_cable = true;
+            self.reconnect_initiate = true;
+        }
+
+        /// Make this a boot keyboard. A pointing device becomes a combo device.
+        pub fn set_boot_keyboard(&mut self) {
+            self.set_boot();
+            self.device_subclass.peripheral = match self.device_subclass.peripheral {
+                Peripheral::Pointing | Peripheral::Combo => Peripheral::Combo,
+                Peripheral::None | Peripheral::Keyboard => Peripheral::Keyboard,
+            };
+        }
+
+        /// Make this a boot mouse. A keyboard becomes a combo device.
+        pub fn set_boot_mouse(&mut self) {
+            self.set_boot();
+            self.device_subclass.peripheral = match self.device_subclass.peripheral {
+                Peripheral::Keyboard | Peripheral::Combo => Peripheral::Combo,
+                Peripheral::None | Peripheral::Pointing => Peripheral::Pointing,
+            };
+        }
     }
 }
 
+/// Protocol-level configuration of the service: the channels it is reached on and how it is
+/// listed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TransportConfiguration {
+    /// L2CAP PSM of the HID control channel, if not the default of 0x0011.
+    pub control_psm: Option<u16>,
+    /// L2CAP PSM of the HID interrupt channel, if not the default of 0x0013.
+    pub interrupt_psm: Option<u16>,
+
+    /// Browse groups in which the service is listed.
+    ///
+    /// If unset, the service is listed in the public browse group. If empty, the browse group
+    /// list is omitted and the service is not browsable.
+    pub browse_groups: Option<Vec<Uuid>>,
+
+    /// Flow specification requested when configuring the control channel, if any. Not part of
+    /// the record, since quality of service is negotiated over L2CAP.
+    pub control_qos: Option<qos::FlowSpec>,
+    /// Flow specification requested when configuring the interrupt channel, if any.
+    pub interrupt_qos: Option<qos::FlowSpec>,
+}
+
 // Configuration for a HID Bluetooth profile.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct Configuration {
+    /// Handle of the service record. Usually assigned by the SDP server, but some servers
+    /// require it to be present in the record.
+    pub service_record_handle: Option<u32>,
+
+    /// Value which changes whenever any attribute of the record changes, so that clients may
+    /// detect stale cached records.
+    pub service_record_state: Option<u32>,
+    /// Number of seconds for which the record is expected to remain valid.
+    pub service_info_time_to_live: Option<u32>,
+    /// Relative ability of the service to accept additional clients, from 0x00 (fully in use)
+    /// to 0xff (not in use).
+    pub service_availability: Option<u8>,
+
+    /// Service classes listed after the HID service class in the service class ID list, such as
+    /// vendor-defined classes.
+    pub additional_service_classes: Vec<Uuid>,
+
     /// Primary language of the device.
     /// The primary language of a Bluetooth HID device is assigned the offset 0x0100 and is
     /// advertised in the general HID profile.
@@ -104,12 +824,79 @@ pub struct Configuration {
     /// MIBEnum encoding from IANA's database
     pub encoding: u16,
 
-    pub service_name: Option<String>,
-    pub service_description: Option<String>,
-    pub provider_name: Option<String>,
+    pub service_name: Option<Cow<'static, str>>,
+    pub service_description: Option<Cow<'static, str>>,
+    pub provider_name: Option<Cow<'static, str>>,
+
+    /// Service strings for additional languages, keyed by the language and its base attribute
+    /// ID. Each string is written at its offset from the base. Languages which are not in
+    /// `hid.additional_languages` are added to the HID language base list after them.
+    pub localized_strings: BTreeMap<hid::LanguageBase, ServiceStrings>,
+
+    /// URL of documentation for the service.
+    pub documentation_url: Option<Cow<'static, str>>,
+    /// URL of a client application which may be used to access the service.
+    pub client_executable_url: Option<Cow<'static, str>>,
+    /// URL of an icon representing the service.
+    pub icon_url: Option<Cow<'static, str>>,
+
+    /// Version of the HID profile, such as 1.1.
+    pub version: ProfileVersion,
+
+    /// Profiles listed after the HID profile in the profile descriptor list, with their versions.
+    pub additional_profiles: Vec<(Uuid, u16)>,
 
-    pub version: u16,
+    /// Identity of the device, published in the Device ID record rather than the HID record.
+    /// A Device ID record is only generated if both the vendor and product IDs are given.
+    pub vendor_id: Option<u16>,
+    pub product_id: Option<u16>,
+    /// Version of the product, as 0xJJMN for version JJ.M.N. Zero if unset.
+    pub product_version: Option<u16>,
+    /// Organization which assigned the vendor ID. The USB Implementers Forum if unset.
+    pub vendor_id_source: Option<u16>,
+
+    /// Additional attributes, such as vendor-defined attributes, emitted as given. A raw
+    /// attribute replaces any generated attribute with the same ID.
+    pub raw_attributes: Vec<(u16, Tag)>,
+
+    /// Protocol-level settings, separate from the HID attributes.
+    pub transport: TransportConfiguration,
 
     pub hid: hid::Configuration,
 }
 
+impl Configuration {
+    /// Create a configuration for a device with the given report descriptor and subclass, with
+    /// every other attribute set to a reasonable default.
+    ///
+    /// The configuration uses English and UTF-8, version 1.1 of the HID profile, and supports
+    /// virtual cables and reconnection. Keyboards and pointing devices are boot devices.
+    pub fn minimal(report_descriptor: Vec<u8>, subclass: hid::DeviceSubclass) -> Self {
+        let hid = hid::Configuration::new(subclass)
+            .with_virtual_cable(true)
+            .with_reconnect_initiate(true)
+            .with_class_descriptor(hid::ClassDescriptor::report(report_descriptor))
+            .with_boot_device(subclass.is_keyboard() || subclass.is_pointing());
+        Configuration::new(language::ENGLISH, encoding::UTF_8, ProfileVersion::V1_1, hid)
+    }
+
+    /// Return the languages listed after the primary language in the HID language base list:
+    /// the additional languages, followed by the other languages of the localized strings.
+    pub fn additional_language_bases(&self) -> Vec<hid::LanguageBase> {
+        let mut language_bases = self.hid.additional_languages.clone();
+        for language_base in self.localized_strings.keys() {
+            if !language_bases.contains(language_base) {
+                language_bases.push(*language_base);
+            }
+        }
+        language_bases
+    }
+
+    /// Add an attribute to be emitted as given in the record. If an attribute with the same ID
+    /// has already been added, it is replaced.
+    pub fn add_raw_attribute(&mut self, id: u16, tag: Tag) {
+        self.raw_attributes.retain(|(existing, _)| *existing != id);
+        self.raw_attributes.push((id, tag));
+    }
+}
+
@@ -0,0 +1,275 @@
+//! Complete configurations for common devices, ready to register.
+//!
+//! Each preset passes validation, and sets the attributes hosts look at beyond those the HID
+//! profile requires, such as remote wake and sniff subrating, to values which work with common
+//! hosts. Presets return a `PresetBuilder`, so names, timeouts and descriptors can be changed
+//! before building the configuration.
+
+use crate::hid::{self, report, DeviceSubclass};
+use crate::hid::report::Axis;
+use crate::hid::usage::{consumer, generic_desktop, page, simulation};
+use crate::hid::{SsrHostMaxLatency, SsrHostMinTimeout, SupervisionTimeout};
+use crate::validate::{Severity, Violation};
+use crate::{encoding, language, Configuration, ProfileVersion};
+use std::borrow::Cow;
+
+/// Sniff subrating latency of input devices, 0x0320 slots or 500 ms, short enough that typing
+/// does not lag after a pause.
+const INPUT_SSR_MAX_LATENCY: SsrHostMaxLatency = SsrHostMaxLatency::from_slots(0x0320);
+/// Time in sniff mode before subrating, 0x00a0 slots or 100 ms.
+const INPUT_SSR_MIN_TIMEOUT: SsrHostMinTimeout = SsrHostMinTimeout::from_slots(0x00a0);
+
+/// Builder for the configuration of a preset, which can be changed before it is built.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PresetBuilder {
+    config: Configuration,
+}
+
+impl PresetBuilder {
+    pub fn with_service_name(mut self, service_name: impl Into<Cow<'static, str>>) -> Self {
+        self.config.service_name = Some(service_name.into());
+        self
+    }
+
+    pub fn with_service_description(mut self, description: impl Into<Cow<'static, str>>) -> Self {
+        self.config.service_description = Some(description.into());
+        self
+    }
+
+    pub fn with_provider_name(mut self, provider_name: impl Into<Cow<'static, str>>) -> Self {
+        self.config.provider_name = Some(provider_name.into());
+        self
+    }
+
+    /// Set the identity of the device, as published in its Device ID record.
+    pub fn with_device_identity(
+        mut self,
+        vendor_id_source: u16,
+        vendor_id: u16,
+        product_id: u16,
+        product_version: u16,
+    ) -> Self {
+        self.config = self.config
+            .with_device_identity(vendor_id_source, vendor_id, product_id, product_version);
+        self
+    }
+
+    pub fn with_supervision_timeout(mut self, supervision_timeout: SupervisionTimeout) -> Self {
+        self.config.hid.supervision_timeout = Some(supervision_timeout);
+        self
+    }
+
+    /// Set the sniff subrating parameters. Unset both to leave sniff subrating to the host.
+    pub fn with_ssr(
+        mut self,
+        max_latency: Option<SsrHostMaxLatency>,
+        min_timeout: Option<SsrHostMinTimeout>,
+    ) -> Self {
+        self.config.hid.ssr_host_max_latency = max_latency;
+        self.config.hid.ssr_host_min_timeout = min_timeout;
+        self
+    }
+
+    pub fn with_battery_power(mut self, battery_power: bool) -> Self {
+        self.config.hid.battery_power = Some(battery_power);
+        self
+    }
+
+    pub fn with_remote_wake(mut self, remote_wake: bool) -> Self {
+        self.config.hid.remote_wake = Some(remote_wake);
+        self
+    }
+
+    pub fn with_normally_connectable(mut self, normally_connectable: bool) -> Self {
+        self.config.hid.normally_connectable = Some(normally_connectable);
+        self
+    }
+
+    /// Add a class descriptor after those of the preset, such as a physical descriptor.
+    pub fn with_class_descriptor(mut self, class_descriptor: hid::ClassDescriptor) -> Self {
+        self.config.hid.class_descriptors.push(class_descriptor);
+        self
+    }
+
+    /// Replace the report descriptor of the preset.
+    pub fn with_report_descriptor(mut self, data: Vec<u8>) -> Self {
+        self.config.hid.class_descriptors
+            .retain(|d| d.descriptor_type() != hid::descriptor_type::REPORT);
+        self.config.hid.class_descriptors.insert(0, hid::ClassDescriptor::report(data));
+        self
+    }
+
+    /// Change the configuration with a function, for attributes without a method here.
+    pub fn map(mut self, f: impl FnOnce(Configuration) -> Configuration) -> Self {
+        self.config = f(self.config);
+        self
+    }
+
+    /// Return the configuration.
+    pub fn build(self) -> Configuration {
+        self.config
+    }
+
+    /// Return the configuration, or the errors found by validation if changes made it invalid.
+    pub fn try_build(self) -> Result<Configuration, Vec<Violation>> {
+        let errors: Vec<Violation> = self.config.validate().into_iter()
+            .filter(|violation| violation.severity == Severity::Error)
+            .collect();
+        if errors.is_empty() {
+            Ok(self.config)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl From<PresetBuilder> for Configuration {
+    fn from(builder: PresetBuilder) -> Configuration {
+        builder.build()
+    }
+}
+
+/// Create an English configuration using UTF-8 and version 1.1 of the HID profile.
+fn configuration(service_name: &str, hid: hid::Configuration) -> PresetBuilder {
+    let config = Configuration::new(language::ENGLISH, encoding::UTF_8, ProfileVersion::V1_1, hid)
+        .with_service_name(service_name.to_string());
+    PresetBuilder { config }
+}
+
+/// Create the HID configuration of an input device with the given report descriptor, which can
+/// wake the host.
+fn input_device(subclass: DeviceSubclass, descriptor: hid::ClassDescriptor) -> hid::Configuration {
+    hid::Configuration::new(subclass)
+        .with_class_descriptor(descriptor)
+        .with_virtual_cable(true)
+        .with_reconnect_initiate(true)
+        .with_remote_wake(true)
+        .with_supervision_timeout(SupervisionTimeout::DEFAULT)
+        .with_ssr_host_max_latency(INPUT_SSR_MAX_LATENCY)
+        .with_ssr_host_min_timeout(INPUT_SSR_MIN_TIMEOUT)
+}
+
+/// Create a boot keyboard with the boot report descriptor, which can wake the host.
+pub fn boot_keyboard(service_name: &str) -> PresetBuilder {
+    let hid = input_device(DeviceSubclass::KEYBOARD, hid::ClassDescriptor::boot_keyboard())
+        .with_boot_device(true)
+        .with_boot_requirements();
+    configuration(service_name, hid)
+        .with_service_description("Keyboard")
+}
+
+/// Create a boot mouse with three buttons and a wheel, which can wake the host.
+pub fn boot_mouse() -> PresetBuilder {
+    let descriptor = hid::ClassDescriptor::report_static(&report::BOOT_MOUSE_WITH_WHEEL);
+    let hid = input_device(DeviceSubclass::MOUSE, descriptor)
+        .with_boot_device(true)
+        .with_boot_requirements();
+    configuration("Mouse", hid)
+        .with_service_description("Mouse")
+}
+
+/// Report ID of the keyboard reports of `combo`.
+pub const COMBO_KEYBOARD_REPORT_ID: u8 = 1;
+/// Report ID of the mouse reports of `combo`.
+pub const COMBO_MOUSE_REPORT_ID: u8 = 2;
+
+/// Create a boot keyboard and mouse in one device, such as a keyboard with a touchpad.
+///
+/// The report descriptor composes those of `boot_keyboard` and `boot_mouse`, with report IDs
+/// `COMBO_KEYBOARD_REPORT_ID` and `COMBO_MOUSE_REPORT_ID`.
+pub fn combo(service_name: &str) -> PresetBuilder {
+    let composite = report::compose(&[
+        ("keyboard", &report::BOOT_KEYBOARD[..]),
+        ("mouse", &report::BOOT_MOUSE_WITH_WHEEL[..]),
+    ]).expect("boot descriptors are well-formed");
+    let descriptor = hid::ClassDescriptor::report(composite.descriptor);
+    let hid = input_device(DeviceSubclass::COMBO, descriptor)
+        .with_boot_device(true)
+        .with_boot_requirements();
+    configuration(service_name, hid)
+        .with_service_description("Keyboard and mouse")
+}
+
+/// Axes of `gamepad`: the left stick on X and Y, the right stick on Z and Rz, and the left and
+/// right triggers as brake and accelerator, the layout Linux and Android map to standard
+/// gamepad controls.
+const GAMEPAD_AXES: [Axis; 6] = [
+    Axis::stick(generic_desktop::X),
+    Axis::stick(generic_desktop::Y),
+    Axis::stick(generic_desktop::Z),
+    Axis::stick(generic_desktop::RZ),
+    Axis::trigger(page::SIMULATION, simulation::BRAKE),
+    Axis::trigger(page::SIMULATION, simulation::ACCELERATOR),
+];
+
+/// Create a gamepad with a D-pad, two sticks, two triggers and 12 buttons, which hosts
+/// enumerate as a game controller. The input report layout is given by `gamepad_descriptor`.
+pub fn gamepad(service_name: &str) -> PresetBuilder {
+    let (descriptor, _) = report::gamepad_descriptor(12, &GAMEPAD_AXES, true);
+    let hid = input_device(DeviceSubclass::GAMEPAD, hid::ClassDescriptor::report(descriptor));
+    configuration(service_name, hid)
+        .with_service_description("Gamepad")
+}
+
+/// Keys of `media_remote`, in the order of their bits in the input report.
+pub const REMOTE_KEYS: [u16; 10] = [
+    consumer::VOLUME_INCREMENT,
+    consumer::VOLUME_DECREMENT,
+    consumer::MUTE,
+    consumer::PLAY_PAUSE,
+    consumer::SCAN_NEXT_TRACK,
+    consumer::SCAN_PREVIOUS_TRACK,
+    consumer::STOP,
+    consumer::AC_HOME,
+    consumer::AC_BACK,
+    consumer::MENU,
+];
+
+/// Create a remote control with the consumer control keys of `REMOTE_KEYS`, such as a TV
+/// remote. The remote is not a boot device, so hosts must read its report descriptor.
+pub fn media_remote(service_name: &str) -> PresetBuilder {
+    let descriptor = report::consumer_control_descriptor(&REMOTE_KEYS, None);
+    let hid = input_device(DeviceSubclass::REMOTE_CONTROL, hid::ClassDescriptor::report(descriptor))
+        .with_boot_device(false);
+    configuration(service_name, hid)
+        .with_service_description("Remote control")
+}
+
+/// Create a barcode scanner which types what it scans as a keyboard, a keyboard wedge.
+///
+/// Like most scanners, it is battery powered and accepts connections from the host at any
+/// time. It is not a boot device, since it is of no use before the host loads a full HID
+/// driver.
+pub fn barcode_scanner(service_name: &str) -> PresetBuilder {
+    let hid = hid::Configuration::new(DeviceSubclass::KEYBOARD)
+        .with_class_descriptor(hid::ClassDescriptor::boot_keyboard())
+        .with_virtual_cable(true)
+        .with_reconnect_initiate(true)
+        .with_boot_device(false)
+        .with_normally_connectable(true)
+        .with_battery_power(true)
+        .with_supervision_timeout(SupervisionTimeout::DEFAULT)
+        .with_ssr_host_max_latency(INPUT_SSR_MAX_LATENCY)
+        .with_ssr_host_min_timeout(INPUT_SSR_MIN_TIMEOUT);
+    configuration(service_name, hid)
+        .with_service_description("Barcode scanner")
+}
+
+/// Largest X position reported by `graphics_tablet`.
+pub const TABLET_X_MAXIMUM: u16 = 20000;
+/// Largest Y position reported by `graphics_tablet`.
+pub const TABLET_Y_MAXIMUM: u16 = 12000;
+/// Largest tip pressure reported by `graphics_tablet`, for 4096 pressure levels.
+pub const TABLET_PRESSURE_MAXIMUM: u16 = 4095;
+
+/// Create a graphics tablet with a pen reporting its tip switch, barrel switch, whether it is
+/// in range, its position and its pressure. The input report layout is given by
+/// `pen_descriptor`.
+pub fn graphics_tablet(service_name: &str) -> PresetBuilder {
+    let (descriptor, _) = report::pen_descriptor(
+        TABLET_X_MAXIMUM, TABLET_Y_MAXIMUM, TABLET_PRESSURE_MAXIMUM);
+    let descriptor = hid::ClassDescriptor::report(descriptor);
+    let hid = input_device(DeviceSubclass::DIGITIZER_TABLET, descriptor);
+    configuration(service_name, hid)
+        .with_service_description("Graphics tablet")
+}
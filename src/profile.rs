@@ -0,0 +1,60 @@
+//! Registration of configurations with BlueZ.
+//!
+//! BlueZ publishes HID records through `org.bluez.ProfileManager1.RegisterProfile`, which bluer
+//! exposes as `Session::register_profile`. The functions here fill in the profile from a
+//! configuration, so that the record, UUID and PSM always agree. With the `register` feature,
+//! `register` also performs the registration.
+
+use bluer::id::ServiceClass;
+use bluer::rfcomm::{Profile, Role};
+#[cfg(feature = "register")]
+use bluer::{rfcomm::ProfileHandle, Session};
+use hid_device_id::bluetooth::psm;
+use uuid::Uuid;
+
+use crate::{Configuration, SerializeOptions};
+
+impl Configuration {
+    /// Create the profile to register with BlueZ for this configuration.
+    pub fn to_bluer_profile(&self) -> Profile {
+        self.to_bluer_profile_with_options(&SerializeOptions::default())
+    }
+
+    /// Create the profile to register with BlueZ for this configuration, serializing the record
+    /// with the given options.
+    ///
+    /// The profile is registered as a server on the HID control PSM, with the record given as
+    /// the service record. BlueZ does not listen on the interrupt PSM for external profiles, so
+    /// the interrupt channel must be listened on separately.
+    pub fn to_bluer_profile_with_options(&self, options: &SerializeOptions) -> Profile {
+        Profile {
+            uuid: Uuid::from(ServiceClass::Hid),
+            name: self.service_name.as_deref().map(String::from),
+            role: Some(Role::Server),
+            psm: Some(self.transport.control_psm.unwrap_or(psm::HID_CONTROL)),
+            version: Some(self.version.packed()),
+            service_record: Some(self.to_bluez_service_record_with_options(options)),
+            ..Profile::default()
+        }
+    }
+}
+
+/// Register the profile of a configuration with BlueZ, publishing its record.
+///
+/// The record stays published until the returned handle is dropped. Connections to the control
+/// PSM are delivered through the handle.
+#[cfg(feature = "register")]
+pub async fn register(session: &Session, config: &Configuration) -> bluer::Result<ProfileHandle> {
+    register_with_options(session, config, &SerializeOptions::default()).await
+}
+
+/// Register the profile of a configuration with BlueZ, serializing the record with the given
+/// options.
+#[cfg(feature = "register")]
+pub async fn register_with_options(
+    session: &Session,
+    config: &Configuration,
+    options: &SerializeOptions,
+) -> bluer::Result<ProfileHandle> {
+    session.register_profile(config.to_bluer_profile_with_options(options)).await
+}
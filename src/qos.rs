@@ -0,0 +1,146 @@
+//! L2CAP quality of service for the HID channels.
+//!
+//! Neither the HID profile nor SDP defines an attribute for quality of service: HID devices
+//! negotiate it with the Quality of Service option when configuring the L2CAP channels, as
+//! described in 5.3 of the L2CAP specification. The configuration keeps the flow specification
+//! next to the PSMs, and the functions here encode and decode the option.
+
+use std::fmt::{self, Display, Formatter};
+
+/// Type of the Quality of Service configuration option.
+pub const QOS_OPTION_TYPE: u8 = 0x03;
+/// Length of the option's payload, without the type and length bytes.
+pub const QOS_OPTION_LEN: u8 = 22;
+
+/// Level of service requested for a channel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ServiceType {
+    NoTraffic,
+    #[default]
+    BestEffort,
+    Guaranteed,
+    /// A value reserved by the specification.
+    Other(u8),
+}
+
+impl From<u8> for ServiceType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x00 => ServiceType::NoTraffic,
+            0x01 => ServiceType::BestEffort,
+            0x02 => ServiceType::Guaranteed,
+            other => ServiceType::Other(other),
+        }
+    }
+}
+
+impl From<ServiceType> for u8 {
+    fn from(service_type: ServiceType) -> u8 {
+        match service_type {
+            ServiceType::NoTraffic => 0x00,
+            ServiceType::BestEffort => 0x01,
+            ServiceType::Guaranteed => 0x02,
+            ServiceType::Other(other) => other,
+        }
+    }
+}
+
+/// Flow specification of a channel, as carried in the Quality of Service option.
+///
+/// Rates are in bytes per second and times in microseconds. The default is the one assumed by
+/// L2CAP when the option is absent: best effort, with no limit on latency or delay variation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlowSpec {
+    pub service_type: ServiceType,
+    pub token_rate: u32,
+    pub token_bucket_size: u32,
+    pub peak_bandwidth: u32,
+    pub latency: u32,
+    pub delay_variation: u32,
+}
+
+impl Default for FlowSpec {
+    fn default() -> Self {
+        FlowSpec {
+            service_type: ServiceType::BestEffort,
+            token_rate: 0,
+            token_bucket_size: 0,
+            peak_bandwidth: 0,
+            latency: u32::MAX,
+            delay_variation: u32::MAX,
+        }
+    }
+}
+
+/// Error for data which is not a Quality of Service option.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QosError {
+    /// The option has another type, given here.
+    UnexpectedType(u8),
+    /// The option is shorter than its payload, or its length is not 22.
+    InvalidLength(usize),
+}
+
+impl Display for QosError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::UnexpectedType(option_type) =>
+                write!(f, "expected quality of service option, received option 0x{:02x}",
+                       option_type),
+            Self::InvalidLength(len) =>
+                write!(f, "invalid quality of service option length {}", len),
+        }
+    }
+}
+
+impl std::error::Error for QosError {}
+
+impl FlowSpec {
+    /// Encode the flow specification as a Quality of Service configuration option, including
+    /// the type and length.
+    pub fn to_option_bytes(&self) -> [u8; 24] {
+        let mut bytes = [0; 24];
+        bytes[0] = QOS_OPTION_TYPE;
+        bytes[1] = QOS_OPTION_LEN;
+        // The flags byte is reserved.
+        bytes[2] = 0;
+        bytes[3] = self.service_type.into();
+        let values = [
+            self.token_rate,
+            self.token_bucket_size,
+            self.peak_bandwidth,
+            self.latency,
+            self.delay_variation,
+        ];
+        for (i, value) in values.iter().enumerate() {
+            bytes[4 + i * 4..8 + i * 4].copy_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode a Quality of Service configuration option, including the type and length. The
+    /// hint bit of the type is ignored.
+    pub fn from_option_bytes(bytes: &[u8]) -> Result<Self, QosError> {
+        if bytes.len() < 2 {
+            return Err(QosError::InvalidLength(bytes.len()));
+        }
+        if bytes[0] & 0x7f != QOS_OPTION_TYPE {
+            return Err(QosError::UnexpectedType(bytes[0]));
+        }
+        if bytes[1] != QOS_OPTION_LEN || bytes.len() < 24 {
+            return Err(QosError::InvalidLength(bytes[1] as usize));
+        }
+        let value = |i: usize| {
+            let offset = 4 + i * 4;
+            u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+        };
+        Ok(FlowSpec {
+            service_type: bytes[3].into(),
+            token_rate: value(0),
+            token_bucket_size: value(1),
+            peak_bandwidth: value(2),
+            latency: value(3),
+            delay_variation: value(4),
+        })
+    }
+}
@@ -0,0 +1,248 @@
+//! Reading HID records from the plain-text output of `sdptool browse`.
+//!
+//! Without `--xml`, `sdptool browse` and `sdptool records` print each record as a block of
+//! fields, separated by blank lines, for example:
+//!
+//! ```text
+//! Service Name: Keyboard
+//! Service RecHandle: 0x10005
+//! Service Class ID List:
+//!   "Human Interface Device" (0x1124)
+//! Protocol Descriptor List:
+//!   "L2CAP" (0x0100)
+//!     PSM: 17
+//!   "HIDP" (0x0011)
+//! Language Base Attr List:
+//!   code_ISO639: 0x656e
+//!   encoding:    0x6a
+//!   base_offset: 0x100
+//! Profile Descriptor List:
+//!   "Human Interface Device" (0x1124)
+//!     Version: 0x0101
+//! ```
+//!
+//! This format only shows the service strings, the record handle and the lists above. The HID
+//! attributes, including the report descriptor, and the interrupt channel PSM are not printed,
+//! so the configuration read from it is missing them.
+
+use sdp_xml::Tag;
+use hid_device_id::bluetooth::attribute_id;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::{Error, PartialConfiguration};
+use crate::cache::is_hid_record;
+use crate::ids::UuidExt;
+
+/// Error type for reading `sdptool` output
+#[derive(Debug)]
+pub enum SdptoolError {
+    Io(io::Error),
+    MalformedLine(usize),
+    MissingHidRecord,
+    Parse(Error),
+}
+
+impl Display for SdptoolError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) =>
+                write!(f, "I/O error: {}", e),
+            Self::MalformedLine(line) =>
+                write!(f, "line {}: malformed entry", line),
+            Self::MissingHidRecord =>
+                write!(f, "no HID record in sdptool output"),
+            Self::Parse(e) =>
+                write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SdptoolError {}
+
+pub type Result<T> = std::result::Result<T, SdptoolError>;
+
+/// The list field whose entries follow on indented lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Section {
+    None,
+    ServiceClasses,
+    Protocols,
+    LanguageBases,
+    Profiles,
+}
+
+/// The fields of a record read so far.
+#[derive(Debug, Default)]
+struct RecordFields {
+    attributes: Vec<(u16, Tag)>,
+    service_classes: Vec<Tag>,
+    protocols: Vec<Vec<Tag>>,
+    language_bases: Vec<Tag>,
+    profiles: Vec<Vec<Tag>>,
+}
+
+impl RecordFields {
+    fn is_empty(&self) -> bool {
+        self.attributes.is_empty()
+            && self.service_classes.is_empty()
+            && self.protocols.is_empty()
+            && self.language_bases.is_empty()
+            && self.profiles.is_empty()
+    }
+
+    fn into_record(self) -> Tag {
+        let mut attributes = self.attributes;
+        let sequences = |list: Vec<Vec<Tag>>| {
+            Tag::Sequence(list.into_iter().map(Tag::Sequence).collect())
+        };
+        if !self.service_classes.is_empty() {
+            attributes.push((attribute_id::SERVICE_CLASS_ID_LIST,
+                             Tag::Sequence(self.service_classes)));
+        }
+        if !self.protocols.is_empty() {
+            attributes.push((attribute_id::PROTOCOL_DESCRIPTOR_LIST, sequences(self.protocols)));
+        }
+        if !self.language_bases.is_empty() {
+            attributes.push((attribute_id::LANGUAGE_BASE_ATTRIBUTE_ID_LIST,
+                             Tag::Sequence(self.language_bases)));
+        }
+        if !self.profiles.is_empty() {
+            attributes.push((attribute_id::BLUETOOTH_PROFILE_DESCRIPTOR_LIST,
+                             sequences(self.profiles)));
+        }
+        attributes.sort_by_key(|(id, _)| *id);
+        Tag::Record(attributes.into_iter()
+            .map(|(id, value)| Tag::Attribute(id, Box::new(value)))
+            .collect())
+    }
+}
+
+/// Parse a hexadecimal number with a `0x` prefix.
+fn parse_hex(value: &str) -> Option<u32> {
+    let digits = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X"))?;
+    u32::from_str_radix(digits, 16).ok()
+}
+
+/// Parse the UUID of a service class, protocol or profile line, such as
+/// `"Human Interface Device" (0x1124)`. 128-bit UUIDs are printed in full.
+fn parse_uuid_line(line: &str) -> Option<Uuid> {
+    let start = line.rfind('(')?;
+    let value = line[start + 1..].strip_suffix(')')?;
+    match parse_hex(value) {
+        Some(uuid) if value.len() <= 6 => Some(Uuid::from_u16(uuid as u16)),
+        Some(uuid) => Some(Uuid::from_u32(uuid)),
+        None => value.parse().ok(),
+    }
+}
+
+/// Parse a parameter of a protocol or profile descriptor, such as `PSM: 17`.
+fn parse_parameter(line: &str) -> Option<Tag> {
+    let (name, value) = line.split_once(':')?;
+    let value = value.trim();
+    match name {
+        "PSM" => value.parse().ok().map(Tag::UInt16),
+        "Channel" => value.parse().ok().map(Tag::UInt8),
+        "Version" => parse_hex(value).and_then(|v| u16::try_from(v).ok()).map(Tag::UInt16),
+        _ => None,
+    }
+}
+
+/// Read every service record in the output of `sdptool browse` or `sdptool records`.
+///
+/// Parameters of protocols other than the L2CAP PSM, RFCOMM channel and versions are skipped,
+/// as are fields this format shows but no HID record needs.
+pub fn parse_sdptool_records(contents: &str) -> Result<Vec<Tag>> {
+    let mut records = Vec::new();
+    let mut fields = RecordFields::default();
+    let mut section = Section::None;
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            if !fields.is_empty() {
+                records.push(std::mem::take(&mut fields).into_record());
+            }
+            section = Section::None;
+            continue;
+        }
+
+        if !line.starts_with(char::is_whitespace) {
+            section = Section::None;
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.trim()),
+                // Headers such as "Browsing 00:11:22:33:44:55 ..." have no field to read.
+                None => continue,
+            };
+            let text = |id| (id, Tag::Text(value.to_string()));
+            match field {
+                "Service Name" => fields.attributes.push(text(attribute_id::SERVICE_NAME)),
+                "Service Description" =>
+                    fields.attributes.push(text(attribute_id::SERVICE_DESCRIPTION)),
+                "Service Provider" => fields.attributes.push(text(attribute_id::PROVIDER_NAME)),
+                "Service RecHandle" => {
+                    let handle = parse_hex(value)
+                        .ok_or(SdptoolError::MalformedLine(line_number))?;
+                    fields.attributes.push((attribute_id::SERVICE_RECORD_HANDLE,
+                                            Tag::UInt32(handle)));
+                },
+                "Service Class ID List" => section = Section::ServiceClasses,
+                "Protocol Descriptor List" => section = Section::Protocols,
+                "Language Base Attr List" => section = Section::LanguageBases,
+                "Profile Descriptor List" => section = Section::Profiles,
+                _ => {},
+            }
+            continue;
+        }
+
+        let malformed = SdptoolError::MalformedLine(line_number);
+        match section {
+            Section::None => {},
+            Section::ServiceClasses => {
+                let uuid = parse_uuid_line(trimmed).ok_or(malformed)?;
+                fields.service_classes.push(Tag::Uuid(uuid));
+            },
+            Section::Protocols | Section::Profiles => {
+                let list = if section == Section::Protocols {
+                    &mut fields.protocols
+                } else {
+                    &mut fields.profiles
+                };
+                if let Some(uuid) = parse_uuid_line(trimmed) {
+                    list.push(vec![Tag::Uuid(uuid)]);
+                } else if let Some(parameter) = parse_parameter(trimmed) {
+                    list.last_mut().ok_or(malformed)?.push(parameter);
+                }
+            },
+            Section::LanguageBases => {
+                let value = trimmed.split_once(':')
+                    .and_then(|(_, value)| parse_hex(value.trim()))
+                    .and_then(|value| u16::try_from(value).ok())
+                    .ok_or(malformed)?;
+                fields.language_bases.push(Tag::UInt16(value));
+            },
+        }
+    }
+    if !fields.is_empty() {
+        records.push(fields.into_record());
+    }
+    Ok(records)
+}
+
+/// Read the HID record in the output of `sdptool browse` or `sdptool records`.
+pub fn parse_sdptool(contents: &str) -> Result<PartialConfiguration> {
+    let record = parse_sdptool_records(contents)?
+        .into_iter()
+        .find(is_hid_record)
+        .ok_or(SdptoolError::MissingHidRecord)?;
+    PartialConfiguration::from_sdp_tag(record).map_err(SdptoolError::Parse)
+}
+
+/// Read the HID record in a file holding the output of `sdptool browse` or `sdptool records`.
+pub fn read_sdptool_path(path: impl AsRef<Path>) -> Result<PartialConfiguration> {
+    let contents = fs::read_to_string(path).map_err(SdptoolError::Io)?;
+    parse_sdptool(&contents)
+}
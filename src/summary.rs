@@ -0,0 +1,122 @@
+//! Human-readable listing of configurations.
+//!
+//! The listing follows the order of the attributes in the record, naming each one as the
+//! specifications do and decoding its value, for debugging and logging. The alternate form,
+//! `{:#}`, also lists the items of report descriptors.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::Configuration;
+use crate::attribute::*;
+use crate::hid::{report, DescriptorKind, LanguageBase};
+
+/// Write a line naming an attribute and giving its value.
+fn line<A: SdpAttribute>(f: &mut Formatter, value: impl Display) -> fmt::Result {
+    writeln!(f, "  {} (0x{:04x}): {}", A::NAME, A::ATTRIBUTE_ID, value)
+}
+
+/// Write a line for an optional attribute, if it has been given.
+fn optional_line<A: SdpAttribute>(f: &mut Formatter, value: Option<impl Display>) -> fmt::Result {
+    match value {
+        Some(value) => line::<A>(f, value),
+        None => Ok(()),
+    }
+}
+
+/// Format a language base.
+fn language_base(language_base: &LanguageBase) -> String {
+    format!("language 0x{:04x} at 0x{:04x}", language_base.language, language_base.base)
+}
+
+impl Display for Configuration {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "HID service record")?;
+
+        optional_line::<ServiceRecordHandle>(f, self.service_record_handle
+            .map(|handle| format!("0x{:08x}", handle)))?;
+        let mut service_classes = vec![String::from("HID")];
+        service_classes.extend(self.additional_service_classes.iter().map(|uuid| uuid.to_string()));
+        line::<ServiceClassIdList>(f, service_classes.join(", "))?;
+        optional_line::<ServiceRecordState>(f, self.service_record_state
+            .map(|state| format!("0x{:08x}", state)))?;
+        line::<ProtocolDescriptorList>(f, format!("L2CAP PSM 0x{:04x}, HIDP",
+            self.transport.control_psm.unwrap_or(hid_device_id::bluetooth::psm::HID_CONTROL)))?;
+        match &self.transport.browse_groups {
+            None => line::<BrowseGroupList>(f, "public")?,
+            Some(groups) if groups.is_empty() => (),
+            Some(groups) => line::<BrowseGroupList>(f, groups.iter()
+                .map(|uuid| uuid.to_string())
+                .collect::<Vec<_>>()
+                .join(", "))?,
+        }
+        line::<LanguageBaseAttributeIdList>(f, format!(
+            "language 0x{:04x}, encoding 0x{:04x}, at 0x0100",
+            self.primary_language.iso_code, self.encoding))?;
+        optional_line::<ServiceInfoTimeToLive>(f, self.service_info_time_to_live
+            .map(|ttl| format!("{} s", ttl)))?;
+        optional_line::<ServiceAvailability>(f, self.service_availability
+            .map(|availability| format!("0x{:02x}", availability)))?;
+        let mut profiles = vec![format!("HID {}", self.version)];
+        profiles.extend(self.additional_profiles.iter()
+            .map(|(uuid, version)| format!("{} 0x{:04x}", uuid, version)));
+        line::<BluetoothProfileDescriptorList>(f, profiles.join(", "))?;
+        optional_line::<DocumentationUrl>(f, self.documentation_url.as_ref())?;
+        optional_line::<ClientExecutableUrl>(f, self.client_executable_url.as_ref())?;
+        optional_line::<IconUrl>(f, self.icon_url.as_ref())?;
+        line::<AdditionalProtocolDescriptorLists>(f, format!("L2CAP PSM 0x{:04x}, HIDP",
+            self.transport.interrupt_psm.unwrap_or(hid_device_id::bluetooth::psm::HID_INTERRUPT)))?;
+        optional_line::<ServiceName>(f, self.service_name.as_ref().map(|s| format!("{:?}", s)))?;
+        optional_line::<ServiceDescription>(f, self.service_description.as_ref()
+            .map(|s| format!("{:?}", s)))?;
+        optional_line::<ProviderName>(f, self.provider_name.as_ref().map(|s| format!("{:?}", s)))?;
+        for (language, strings) in &self.localized_strings {
+            writeln!(f, "  Strings for {}", language_base(language))?;
+            let offset_strings = [
+                ("Service Name", &strings.service_name),
+                ("Service Description", &strings.service_description),
+                ("Provider Name", &strings.provider_name),
+            ];
+            for (name, string) in offset_strings {
+                if let Some(string) = string {
+                    writeln!(f, "    {}: {:?}", name, string)?;
+                }
+            }
+        }
+
+        let subclass = self.hid.device_subclass;
+        line::<HidDeviceSubclass>(f, format!("0x{:02x} ({:?}, {:?})",
+            u8::from(subclass), subclass.peripheral, subclass.device_type))?;
+        line::<HidCountryCode>(f, format!("0x{:02x} ({:?})",
+            u8::from(self.hid.country_code), self.hid.country_code))?;
+        line::<HidVirtualCable>(f, self.hid.virtual_cable)?;
+        line::<HidReconnectInitiate>(f, self.hid.reconnect_initiate)?;
+        line::<HidDescriptorList>(f, format!("{} descriptors", self.hid.class_descriptors.len()))?;
+        for descriptor in &self.hid.class_descriptors {
+            writeln!(f, "    {}", descriptor)?;
+            // The alternate form also lists the items of report descriptors.
+            if let (true, DescriptorKind::Report(data)) = (f.alternate(), descriptor.kind()) {
+                report::write_listing(f, data, 6)?;
+            }
+        }
+        let mut language_bases = vec![
+            format!("language 0x{:04x} at 0x0100", self.primary_language.hid_code),
+        ];
+        language_bases.extend(self.additional_language_bases().iter().map(language_base));
+        line::<HidLangBaseAttributeIdList>(f, language_bases.join(", "))?;
+        optional_line::<HidBatteryPower>(f, self.hid.battery_power)?;
+        optional_line::<HidRemoteWake>(f, self.hid.remote_wake)?;
+        optional_line::<HidSupervisionTimeout>(f, self.hid.supervision_timeout
+            .map(|timeout| format!("{:?}", timeout.as_duration())))?;
+        optional_line::<HidNormallyConnectable>(f, self.hid.normally_connectable)?;
+        line::<HidBootDevice>(f, self.hid.boot_device)?;
+        optional_line::<HidSsrHostMaxLatency>(f, self.hid.ssr_host_max_latency
+            .map(|latency| format!("{:?}", latency.as_duration())))?;
+        optional_line::<HidSsrHostMinTimeout>(f, self.hid.ssr_host_min_timeout
+            .map(|timeout| format!("{:?}", timeout.as_duration())))?;
+
+        for (id, tag) in &self.raw_attributes {
+            writeln!(f, "  Raw attribute (0x{:04x}): {}", id, tag.name())?;
+        }
+        Ok(())
+    }
+}
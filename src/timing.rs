@@ -0,0 +1,116 @@
+//! Durations measured in baseband slots.
+//!
+//! The HID record gives timeouts and latencies as a number of baseband slots of 625
+//! microseconds each. The types here convert them from and to `Duration`, so that callers do
+//! not need to remember the unit.
+
+use std::time::Duration;
+
+/// Length of a baseband slot.
+pub const SLOT: Duration = Duration::from_micros(625);
+
+/// Convert a duration to the nearest number of slots, or return `None` if it does not fit in
+/// 16 bits.
+pub(crate) fn slots_from_duration(duration: Duration) -> Option<u16> {
+    let slot_nanos = SLOT.as_nanos();
+    let slots = (duration.as_nanos() + slot_nanos / 2) / slot_nanos;
+    u16::try_from(slots).ok()
+}
+
+/// Convert a number of slots to a duration.
+pub(crate) fn duration_from_slots(slots: u16) -> Duration {
+    SLOT * slots as u32
+}
+
+/// Link supervision timeout, after which the host considers the link lost.
+///
+/// Ranges from one slot, 0.625 ms, to 0xffff slots, about 41 seconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SupervisionTimeout(u16);
+
+impl SupervisionTimeout {
+    /// Timeout of 0x0c80 slots, or two seconds, assumed by hosts if the attribute is absent.
+    pub const DEFAULT: Self = SupervisionTimeout(0x0c80);
+
+    /// Create a timeout from a number of slots. The value is not checked, so that records with
+    /// a timeout of zero slots can be read and written unchanged.
+    pub const fn from_slots(slots: u16) -> Self {
+        SupervisionTimeout(slots)
+    }
+
+    /// Create a timeout from a duration, rounded to the nearest slot. Return `None` if the
+    /// duration rounds to zero or exceeds 0xffff slots.
+    pub fn from_duration(duration: Duration) -> Option<Self> {
+        match slots_from_duration(duration) {
+            Some(0) | None => None,
+            Some(slots) => Some(SupervisionTimeout(slots)),
+        }
+    }
+
+    /// Return the timeout as a number of slots, as written in the record.
+    pub const fn slots(&self) -> u16 {
+        self.0
+    }
+
+    /// Return the timeout as a duration.
+    pub fn as_duration(&self) -> Duration {
+        duration_from_slots(self.0)
+    }
+}
+
+impl Default for SupervisionTimeout {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Define a sniff subrating parameter, ranging from zero to 0xfffe slots.
+macro_rules! ssr_duration {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(u16);
+
+        impl $name {
+            /// Largest valid value, 0xfffe slots, or about 41 seconds.
+            pub const MAX: Self = $name(0xfffe);
+
+            /// Create a value from a number of slots. The value is not checked, so that records
+            /// with reserved values can be read and written unchanged.
+            pub const fn from_slots(slots: u16) -> Self {
+                $name(slots)
+            }
+
+            /// Create a value from a duration, rounded to the nearest slot. Return `None` if the
+            /// duration exceeds 0xfffe slots.
+            pub fn from_duration(duration: Duration) -> Option<Self> {
+                slots_from_duration(duration)
+                    .filter(|slots| *slots <= Self::MAX.0)
+                    .map($name)
+            }
+
+            /// Return the value as a number of slots, as written in the record.
+            pub const fn slots(&self) -> u16 {
+                self.0
+            }
+
+            /// Return the value as a duration.
+            pub fn as_duration(&self) -> Duration {
+                duration_from_slots(self.0)
+            }
+
+            /// Return true if the value is within the range allowed by the HID specification.
+            pub fn is_valid(&self) -> bool {
+                self.0 <= Self::MAX.0
+            }
+        }
+    };
+}
+
+ssr_duration!(
+    /// Maximum latency the host may use for sniff subrating.
+    SsrHostMaxLatency);
+
+ssr_duration!(
+    /// Minimum time the host must remain in sniff mode before entering sniff subrating.
+    SsrHostMinTimeout);
@@ -1,235 +1,509 @@
-use bluer::id::ServiceClass;
 use sdp_xml::Tag;
-use hid_device_id::bluetooth::{attribute_id, protocol, psm};
+use hid_device_id::bluetooth::psm;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
 use uuid::Uuid;
 
-use crate::{Configuration, hid};
+use crate::{Configuration, ServiceStrings, hid};
+use crate::attribute::*;
+use crate::data_element::encode_element;
+use crate::ids::ServiceClass;
+use crate::to_xml;
 
 // Unit = 625 microseconds for each duration.
 
-// For later, consider a design where every attribute is a separate struct that implements some
-// kind of automatic conversion to a tag.
-// This way, we can correlate each setting's type to a potential attribute.
+/// Error type for configurations which cannot be serialized into a meaningful record
+#[derive(Debug)]
+pub enum SerializeError {
+    EmptyDescriptorList,
+    /// The class descriptor list has no report descriptor, so hosts cannot use the device.
+    MissingReportDescriptor,
+    /// A descriptor, of the given type and length, is too long to fit in a record.
+    DescriptorTooLong { descriptor_type: u8, len: usize },
+    /// An additional language uses the base of the primary language, 0x0100.
+    PrimaryLanguageBaseReused,
+    /// The strings of two languages, with the given bases, would overlap.
+    OverlappingLanguageBases(u16, u16),
+    BootDeviceWithoutVirtualCable,
+    BootDeviceWithoutReconnectInitiate,
+}
+
+impl Display for SerializeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::EmptyDescriptorList =>
+                write!(f, "class descriptor list is empty"),
+            Self::MissingReportDescriptor =>
+                write!(f, "class descriptor list has no report descriptor"),
+            Self::DescriptorTooLong { descriptor_type, len } =>
+                write!(f, "descriptor of type 0x{:02x} is too long, {} bytes", descriptor_type, len),
+            Self::PrimaryLanguageBaseReused =>
+                write!(f, "additional language uses the primary language base 0x0100"),
+            Self::OverlappingLanguageBases(a, b) =>
+                write!(f, "strings of language bases 0x{:04x} and 0x{:04x} overlap", a, b),
+            Self::BootDeviceWithoutVirtualCable =>
+                write!(f, "boot device must support virtual cable"),
+            Self::BootDeviceWithoutReconnectInitiate =>
+                write!(f, "boot device must initiate reconnection"),
+        }
+    }
+}
+
+/// Options controlling how configurations are serialized.
+#[derive(Clone, Debug, Default)]
+pub struct SerializeOptions {
+    /// Indent XML output, one element per line, so that records can be read and diffed.
+    /// Otherwise, XML is written without any whitespace.
+    pub pretty: bool,
+
+    /// Emit optional attributes which have not been set, with the value hosts assume when they
+    /// are absent.
+    pub emit_defaults: bool,
+
+    /// Emit attributes deprecated by HID 1.1, such as HIDProfileVersion, for older hosts.
+    pub include_deprecated: bool,
+
+    /// Encoding used for every class descriptor, overriding the form stored in each descriptor.
+    pub descriptor_encoding: Option<DescriptorEncoding>,
+}
+
+/// Encoding of class descriptor data in a record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DescriptorEncoding {
+    /// Write the bytes as a plain text element, if they are valid UTF-8 and may appear in XML.
+    /// Other data is written as hex.
+    Raw,
+    /// Always write the bytes as a raw text element, hex-encoded in XML.
+    Hex,
+}
+
+impl SerializeOptions {
+    /// Return the value of an optional attribute, or, if defaults are emitted, the value hosts
+    /// assume when the attribute is absent.
+    fn or_default<T>(&self, value: Option<T>, default: T) -> Option<T> {
+        match value {
+            Some(value) => Some(value),
+            None if self.emit_defaults => Some(default),
+            None => None,
+        }
+    }
+}
+
+impl From<hid::DescriptorForm> for DescriptorEncoding {
+    fn from(form: hid::DescriptorForm) -> Self {
+        match form {
+            hid::DescriptorForm::Text => DescriptorEncoding::Raw,
+            hid::DescriptorForm::RawText => DescriptorEncoding::Hex,
+        }
+    }
+}
+
+/// Merges generated attributes, which arrive in ascending ID order, with attributes at
+/// arbitrary IDs, passing the result to a sink in ascending ID order.
+struct AttributeMerger<F> {
+    extra_attributes: BTreeMap<u16, Tag>,
+    emit: F,
+}
+
+impl<E, F: FnMut(Tag) -> Result<(), E>> AttributeMerger<F> {
+    /// Emit every extra attribute with an ID below the given ID.
+    fn flush_below(&mut self, id: u16) -> Result<(), E> {
+        while let Some(entry) = self.extra_attributes.first_entry() {
+            if *entry.key() >= id {
+                break;
+            }
+            let (extra_id, tag) = entry.remove_entry();
+            (self.emit)(Tag::attribute(extra_id, tag))?;
+        }
+        Ok(())
+    }
+
+    /// Emit a generated attribute, unless an extra attribute replaces it.
+    fn push_tag(&mut self, id: u16, tag: Tag) -> Result<(), E> {
+        self.flush_below(id)?;
+        let tag = self.extra_attributes.remove(&id).unwrap_or(tag);
+        (self.emit)(Tag::attribute(id, tag))
+    }
+
+    /// Emit a typed attribute, unless an extra attribute replaces it.
+    fn push<A: SdpAttribute>(&mut self, attribute: A) -> Result<(), E> {
+        self.push_tag(A::ATTRIBUTE_ID, attribute.into_tag())
+    }
+
+    /// Emit the remaining extra attributes.
+    fn finish(mut self) -> Result<(), E> {
+        for (id, tag) in self.extra_attributes {
+            (self.emit)(Tag::attribute(id, tag))?;
+        }
+        Ok(())
+    }
+}
 
 impl Configuration {
     pub fn to_sdp_tag(&self) -> Tag {
+        self.to_sdp_tag_with_options(&SerializeOptions::default())
+    }
+
+    /// Create the SDP record for this configuration, using the given options.
+    ///
+    /// Tags own their data, so every string and descriptor is copied into the record. Use
+    /// `into_sdp_tag_with_options` to move the strings instead.
+    pub fn to_sdp_tag_with_options(&self, options: &SerializeOptions) -> Tag {
+        self.clone().into_sdp_tag_with_options(options)
+    }
+
+    /// Check that the configuration describes a usable record.
+    fn check(&self) -> Result<(), SerializeError> {
+        if self.hid.class_descriptors.is_empty() {
+            return Err(SerializeError::EmptyDescriptorList);
+        }
+        let has_report_descriptor = self.hid.class_descriptors.iter()
+            .any(|d| d.descriptor_type() == hid::descriptor_type::REPORT);
+        if !has_report_descriptor {
+            return Err(SerializeError::MissingReportDescriptor);
+        }
+        if let Some(descriptor) = self.hid.class_descriptors.iter().find(|d| !d.fits_in_record()) {
+            return Err(SerializeError::DescriptorTooLong {
+                descriptor_type: descriptor.descriptor_type(),
+                len: descriptor.len(),
+            });
+        }
+        // Each language's strings occupy three IDs from its base, which must not overlap with
+        // those of any other language.
+        let mut bases = vec![0x0100];
+        for language in &self.additional_language_bases() {
+            if language.base == 0x0100 {
+                return Err(SerializeError::PrimaryLanguageBaseReused);
+            }
+            let overlapping = bases.iter()
+                .find(|base| base.abs_diff(language.base) <= ServiceStrings::PROVIDER_NAME_OFFSET);
+            if let Some(other) = overlapping {
+                return Err(SerializeError::OverlappingLanguageBases(*other, language.base));
+            }
+            bases.push(language.base);
+        }
+        // Boot devices must support virtual cables and reconnection, HID 5.3.4.12.
+        if self.hid.boot_device && !self.hid.virtual_cable {
+            return Err(SerializeError::BootDeviceWithoutVirtualCable);
+        }
+        if self.hid.boot_device && !self.hid.reconnect_initiate {
+            return Err(SerializeError::BootDeviceWithoutReconnectInitiate);
+        }
+        Ok(())
+    }
+
+    /// Create the SDP record for this configuration, or return an error if the configuration
+    /// would produce an invalid record.
+    pub fn try_to_sdp_tag(&self) -> Result<Tag, SerializeError> {
+        self.try_to_sdp_tag_with_options(&SerializeOptions::default())
+    }
+
+    /// Create the SDP record for this configuration using the given options, or return an error
+    /// if the configuration would produce an invalid record.
+    pub fn try_to_sdp_tag_with_options(&self, options: &SerializeOptions) -> Result<Tag, SerializeError> {
+        self.check()?;
+        Ok(self.to_sdp_tag_with_options(options))
+    }
+
+    /// Create the SDP record for this configuration, moving its strings into the record instead
+    /// of copying them.
+    pub fn into_sdp_tag(self) -> Tag {
+        self.into_sdp_tag_with_options(&SerializeOptions::default())
+    }
+
+    /// Create the SDP record for this configuration, using the given options, moving its strings
+    /// into the record instead of copying them. Descriptor data may be shared with other
+    /// configurations, so it is always copied.
+    pub fn into_sdp_tag_with_options(self, options: &SerializeOptions) -> Tag {
+        let mut attributes = Vec::new();
+        self.emit_attributes(options, |attribute| {
+            attributes.push(attribute);
+            Ok::<(), std::convert::Infallible>(())
+        }).unwrap_or_else(|e| match e {});
+
+        // Construct document from attribute list
+
+        Tag::record(attributes)
+    }
+
+    /// Generate every attribute of the record, in ascending ID order as SDP requires, passing
+    /// each to `emit` as soon as it is created. Both the tree-based and the streaming
+    /// serializers are built on this, so the record is never held in memory as a whole unless
+    /// the caller collects it.
+    fn emit_attributes<E>(
+        self,
+        options: &SerializeOptions,
+        emit: impl FnMut(Tag) -> Result<(), E>,
+    ) -> Result<(), E> {
         // Supervision timeout: Optional. Default 2 seconds. Unit is 625
         // microseconds, one baseband slot.
         // Normally connectable: false because we are not always in page scan mode.
         // HID boot device:  Required for keyboards and mice. Also mandatory argument.
 
-        let mut attributes = Vec::new();
+        // Attributes at arbitrary IDs, the strings of additional languages and raw attributes,
+        // are merged into the generated attributes by ID. Raw attributes replace any other
+        // attribute with the same ID.
+
+        let additional_language_bases = self.additional_language_bases();
+        let mut extra_attributes = BTreeMap::new();
+        for (hid::LanguageBase { base, .. }, strings) in self.localized_strings {
+            let offset_strings = [
+                (ServiceStrings::SERVICE_NAME_OFFSET, strings.service_name),
+                (ServiceStrings::SERVICE_DESCRIPTION_OFFSET, strings.service_description),
+                (ServiceStrings::PROVIDER_NAME_OFFSET, strings.provider_name),
+            ];
+            for (offset, string) in offset_strings {
+                if let Some(string) = string {
+                    extra_attributes.insert(base + offset, Tag::Text(string.into_owned()));
+                }
+            }
+        }
+        for (id, tag) in self.raw_attributes {
+            extra_attributes.insert(id, tag);
+        }
+        let mut out = AttributeMerger { extra_attributes, emit };
+
+        // Add the service record handle, if it has been given.
+
+        if let Some(handle) = self.service_record_handle {
+            out.push(ServiceRecordHandle(handle))?;
+        }
 
         // Add service class ID list attribute
+        // The HID service class is always listed first.
+
+        let mut service_classes = vec![Uuid::from(ServiceClass::Hid)];
+        service_classes.extend(self.additional_service_classes);
+        out.push(ServiceClassIdList(service_classes))?;
+
+        // Add the service record state, if it has been given.
 
-        attributes.push(Tag::attribute(
-                attribute_id::SERVICE_CLASS_ID_LIST,
-                [Uuid::from(ServiceClass::Hid),]));
+        if let Some(state) = self.service_record_state {
+            out.push(ServiceRecordState(state))?;
+        }
 
         // Add protocol descriptor list (L2CAP:HIDControl -> HIDP)
 
-        attributes.push(Tag::attribute(
-                attribute_id::PROTOCOL_DESCRIPTOR_LIST,
-                (
-                    // Protocol Descriptor 0
-                    (
-                        protocol::L2CAP,
-                        psm::HID_CONTROL,
-                        ),
-                        // Protocol Descriptor 1
-                        (
-                            protocol::HID_PROTOCOL,
-                            ),
-                            )));
+        out.push(ProtocolDescriptorList {
+            psm: self.transport.control_psm.unwrap_or(psm::HID_CONTROL),
+        })?;
 
         // Add browse group list (optional)
+        // Defaults to the public browse group, and is omitted if empty.
 
-        attributes.push(Tag::attribute(
-                attribute_id::BROWSE_GROUP_LIST,
-                (Uuid::from(ServiceClass::PublicBrowseGroup),)));
-
+        match self.transport.browse_groups {
+            None => out.push(BrowseGroupList::public())?,
+            Some(browse_groups) if browse_groups.is_empty() => (),
+            Some(browse_groups) => out.push(BrowseGroupList(browse_groups))?,
+        }
 
         // Add primary base attribute ID
+        // The base ID of the primary language must be 0x0100, which is why the strings are
+        // offset from 0x0100.
 
-        attributes.push(Tag::attribute(
-                attribute_id::LANGUAGE_BASE_ATTRIBUTE_ID_LIST,
-                ( // Described in 5.1.8 of Bluetooth Core
-                    self.primary_language.iso_code, // 0x656eu16, English, ISO 639:1988 (E/F)
-                    self.encoding, // 0x006au16, // MIBEnum value for UTF-8, from IANA's database
-                    0x0100u16, // Base ID: Primary Language
-                               // This value is why the strings are offset from 0x0100.
-                               // However, for the primary language, the offset must be 0x0100.
-                               // Don't forget the u16.
-                )));
+        out.push(LanguageBaseAttributeIdList {
+            language: self.primary_language.iso_code,
+            encoding: self.encoding,
+            base: 0x0100,
+        })?;
 
-        // Add additional protocol descriptor lists (L2CAP:HIDInterrupt -> HIDP)
+        // Add the service info time to live and service availability, if they have been given.
 
-        attributes.push(Tag::attribute(
-                attribute_id::ADDITIONAL_PROTOCOL_DESCRIPTOR_LISTS,
-                (
-                    (
-                        // Protocol Descriptor List 0
-                        (
-                            protocol::L2CAP,
-                            psm::HID_INTERRUPT,
-                            ),
-                            // Protocol Descriptor 1
-                            (
-                                protocol::HID_PROTOCOL,
-                                ),
-                                ),
-                                )));
-
-        // Add the service name, if it has been given.
+        if let Some(ttl) = self.service_info_time_to_live {
+            out.push(ServiceInfoTimeToLive(ttl))?;
+        }
 
-        // Note: if we don't clone here, the stack overflows. Probably an issue with the type
-        // inference. We should fix the inference rules to prevent ambiguous situations like these.
-        // Probably a cycle where Strings and char strings are converted between each other.
-        if let Some(service_name) = &self.service_name {
-            attributes.push(Tag::attribute(
-                    attribute_id::SERVICE_NAME,
-                    service_name.clone()));
+        if let Some(availability) = self.service_availability {
+            out.push(ServiceAvailability(availability))?;
         }
 
-        // Add the service description, if it has been given.
+        // Add profile descriptor list, which contains the HID UUID and the version, followed by
+        // any additional profiles.
+
+        let mut profiles = vec![(Uuid::from(ServiceClass::Hid), self.version.packed())];
+        profiles.extend(self.additional_profiles);
+        out.push(BluetoothProfileDescriptorList(profiles))?;
 
-        if let Some(service_description) = &self.service_description {
-            attributes.push(Tag::attribute(
-                    attribute_id::SERVICE_DESCRIPTION,
-                    service_description.clone()));
+        // Add the documentation, client executable and icon URLs, if they have been given.
+
+        if let Some(url) = self.documentation_url {
+            out.push(DocumentationUrl(url.into_owned()))?;
         }
 
-        // Add the provider name, if it has been given.
+        if let Some(url) = self.client_executable_url {
+            out.push(ClientExecutableUrl(url.into_owned()))?;
+        }
 
-        if let Some(provider_name) = &self.provider_name {
-            attributes.push(Tag::attribute(
-                    attribute_id::PROVIDER_NAME,
-                    provider_name.clone()));
+        if let Some(url) = self.icon_url {
+            out.push(IconUrl(url.into_owned()))?;
         }
 
-        // Add profile descriptor list, which contains the HID UUID and the version.
+        // Add additional protocol descriptor lists (L2CAP:HIDInterrupt -> HIDP)
 
-        attributes.push(Tag::attribute(
-                attribute_id::BLUETOOTH_PROFILE_DESCRIPTOR_LIST,
-                (
-                    // Profile 0
-                    (
-                        Uuid::from(ServiceClass::Hid),
-                        self.version, // Version
-                    ),
-                    )));
+        out.push(AdditionalProtocolDescriptorLists {
+            psm: self.transport.interrupt_psm.unwrap_or(psm::HID_INTERRUPT),
+        })?;
 
-        // Add the HID parser version (1.1.1).
+        // Add the service name, description and provider name, if they have been given.
 
-        attributes.push(Tag::attribute(
-                attribute_id::hid::HID_PARSER_VERSION,
-                0x0111u16)); // Mandatory this value
+        if let Some(service_name) = self.service_name {
+            out.push(ServiceName(service_name.into_owned()))?;
+        }
 
-        // Add the HID device subclass.
+        if let Some(service_description) = self.service_description {
+            out.push(ServiceDescription(service_description.into_owned()))?;
+        }
 
-        attributes.push(Tag::attribute(
-                attribute_id::hid::HID_DEVICE_SUBCLASS,
-                self.hid.device_subclass));
+        if let Some(provider_name) = self.provider_name {
+            out.push(ProviderName(provider_name.into_owned()))?;
+        }
 
-        // Add the country code.
+        // Add the HID parser version (1.1.1). Mandatory this value.
 
-        attributes.push(Tag::attribute(
-                attribute_id::hid::HID_COUNTRY_CODE,
-                self.hid.country_code)); // Optional, can be 0
+        out.push(HidParserVersion(0x0111))?;
 
-        // Add the virtual cable attribute.
+        // Add the HID device subclass and country code. The country code may be 0.
 
-        attributes.push(Tag::attribute(
-                attribute_id::hid::HID_VIRTUAL_CABLE,
-                self.hid.virtual_cable)); // If HIDBootDevice is true, 5.3.4.12
+        out.push(HidDeviceSubclass(self.hid.device_subclass.into()))?;
+        out.push(HidCountryCode(self.hid.country_code.into()))?;
 
-        // Add the ReconnectInitiate attribute.
+        // Add the virtual cable and reconnect initiate attributes.
+        // Both must be true if HIDBootDevice is true, 5.3.4.12.
 
-        attributes.push(Tag::attribute(
-                attribute_id::hid::HID_RECONNECT_INITIATE,
-                self.hid.reconnect_initiate)); // If HIDBootDevice is true, 5.3.4.12
+        out.push(HidVirtualCable(self.hid.virtual_cable))?;
+        out.push(HidReconnectInitiate(self.hid.reconnect_initiate))?;
 
         // Add HID descriptor lists.
         // This will likely contain a HID report descriptor.
 
-        let descriptor_list: Vec<_> = self.hid.class_descriptors.iter()
-            .map(|hid::ClassDescriptor(t, data)| (t, Tag::bytes(&data)))
-            .collect();
-        attributes.push(Tag::attribute(
-                attribute_id::hid::HID_DESCRIPTOR_LIST,
-                Tag::sequence(descriptor_list)));
-
-        // Add language base attribute
-        // Described in 5.3.4.8 of HID
-
-        let mut language_bases = Vec::new();
-        // Add primary language
-        language_bases.push((self.primary_language.hid_code, 0x0100));
-        // Add additional languages
-        let additional_language_bases = self.hid.additional_languages.iter()
-            .map(|l| (l.language, l.base));
+        out.push_tag(
+            HidDescriptorList::ATTRIBUTE_ID,
+            HidDescriptorList(self.hid.class_descriptors)
+                .into_tag_with_encoding(options.descriptor_encoding))?;
+
+        // Add language base attribute, the primary language followed by additional languages.
+
+        let mut language_bases = vec![hid::LanguageBase {
+            language: self.primary_language.hid_code,
+            base: 0x0100,
+        }];
         language_bases.extend(additional_language_bases);
+        out.push(HidLangBaseAttributeIdList(language_bases))?;
 
-        attributes.push(Tag::attribute(
-                attribute_id::hid::HID_LANG_BASE_ATTRIBUTE,
-                Tag::sequence(language_bases)));
+        // Add the optional power and wake attributes.
 
-        // Add battery power, if specified
+        if let Some(v) = options.or_default(self.hid.battery_power, hid::defaults::BATTERY_POWER) {
+            out.push(HidBatteryPower(v))?;
+        }
 
-        if let Some(battery_power) = self.hid.battery_power {
-            attributes.push(Tag::attribute(
-                    attribute_id::hid::HID_BATTERY_POWER,
-                    battery_power));
+        if let Some(v) = options.or_default(self.hid.remote_wake, hid::defaults::REMOTE_WAKE) {
+            out.push(HidRemoteWake(v))?;
         }
 
-        // Add the remote wake attribute, if it has been specified.
+        // Add the deprecated HID profile version, if requested.
 
-        if let Some(remote_wake) = self.hid.remote_wake {
-            attributes.push(Tag::attribute(
-                    attribute_id::hid::HID_REMOTE_WAKE,
-                    remote_wake));
+        if options.include_deprecated {
+            out.push(HidProfileVersion(self.version.packed()))?;
         }
 
-        // Specify the HIDSupervisionTimeout attribute, if it has been given.
+        // Add the optional timeout and connectability attributes.
+        // Normally connectable is false if we are not always in page scan mode.
 
-        if let Some(supervision_timeout) = self.hid.supervision_timeout {
-            attributes.push(Tag::attribute(
-                    attribute_id::hid::HID_SUPERVISION_TIMEOUT,
-                    supervision_timeout));
+        if let Some(v) = options.or_default(self.hid.supervision_timeout,
+                                            hid::defaults::SUPERVISION_TIMEOUT) {
+            out.push(HidSupervisionTimeout(v.slots()))?;
         }
 
-        // Specify the HIDNormallyConnectable attribute, if it has been given.
-
-        if let Some(normally_connectable) = self.hid.normally_connectable {
-            attributes.push(Tag::attribute(
-                    attribute_id::hid::HID_NORMALLY_CONNECTABLE,
-                    normally_connectable)); // False because we are not always in page scan mode.
+        if let Some(v) = options.or_default(self.hid.normally_connectable,
+                                            hid::defaults::NORMALLY_CONNECTABLE) {
+            out.push(HidNormallyConnectable(v))?;
         }
 
-        // Specify the boot device attribute.
+        // Specify the boot device attribute. Required for keyboards and mice.
 
-        attributes.push(Tag::attribute(
-                attribute_id::hid::HID_BOOT_DEVICE,
-                self.hid.boot_device)); // Required for keyboards and mice.
+        out.push(HidBootDevice(self.hid.boot_device))?;
 
-        // Add the SSR Host Max Latency attribute, if it has been given.
+        // Add the SSR host max latency and min timeout attributes, if they have been given.
 
-        if let Some(ssr_host_max_latency) = self.hid.ssr_host_max_latency {
-            attributes.push(Tag::attribute(
-                    attribute_id::hid::HID_SSR_HOST_MAX_LATENCY,
-                    ssr_host_max_latency));
+        if let Some(latency) = self.hid.ssr_host_max_latency {
+            out.push(HidSsrHostMaxLatency(latency.slots()))?;
         }
 
-        // Add the SSR Host Min Latency attribute, if it has been given.
-
-        if let Some(ssr_host_min_timeout) = self.hid.ssr_host_min_timeout {
-            attributes.push(Tag::attribute(
-                    attribute_id::hid::HID_SSR_HOST_MIN_TIMEOUT,
-                    ssr_host_min_timeout));
+        if let Some(timeout) = self.hid.ssr_host_min_timeout {
+            out.push(HidSsrHostMinTimeout(timeout.slots()))?;
         }
 
-        // Construct document from attribute list
+        out.finish()
+    }
 
-        Tag::record(attributes)
+    /// Encode the configuration as a binary SDP record, as sent in SDP responses.
+    pub fn to_sdp_bytes(&self) -> Vec<u8> {
+        self.to_sdp_bytes_with_options(&SerializeOptions::default())
+    }
+
+    /// Encode the configuration as a binary SDP record, using the given options.
+    pub fn to_sdp_bytes_with_options(&self, options: &SerializeOptions) -> Vec<u8> {
+        encode_element(&self.to_sdp_tag_with_options(options))
+    }
+
+    /// Return the length in bytes of the binary SDP record. SDP servers encode records
+    /// themselves, and may choose longer size indices or add attributes such as the record
+    /// handle, so the record they send may be a few bytes longer.
+    pub fn serialized_size_estimate(&self) -> usize {
+        self.to_sdp_bytes().len()
+    }
+
+    /// Return the configuration as an SDP XML document, as expected by BlueZ.
+    pub fn to_sdp_xml_string(&self) -> String {
+        self.to_sdp_xml_string_with_options(&SerializeOptions::default())
+    }
+
+    /// Return the configuration as an SDP XML document, using the given options.
+    pub fn to_sdp_xml_string_with_options(&self, options: &SerializeOptions) -> String {
+        to_xml::to_sdp_xml_string(&self.to_sdp_tag_with_options(options), options.pretty)
+    }
+
+    /// Return the configuration as a record string for the ServiceRecord option of BlueZ's
+    /// `RegisterProfile`, written in the dialect BlueZ's parser expects; see `to_xml` for the
+    /// escaping rules.
+    pub fn to_bluez_service_record(&self) -> String {
+        self.to_bluez_service_record_with_options(&SerializeOptions::default())
+    }
+
+    /// Return the configuration as a record string for BlueZ, using the given options. The
+    /// record is never indented, whatever `pretty` is set to.
+    pub fn to_bluez_service_record_with_options(&self, options: &SerializeOptions) -> String {
+        to_xml::to_bluez_record_string(&self.to_sdp_tag_with_options(options))
+    }
+
+    /// Write the configuration to the given writer as an SDP XML document.
+    pub fn to_sdp_xml_writer<W: Write>(&self, writer: W) -> io::Result<()> {
+        self.to_sdp_xml_writer_with_options(writer, &SerializeOptions::default())
+    }
+
+    /// Write the configuration to the given writer as an SDP XML document, using the given
+    /// options.
+    ///
+    /// The document is streamed to the writer one attribute at a time, without building the
+    /// whole record first.
+    pub fn to_sdp_xml_writer_with_options<W: Write>(
+        &self,
+        writer: W,
+        options: &SerializeOptions,
+    ) -> io::Result<()> {
+        self.clone().into_sdp_xml_writer_with_options(writer, options)
+    }
+
+    /// Stream the configuration to the given writer as an SDP XML document, using the given
+    /// options, moving its strings instead of copying them.
+    pub fn into_sdp_xml_writer_with_options<W: Write>(
+        self,
+        writer: W,
+        options: &SerializeOptions,
+    ) -> io::Result<()> {
+        let mut record_writer = to_xml::RecordWriter::begin(writer, options.pretty)?;
+        self.emit_attributes(options, |attribute| record_writer.write_attribute(&attribute))?;
+        record_writer.finish()
     }
 }
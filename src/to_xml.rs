@@ -0,0 +1,220 @@
+//! Writing SDP records as XML.
+//!
+//! The output follows the format BlueZ uses for service records, as accepted by the
+//! ServiceRecord option of `org.bluez.ProfileManager1.RegisterProfile`.
+//!
+//! The canonical form read back by `sdp_xml_reader` differs from what BlueZ's own parser
+//! expects in a few places, so `to_bluez_record_string` writes the BlueZ dialect:
+//!
+//! - `&`, `<`, `>`, `"` and `'` are written as entities, as in the canonical form.
+//! - Tabs, line feeds and carriage returns in text are written as character references, since
+//!   XML parsers turn them into spaces in attribute values.
+//! - Text with any other control character, which XML 1.0 cannot carry at all, is written as a
+//!   hex-encoded `text` element, which BlueZ decodes into the same bytes.
+//! - 128-bit integers are written as 32 hex digits without a `0x` prefix, as BlueZ reads them
+//!   byte by byte.
+//!
+//! The record is written without whitespace between elements.
+
+use sdp_xml::Tag;
+use std::io::{self, Write};
+use uuid::Uuid;
+
+use crate::attribute::is_xml_safe;
+use crate::ids::UuidExt;
+
+/// Escape the characters which may not appear in an XML attribute value. If `whitespace` is
+/// set, whitespace other than spaces is escaped too, so that it survives normalization.
+fn escape(text: &str, whitespace: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' if whitespace => escaped.push_str(&format!("&#{};", c as u32)),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Format bytes as lowercase hex.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Format a UUID as BlueZ does: short UUIDs as hex, others in the full hyphenated form.
+fn format_uuid(uuid: &Uuid) -> String {
+    if let Some(short) = uuid.as_u16() {
+        format!("0x{:04x}", short)
+    } else if let Some(short) = uuid.as_u32() {
+        format!("0x{:08x}", short)
+    } else {
+        uuid.hyphenated().to_string()
+    }
+}
+
+/// Writer for SDP XML, either compact or indented.
+struct XmlWriter<W: Write> {
+    writer: W,
+    pretty: bool,
+    /// Write the dialect BlueZ's parser expects, rather than the canonical form.
+    bluez: bool,
+    depth: usize,
+}
+
+impl<W: Write> XmlWriter<W> {
+    /// Begin a line at the current depth, if pretty-printing.
+    fn start_line(&mut self) -> io::Result<()> {
+        if self.pretty {
+            write!(self.writer, "{:width$}", "", width = self.depth * 2)?;
+        }
+        Ok(())
+    }
+
+    /// End a line, if pretty-printing.
+    fn end_line(&mut self) -> io::Result<()> {
+        if self.pretty {
+            writeln!(self.writer)?;
+        }
+        Ok(())
+    }
+
+    /// Write an element with only a value attribute.
+    fn write_value(&mut self, name: &str, value: &str) -> io::Result<()> {
+        self.start_line()?;
+        write!(self.writer, "<{} value=\"{}\" />", name, value)?;
+        self.end_line()
+    }
+
+    /// Write an opening tag and increase the depth.
+    fn open(&mut self, tag: &str) -> io::Result<()> {
+        self.start_line()?;
+        write!(self.writer, "<{}>", tag)?;
+        self.end_line()?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Decrease the depth and write a closing tag.
+    fn close(&mut self, name: &str) -> io::Result<()> {
+        self.depth -= 1;
+        self.start_line()?;
+        write!(self.writer, "</{}>", name)?;
+        self.end_line()
+    }
+
+    /// Write a single tag and its children.
+    fn write_tag(&mut self, tag: &Tag) -> io::Result<()> {
+        match tag {
+            Tag::Nil => {
+                self.start_line()?;
+                write!(self.writer, "<nil />")?;
+                self.end_line()
+            },
+            Tag::Boolean(v) => self.write_value("boolean", if *v { "true" } else { "false" }),
+            Tag::UInt8(v) => self.write_value("uint8", &format!("0x{:02x}", v)),
+            Tag::UInt16(v) => self.write_value("uint16", &format!("0x{:04x}", v)),
+            Tag::UInt32(v) => self.write_value("uint32", &format!("0x{:08x}", v)),
+            Tag::UInt64(v) => self.write_value("uint64", &format!("0x{:016x}", v)),
+            Tag::UInt128(v) if self.bluez => self.write_value("uint128", &format!("{:032x}", v)),
+            Tag::UInt128(v) => self.write_value("uint128", &format!("0x{:032x}", v)),
+            Tag::Int8(v) => self.write_value("int8", &v.to_string()),
+            Tag::Int16(v) => self.write_value("int16", &v.to_string()),
+            Tag::Int32(v) => self.write_value("int32", &v.to_string()),
+            Tag::Int64(v) => self.write_value("int64", &v.to_string()),
+            Tag::Int128(v) if self.bluez =>
+                self.write_value("int128", &format!("{:032x}", *v as u128)),
+            Tag::Int128(v) => self.write_value("int128", &v.to_string()),
+            Tag::Uuid(uuid) => self.write_value("uuid", &format_uuid(uuid)),
+            Tag::Text(text) if self.bluez && !is_xml_safe(text) =>
+                self.write_hex_text(text.as_bytes()),
+            Tag::Text(text) => self.write_value("text", &escape(text, self.bluez)),
+            Tag::RawText(bytes) => self.write_hex_text(bytes),
+            Tag::Url(url) => self.write_value("url", &escape(url, self.bluez)),
+            Tag::Sequence(children) => self.write_list("sequence", children),
+            Tag::Alternate(children) => self.write_list("alternate", children),
+            Tag::Record(children) => self.write_list("record", children),
+            Tag::Attribute(id, value) => {
+                self.open(&format!("attribute id=\"0x{:04x}\"", id))?;
+                self.write_tag(value)?;
+                self.close("attribute")
+            },
+        }
+    }
+
+    /// Write a text element with the given bytes, hex-encoded.
+    fn write_hex_text(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.start_line()?;
+        write!(self.writer, "<text encoding=\"hex\" value=\"{}\" />", hex(bytes))?;
+        self.end_line()
+    }
+
+    /// Write an element containing the given children.
+    fn write_list(&mut self, name: &str, children: &[Tag]) -> io::Result<()> {
+        self.open(name)?;
+        for child in children {
+            self.write_tag(child)?;
+        }
+        self.close(name)
+    }
+}
+
+/// Writer streaming a record as an SDP XML document, one attribute at a time.
+pub(crate) struct RecordWriter<W: Write> {
+    xml_writer: XmlWriter<W>,
+}
+
+impl<W: Write> RecordWriter<W> {
+    /// Write the document header and open the record.
+    pub(crate) fn begin(writer: W, pretty: bool) -> io::Result<Self> {
+        let mut xml_writer = XmlWriter { writer, pretty, bluez: false, depth: 0 };
+        write!(xml_writer.writer, "<?xml version=\"1.0\" encoding=\"UTF-8\" ?>")?;
+        xml_writer.end_line()?;
+        xml_writer.open("record")?;
+        Ok(RecordWriter { xml_writer })
+    }
+
+    /// Write an attribute tag.
+    pub(crate) fn write_attribute(&mut self, attribute: &Tag) -> io::Result<()> {
+        self.xml_writer.write_tag(attribute)
+    }
+
+    /// Close the record.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        self.xml_writer.close("record")?;
+        self.xml_writer.writer.flush()
+    }
+}
+
+/// Write the given tag as an SDP XML document, indenting it if `pretty` is set.
+pub fn write_sdp_xml<W: Write>(tag: &Tag, writer: W, pretty: bool) -> io::Result<()> {
+    let mut xml_writer = XmlWriter { writer, pretty, bluez: false, depth: 0 };
+    write!(xml_writer.writer, "<?xml version=\"1.0\" encoding=\"UTF-8\" ?>")?;
+    xml_writer.end_line()?;
+    xml_writer.write_tag(tag)
+}
+
+/// Return the given tag as an SDP XML document, indenting it if `pretty` is set.
+pub fn to_sdp_xml_string(tag: &Tag, pretty: bool) -> String {
+    let mut output = Vec::new();
+    write_sdp_xml(tag, &mut output, pretty)
+        .expect("writing to a Vec cannot fail");
+    String::from_utf8(output)
+        .expect("SDP XML is always valid UTF-8")
+}
+
+/// Return the given tag as a record string in the dialect BlueZ's parser expects, for the
+/// ServiceRecord option of `RegisterProfile`.
+pub fn to_bluez_record_string(tag: &Tag) -> String {
+    let mut output = Vec::new();
+    let mut xml_writer = XmlWriter { writer: &mut output, pretty: false, bluez: true, depth: 0 };
+    write!(xml_writer.writer, "<?xml version=\"1.0\" encoding=\"UTF-8\" ?>")
+        .and_then(|()| xml_writer.write_tag(tag))
+        .expect("writing to a Vec cannot fail");
+    String::from_utf8(output)
+        .expect("SDP XML is always valid UTF-8")
+}
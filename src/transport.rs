@@ -0,0 +1,87 @@
+//! L2CAP channels of HID connections.
+//!
+//! A HID connection is a pair of L2CAP channels: the control channel, which carries handshakes
+//! and GET_REPORT and SET_REPORT requests, and the interrupt channel, which carries input and
+//! output reports. Hosts open the control channel first, and devices reconnecting to their host
+//! do the same. The functions here use the PSMs the configuration advertises, so the channels
+//! always match the record.
+
+use bluer::l2cap::{SocketAddr, Stream, StreamListener};
+use bluer::{Address, AddressType};
+use futures::stream::{self, Stream as AsyncStream};
+use hid_device_id::bluetooth::psm;
+use std::io;
+
+use crate::Configuration;
+
+/// The channels of a HID connection to a host.
+pub struct HidConnection {
+    /// Address of the host.
+    pub address: Address,
+    pub control: Stream,
+    pub interrupt: Stream,
+}
+
+impl Configuration {
+    /// Return the PSM of the control channel, as advertised in the record.
+    pub fn control_psm(&self) -> u16 {
+        self.transport.control_psm.unwrap_or(psm::HID_CONTROL)
+    }
+
+    /// Return the PSM of the interrupt channel, as advertised in the record.
+    pub fn interrupt_psm(&self) -> u16 {
+        self.transport.interrupt_psm.unwrap_or(psm::HID_INTERRUPT)
+    }
+}
+
+/// Listener accepting HID connections from hosts.
+pub struct HidListener {
+    control: StreamListener,
+    interrupt: StreamListener,
+}
+
+impl HidListener {
+    /// Listen on the control and interrupt PSMs of the configuration, on every adapter.
+    ///
+    /// Binding the PSMs below 0x1001 requires `CAP_NET_BIND_SERVICE`. Binding fails if BlueZ
+    /// already listens on the control PSM, as it does for profiles registered with a PSM, such
+    /// as those of `Configuration::to_bluer_profile`.
+    pub async fn bind(config: &Configuration) -> io::Result<Self> {
+        let bind = |psm| {
+            StreamListener::bind(SocketAddr::new(Address::any(), AddressType::BrEdr, psm))
+        };
+        Ok(HidListener {
+            control: bind(config.control_psm()).await?,
+            interrupt: bind(config.interrupt_psm()).await?,
+        })
+    }
+
+    /// Accept the next HID connection: a control channel, then an interrupt channel from the
+    /// same host. Interrupt channels opened by other hosts in between are closed.
+    pub async fn accept(&self) -> io::Result<HidConnection> {
+        let (control, control_address) = self.control.accept().await?;
+        loop {
+            let (interrupt, interrupt_address) = self.interrupt.accept().await?;
+            if interrupt_address.addr == control_address.addr {
+                return Ok(HidConnection { address: control_address.addr, control, interrupt });
+            }
+        }
+    }
+
+    /// Return a stream of the HID connections accepted by the listener.
+    pub fn incoming(self) -> impl AsyncStream<Item = io::Result<HidConnection>> {
+        stream::unfold(self, |listener| async move {
+            let connection = listener.accept().await;
+            Some((connection, listener))
+        })
+    }
+}
+
+/// Connect to a host, opening the control channel and then the interrupt channel on the PSMs of
+/// the configuration, as a device does to reconnect to its host.
+pub async fn connect(config: &Configuration, host: Address) -> io::Result<HidConnection> {
+    let connect = |psm| Stream::connect(SocketAddr::new(host, AddressType::BrEdr, psm));
+    let control = connect(config.control_psm()).await?;
+    let interrupt = connect(config.interrupt_psm()).await?;
+    Ok(HidConnection { address: host, control, interrupt })
+}
@@ -0,0 +1,200 @@
+//! Building configurations from the descriptors of USB HID devices.
+//!
+//! A USB HID interface declares its class, subclass and protocol in its interface descriptor,
+//! its country code in its HID descriptor, and its reports in its report descriptor, which
+//! Bluetooth HID uses unchanged. Porting a USB device to Bluetooth keeps the report descriptor,
+//! and derives the device subclass from the boot protocol or the top-level usages.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::hid::report::{self, ParseError};
+use crate::hid::usage::{consumer, digitizer, generic_desktop, page};
+use crate::hid::{self, DeviceSubclass, Peripheral};
+use crate::{encoding, language, Configuration, ProfileVersion};
+
+/// USB interface class of HID interfaces.
+pub const INTERFACE_CLASS_HID: u8 = 0x03;
+/// USB interface subclass of HID interfaces supporting the boot protocol.
+pub const INTERFACE_SUBCLASS_BOOT: u8 = 0x01;
+
+/// USB interface protocols of boot interfaces.
+pub mod interface_protocol {
+    pub const NONE: u8 = 0x00;
+    pub const KEYBOARD: u8 = 0x01;
+    pub const MOUSE: u8 = 0x02;
+}
+
+/// USB descriptor types.
+pub mod descriptor_type {
+    pub const INTERFACE: u8 = 0x04;
+    pub const HID: u8 = 0x21;
+}
+
+/// Error type for USB descriptors which cannot be imported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UsbImportError {
+    /// The interface has the given class, which is not HID.
+    NotHid(u8),
+    /// The configuration descriptor has no interface with the given number, or no HID
+    /// descriptor follows it.
+    MissingInterface(u8),
+    /// The configuration descriptor ends in the middle of a descriptor, at the given offset.
+    Truncated(usize),
+    /// The report descriptor is malformed.
+    Malformed(ParseError),
+}
+
+impl Display for UsbImportError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::NotHid(class) =>
+                write!(f, "interface class 0x{:02x} is not HID", class),
+            Self::MissingInterface(number) =>
+                write!(f, "no HID interface {} in configuration descriptor", number),
+            Self::Truncated(offset) =>
+                write!(f, "configuration descriptor truncated at offset {}", offset),
+            Self::Malformed(e) =>
+                write!(f, "report descriptor: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for UsbImportError {}
+
+/// The descriptors of a USB HID interface needed to describe it over Bluetooth.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UsbHidInterface {
+    pub interface_class: u8,
+    pub interface_subclass: u8,
+    pub interface_protocol: u8,
+    /// Country code, from the HID descriptor.
+    pub country_code: u8,
+    pub report_descriptor: Vec<u8>,
+}
+
+impl UsbHidInterface {
+    /// Read the interface with the given number from a USB configuration descriptor, as
+    /// returned by GET_DESCRIPTOR with all its interface, endpoint and class descriptors.
+    ///
+    /// The report descriptor is requested separately from USB devices, so it is given here.
+    pub fn from_configuration_descriptor(
+        data: &[u8],
+        interface_number: u8,
+        report_descriptor: Vec<u8>,
+    ) -> Result<Self, UsbImportError> {
+        let mut interface = None;
+        let mut offset = 0;
+        while offset < data.len() {
+            let len = usize::from(data[offset]);
+            let descriptor = data.get(offset..offset + len)
+                .filter(|descriptor| descriptor.len() >= 2)
+                .ok_or(UsbImportError::Truncated(offset))?;
+            match descriptor[1] {
+                descriptor_type::INTERFACE if descriptor.len() >= 8 => {
+                    interface = (descriptor[2] == interface_number)
+                        .then(|| (descriptor[5], descriptor[6], descriptor[7]));
+                },
+                descriptor_type::HID if descriptor.len() >= 5 => {
+                    if let Some((class, subclass, protocol)) = interface {
+                        return Ok(UsbHidInterface {
+                            interface_class: class,
+                            interface_subclass: subclass,
+                            interface_protocol: protocol,
+                            country_code: descriptor[4],
+                            report_descriptor,
+                        });
+                    }
+                },
+                _ => {},
+            }
+            offset += len;
+        }
+        Err(UsbImportError::MissingInterface(interface_number))
+    }
+
+    /// Return true if the interface supports the boot protocol.
+    pub fn is_boot_interface(&self) -> bool {
+        self.interface_subclass == INTERFACE_SUBCLASS_BOOT
+            && matches!(self.interface_protocol,
+                        interface_protocol::KEYBOARD | interface_protocol::MOUSE)
+    }
+
+    /// Return the device subclass of the interface: from its protocol if it is a boot
+    /// interface, and otherwise from the top-level usages of its report descriptor.
+    pub fn device_subclass(&self) -> DeviceSubclass {
+        match (self.interface_subclass, self.interface_protocol) {
+            (INTERFACE_SUBCLASS_BOOT, interface_protocol::KEYBOARD)
+                if infer_subclass(&self.report_descriptor).is_pointing() => DeviceSubclass::COMBO,
+            (INTERFACE_SUBCLASS_BOOT, interface_protocol::KEYBOARD) => DeviceSubclass::KEYBOARD,
+            (INTERFACE_SUBCLASS_BOOT, interface_protocol::MOUSE) => DeviceSubclass::MOUSE,
+            _ => infer_subclass(&self.report_descriptor),
+        }
+    }
+
+    /// Create a configuration for the interface. Boot interfaces are boot devices, and every
+    /// device supports virtual cables and reconnection, as with `Configuration::minimal`.
+    pub fn to_configuration(&self) -> Result<Configuration, UsbImportError> {
+        if self.interface_class != INTERFACE_CLASS_HID {
+            return Err(UsbImportError::NotHid(self.interface_class));
+        }
+        report::parse(&self.report_descriptor).map_err(UsbImportError::Malformed)?;
+        let subclass = self.device_subclass();
+        let hid = hid::Configuration::new(subclass)
+            .with_country_code(self.country_code.into())
+            .with_virtual_cable(true)
+            .with_reconnect_initiate(true)
+            .with_class_descriptor(hid::ClassDescriptor::report(self.report_descriptor.clone()))
+            .with_boot_device(self.is_boot_interface());
+        Ok(Configuration::new(language::ENGLISH, encoding::UTF_8, ProfileVersion::V1_1, hid))
+    }
+}
+
+/// Infer the device subclass of a device from the top-level usages of its report descriptor.
+///
+/// Keyboards, mice and pointers set the keyboard and pointing bits. Otherwise, the first
+/// joystick, gamepad, consumer control or pen found sets the device type; the other collections
+/// of keyboards and pointing devices, such as media keys, are not their device type.
+pub fn infer_subclass(report_descriptor: &[u8]) -> DeviceSubclass {
+    let mut keyboard = false;
+    let mut pointing = false;
+    let mut subclass = DeviceSubclass::default();
+    for usage in report::top_level_usages(report_descriptor) {
+        let device_type = match usage {
+            (page::GENERIC_DESKTOP, generic_desktop::KEYBOARD | generic_desktop::KEYPAD) => {
+                keyboard = true;
+                None
+            },
+            (page::GENERIC_DESKTOP, generic_desktop::MOUSE | generic_desktop::POINTER)
+            | (page::DIGITIZER, digitizer::TOUCH_PAD) => {
+                pointing = true;
+                None
+            },
+            (page::GENERIC_DESKTOP, generic_desktop::JOYSTICK) => Some(DeviceSubclass::JOYSTICK),
+            (page::GENERIC_DESKTOP, generic_desktop::GAMEPAD) => Some(DeviceSubclass::GAMEPAD),
+            (page::CONSUMER, consumer::CONSUMER_CONTROL) => Some(DeviceSubclass::REMOTE_CONTROL),
+            (page::DIGITIZER, digitizer::DIGITIZER | digitizer::PEN) =>
+                Some(DeviceSubclass::DIGITIZER_TABLET),
+            _ => None,
+        };
+        if subclass == DeviceSubclass::default() {
+            subclass = device_type.unwrap_or(subclass);
+        }
+    }
+    if keyboard || pointing {
+        subclass.device_type = hid::DeviceType::Uncategorized;
+    }
+    subclass.peripheral = match (keyboard, pointing) {
+        (false, false) => Peripheral::None,
+        (true, false) => Peripheral::Keyboard,
+        (false, true) => Peripheral::Pointing,
+        (true, true) => Peripheral::Combo,
+    };
+    subclass
+}
+
+impl Configuration {
+    /// Create a configuration for a USB HID interface; see `UsbHidInterface::to_configuration`.
+    pub fn from_usb_interface(interface: &UsbHidInterface) -> Result<Self, UsbImportError> {
+        interface.to_configuration()
+    }
+}
@@ -0,0 +1,444 @@
+//! Checking configurations for mistakes which hosts may reject.
+//!
+//! Validation goes further than the checks made before serializing: it reports every problem at
+//! once, including those which still produce a record, such as malformed report descriptors or
+//! reserved PSMs, so applications can refuse to register a configuration with problems.
+//!
+//! Each check is a named rule, listed by `rules`, and can be skipped per call with
+//! `ValidateOptions::disable`, such as `disable("ssr-consistency")`.
+
+use std::collections::BTreeSet;
+use std::fmt::{self, Display, Formatter};
+
+use crate::{encoding, hid, ClassOfDevice, Configuration, ServiceStrings};
+use crate::attribute::*;
+use crate::hid::report::validate_report_descriptor;
+use crate::hid::DescriptorKind;
+
+/// How serious a violation is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The configuration works with most hosts, but is likely a mistake.
+    Warning,
+    /// The configuration breaks a requirement of the specifications.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem found by validation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// Name of the rule violated, such as "report-descriptor".
+    pub rule: &'static str,
+    pub severity: Severity,
+    /// ID of the attribute with the problem, if it concerns a single attribute.
+    pub attribute: Option<u16>,
+    pub message: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.attribute {
+            Some(attribute) => write!(f, "{} (0x{:04x}): {} [{}]",
+                                      self.severity, attribute, self.message, self.rule),
+            None => write!(f, "{}: {} [{}]", self.severity, self.message, self.rule),
+        }
+    }
+}
+
+/// Violations found by a rule, which gives them their severity.
+struct Findings {
+    violations: Vec<(Option<u16>, String)>,
+}
+
+impl Findings {
+    /// Record a problem with the given attribute.
+    fn push<A: SdpAttribute>(&mut self, message: impl Display) {
+        self.violations.push((Some(A::ATTRIBUTE_ID), message.to_string()));
+    }
+
+    /// Record a problem with the whole configuration.
+    fn push_general(&mut self, message: impl Display) {
+        self.violations.push((None, message.to_string()));
+    }
+}
+
+/// A rule checked by validation.
+struct Rule {
+    name: &'static str,
+    /// Severity of the violations of this rule, before the conformance level applies.
+    severity: Severity,
+    check: fn(&Configuration, &ValidateOptions, &mut Findings),
+}
+
+/// Check that there is a report descriptor, without which hosts cannot use the device.
+fn check_report_descriptor_present(
+    config: &Configuration,
+    _: &ValidateOptions,
+    findings: &mut Findings,
+) {
+    let has_report_descriptor = config.hid.class_descriptors.iter()
+        .any(|descriptor| descriptor.descriptor_type() == hid::descriptor_type::REPORT);
+    if !has_report_descriptor {
+        findings.push::<HidDescriptorList>("no report descriptor");
+    }
+}
+
+/// Check that descriptors fit in the record.
+fn check_descriptor_size(config: &Configuration, _: &ValidateOptions, findings: &mut Findings) {
+    for descriptor in config.hid.class_descriptors.iter().filter(|d| !d.fits_in_record()) {
+        findings.push::<HidDescriptorList>(format!(
+            "descriptor of type 0x{:02x} is too long, {} bytes",
+            descriptor.descriptor_type(), descriptor.len()));
+    }
+}
+
+/// Check for descriptors which are likely mistakes.
+fn check_descriptor_warnings(config: &Configuration, _: &ValidateOptions, findings: &mut Findings) {
+    for warning in config.hid.class_descriptors.iter().flat_map(|d| d.warnings()) {
+        findings.push::<HidDescriptorList>(warning);
+    }
+}
+
+/// Check that report descriptors are well-formed.
+fn check_report_descriptors(config: &Configuration, _: &ValidateOptions, findings: &mut Findings) {
+    for descriptor in &config.hid.class_descriptors {
+        if let DescriptorKind::Report(data) = descriptor.kind() {
+            for violation in validate_report_descriptor(data).err().unwrap_or_default() {
+                findings.push::<HidDescriptorList>(violation);
+            }
+        }
+    }
+}
+
+/// Check that boot devices support virtual cables and reconnection, HID 5.3.4.12.
+fn check_boot_device(config: &Configuration, _: &ValidateOptions, findings: &mut Findings) {
+    if config.hid.boot_device && !config.hid.virtual_cable {
+        findings.push::<HidVirtualCable>("boot device must support virtual cable");
+    }
+    if config.hid.boot_device && !config.hid.reconnect_initiate {
+        findings.push::<HidReconnectInitiate>("boot device must initiate reconnection");
+    }
+}
+
+/// Check that each language appears once, and that its strings have their own attribute IDs in
+/// the range of universal attributes, below the attributes of the HID profile at 0x0200.
+fn check_language_bases(config: &Configuration, _: &ValidateOptions, findings: &mut Findings) {
+    const MIN_BASE: u16 = 0x0100;
+    const MAX_BASE: u16 = 0x01ff - ServiceStrings::PROVIDER_NAME_OFFSET;
+
+    // Each language's strings occupy three IDs from its base.
+    let mut bases = vec![MIN_BASE];
+    let mut languages = vec![config.primary_language.hid_code];
+    for language in &config.additional_language_bases() {
+        if !(MIN_BASE..=MAX_BASE).contains(&language.base) {
+            findings.push::<HidLangBaseAttributeIdList>(format!(
+                "language base 0x{:04x} is outside 0x{:04x} to 0x{:04x}",
+                language.base, MIN_BASE, MAX_BASE));
+        }
+        if languages.contains(&language.language) {
+            findings.push::<HidLangBaseAttributeIdList>(format!(
+                "language 0x{:04x} is listed more than once", language.language));
+        }
+        let overlapping = bases.iter()
+            .find(|base| base.abs_diff(language.base) <= ServiceStrings::PROVIDER_NAME_OFFSET);
+        if let Some(other) = overlapping {
+            findings.push::<HidLangBaseAttributeIdList>(format!(
+                "strings of language bases 0x{:04x} and 0x{:04x} overlap", other, language.base));
+        }
+        bases.push(language.base);
+        languages.push(language.language);
+    }
+}
+
+/// Check that service strings use an encoding hosts know.
+fn check_encoding(config: &Configuration, _: &ValidateOptions, findings: &mut Findings) {
+    if !encoding::is_known(config.encoding) {
+        findings.push::<LanguageBaseAttributeIdList>(format!(
+            "encoding {} is not a known MIBEnum value", config.encoding));
+    }
+}
+
+/// Check that the record fits in one SDP response, since some stacks truncate records rather
+/// than continuing them in further responses.
+fn check_record_size(config: &Configuration, options: &ValidateOptions, findings: &mut Findings) {
+    let size = config.serialized_size_estimate();
+    let mtu = options.mtu.unwrap_or(DEFAULT_MTU);
+    if size > usize::from(mtu) {
+        findings.push_general(format!(
+            "record of about {} bytes exceeds the MTU of {} bytes", size, mtu));
+    }
+}
+
+/// Check that the L2CAP PSMs are valid: odd, with the lowest bit of the upper byte clear.
+fn check_psms(config: &Configuration, _: &ValidateOptions, findings: &mut Findings) {
+    let is_valid = |psm: u16| psm & 0x0101 == 0x0001;
+    if let Some(psm) = config.transport.control_psm.filter(|psm| !is_valid(*psm)) {
+        findings.push::<ProtocolDescriptorList>(
+            format!("control PSM 0x{:04x} is not a valid L2CAP PSM", psm));
+    }
+    if let Some(psm) = config.transport.interrupt_psm.filter(|psm| !is_valid(*psm)) {
+        findings.push::<AdditionalProtocolDescriptorLists>(
+            format!("interrupt PSM 0x{:04x} is not a valid L2CAP PSM", psm));
+    }
+    if config.transport.control_psm.is_some()
+        && config.transport.control_psm == config.transport.interrupt_psm
+    {
+        findings.push::<AdditionalProtocolDescriptorLists>(
+            "control and interrupt channels use the same PSM");
+    }
+}
+
+/// Check that the sniff subrating parameters are in range.
+fn check_ssr_range(config: &Configuration, _: &ValidateOptions, findings: &mut Findings) {
+    if let Some(latency) = config.hid.ssr_host_max_latency.filter(|v| !v.is_valid()) {
+        findings.push::<HidSsrHostMaxLatency>(
+            format!("0x{:04x} slots is out of range", latency.slots()));
+    }
+    if let Some(timeout) = config.hid.ssr_host_min_timeout.filter(|v| !v.is_valid()) {
+        findings.push::<HidSsrHostMinTimeout>(
+            format!("0x{:04x} slots is out of range", timeout.slots()));
+    }
+}
+
+/// Check that the sniff subrating parameters fit each other and the supervision timeout.
+fn check_ssr_consistency(config: &Configuration, _: &ValidateOptions, findings: &mut Findings) {
+    let supervision_timeout = config.hid.supervision_timeout
+        .unwrap_or(hid::defaults::SUPERVISION_TIMEOUT);
+    if let Some(latency) = config.hid.ssr_host_max_latency {
+        if latency.slots() > supervision_timeout.slots() {
+            findings.push::<HidSsrHostMaxLatency>(format!(
+                "maximum latency of {:?} exceeds the supervision timeout of {:?}",
+                latency.as_duration(), supervision_timeout.as_duration()));
+        }
+        if let Some(timeout) = config.hid.ssr_host_min_timeout {
+            if timeout.slots() > latency.slots() {
+                findings.push::<HidSsrHostMinTimeout>(format!(
+                    "minimum timeout of {:?} exceeds the maximum latency of {:?}",
+                    timeout.as_duration(), latency.as_duration()));
+            }
+        }
+    }
+}
+
+/// Check that the Class of Device the caller declared agrees with the device subclass.
+fn check_class_of_device(
+    config: &Configuration,
+    options: &ValidateOptions,
+    findings: &mut Findings,
+) {
+    if let Some(class) = options.class_of_device {
+        match class.device_subclass() {
+            None => findings.push::<HidDeviceSubclass>(format!(
+                "class of device {} is not a peripheral", class)),
+            Some(subclass) if subclass != config.hid.device_subclass => {
+                findings.push::<HidDeviceSubclass>(format!(
+                    "class of device {} gives subclass {:?}, but the record gives {:?}",
+                    class, subclass, config.hid.device_subclass));
+            },
+            Some(_) => {},
+        }
+    }
+}
+
+/// Check the vendor and product IDs.
+fn check_identity(config: &Configuration, _: &ValidateOptions, findings: &mut Findings) {
+    for warning in config.identity_warnings() {
+        findings.push_general(warning);
+    }
+}
+
+/// Every rule, in the order they are checked.
+const RULES: &[Rule] = &[
+    Rule {
+        name: "report-descriptor-required",
+        severity: Severity::Error,
+        check: check_report_descriptor_present,
+    },
+    Rule {
+        name: "descriptor-size",
+        severity: Severity::Error,
+        check: check_descriptor_size,
+    },
+    Rule {
+        name: "descriptor-warnings",
+        severity: Severity::Warning,
+        check: check_descriptor_warnings,
+    },
+    Rule {
+        name: "report-descriptor",
+        severity: Severity::Error,
+        check: check_report_descriptors,
+    },
+    Rule {
+        name: "boot-device",
+        severity: Severity::Error,
+        check: check_boot_device,
+    },
+    Rule {
+        name: "language-bases",
+        severity: Severity::Error,
+        check: check_language_bases,
+    },
+    Rule {
+        name: "encoding",
+        severity: Severity::Warning,
+        check: check_encoding,
+    },
+    Rule {
+        name: "record-size",
+        severity: Severity::Warning,
+        check: check_record_size,
+    },
+    Rule {
+        name: "psm",
+        severity: Severity::Error,
+        check: check_psms,
+    },
+    Rule {
+        name: "ssr-range",
+        severity: Severity::Error,
+        check: check_ssr_range,
+    },
+    Rule {
+        name: "ssr-consistency",
+        severity: Severity::Warning,
+        check: check_ssr_consistency,
+    },
+    Rule {
+        name: "class-of-device",
+        severity: Severity::Warning,
+        check: check_class_of_device,
+    },
+    Rule {
+        name: "identity",
+        severity: Severity::Warning,
+        check: check_identity,
+    },
+];
+
+/// Return the name of every rule, with the severity of its violations before the conformance
+/// level applies: `Severity::Error` for requirements of the specifications, and
+/// `Severity::Warning` for recommendations and common practice.
+pub fn rules() -> impl Iterator<Item = (&'static str, Severity)> {
+    RULES.iter().map(|rule| (rule.name, rule.severity))
+}
+
+/// How strictly validation enforces the rules.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConformanceLevel {
+    /// Treat every violation as an error, including those of recommendations and common
+    /// practice.
+    Strict,
+    /// Treat violations of requirements as errors, and others as warnings.
+    #[default]
+    Standard,
+    /// Treat every violation as a warning, for experimenting with configurations hosts may
+    /// reject.
+    Lenient,
+}
+
+impl ConformanceLevel {
+    /// Return the severity a violation of a rule with the given severity has at this level.
+    pub fn apply(&self, severity: Severity) -> Severity {
+        match self {
+            Self::Strict => Severity::Error,
+            Self::Standard => severity,
+            Self::Lenient => Severity::Warning,
+        }
+    }
+}
+
+/// Default MTU of L2CAP channels, which SDP servers commonly use.
+pub const DEFAULT_MTU: u16 = 672;
+
+/// Options controlling validation.
+#[derive(Clone, Debug, Default)]
+pub struct ValidateOptions {
+    pub level: ConformanceLevel,
+    /// The Class of Device the adapter advertises, checked against the device subclass if
+    /// given.
+    pub class_of_device: Option<ClassOfDevice>,
+    /// MTU of the L2CAP channel of SDP, which the record should fit in. `DEFAULT_MTU` if unset.
+    pub mtu: Option<u16>,
+    /// Names of the rules not to check, as given by `rules`.
+    pub disabled_rules: BTreeSet<String>,
+}
+
+impl ValidateOptions {
+    /// Create options validating at the given conformance level.
+    pub fn with_level(level: ConformanceLevel) -> Self {
+        ValidateOptions { level, ..Default::default() }
+    }
+
+    /// Check the device subclass against the given Class of Device.
+    pub fn with_class_of_device(mut self, class: ClassOfDevice) -> Self {
+        self.class_of_device = Some(class);
+        self
+    }
+
+    /// Check that the record fits in the given MTU.
+    pub fn with_mtu(mut self, mtu: u16) -> Self {
+        self.mtu = Some(mtu);
+        self
+    }
+
+    /// Skip the rule with the given name, as given by `rules`. Names of unknown rules are
+    /// ignored, so that options written for other versions of this crate still apply.
+    pub fn disable(mut self, rule: &str) -> Self {
+        self.disabled_rules.insert(rule.to_string());
+        self
+    }
+
+    /// Check the rule with the given name again, after it was disabled.
+    pub fn enable(mut self, rule: &str) -> Self {
+        self.disabled_rules.remove(rule);
+        self
+    }
+
+    /// Return true if the rule with the given name is checked.
+    pub fn is_enabled(&self, rule: &str) -> bool {
+        !self.disabled_rules.contains(rule)
+    }
+}
+
+impl Configuration {
+    /// Check the configuration against the rules of the HID profile, returning every violation
+    /// found. A configuration with no violations of `Severity::Error` is valid.
+    pub fn validate(&self) -> Vec<Violation> {
+        self.validate_with_options(&ValidateOptions::default())
+    }
+
+    /// Check the configuration using the given options, returning every violation found.
+    pub fn validate_with_options(&self, options: &ValidateOptions) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for rule in RULES.iter().filter(|rule| options.is_enabled(rule.name)) {
+            let mut findings = Findings { violations: Vec::new() };
+            (rule.check)(self, options, &mut findings);
+            let severity = options.level.apply(rule.severity);
+            violations.extend(findings.violations.into_iter().map(|(attribute, message)| {
+                Violation { rule: rule.name, severity, attribute, message }
+            }));
+        }
+        violations
+    }
+
+    /// Return true if validation finds no errors, though it may find warnings.
+    pub fn is_valid(&self) -> bool {
+        self.is_valid_with_options(&ValidateOptions::default())
+    }
+
+    /// Return true if validation using the given options finds no errors.
+    pub fn is_valid_with_options(&self, options: &ValidateOptions) -> bool {
+        self.validate_with_options(options).iter()
+            .all(|violation| violation.severity < Severity::Error)
+    }
+}
@@ -0,0 +1,91 @@
+//! JavaScript bindings, for inspecting records in the browser.
+//!
+//! Build with `--target wasm32-unknown-unknown --features wasm` and run `wasm-bindgen` on the
+//! output. The bindings wrap a configuration read from a record, and expose its listing, its
+//! violations and its record in both encodings. The `bluer`, `profile` and `watch` features
+//! are not available on WebAssembly.
+
+use wasm_bindgen::prelude::*;
+
+use crate::hid::DescriptorKind;
+use crate::{Configuration, ParseOptions, PartialConfiguration, SerializeOptions};
+
+/// A HID service record, read into a configuration.
+#[wasm_bindgen]
+pub struct Record {
+    configuration: Configuration,
+}
+
+impl Record {
+    fn from_partial(partial: PartialConfiguration) -> Result<Record, JsError> {
+        Configuration::try_from(partial)
+            .map(|configuration| Record { configuration })
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+}
+
+#[wasm_bindgen]
+impl Record {
+    /// Read a record from SDP XML, accepting the dialects of common tools.
+    #[wasm_bindgen(js_name = fromSdpXml)]
+    pub fn from_sdp_xml(xml: &str) -> Result<Record, JsError> {
+        let options = ParseOptions::compatible();
+        let partial = PartialConfiguration::from_sdp_xml_with_options(xml.as_bytes(), options)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Record::from_partial(partial)
+    }
+
+    /// Read a record from its binary encoding, as sent in SDP responses.
+    #[wasm_bindgen(js_name = fromSdpBytes)]
+    pub fn from_sdp_bytes(data: &[u8]) -> Result<Record, JsError> {
+        let partial = PartialConfiguration::from_sdp_bytes(data)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Record::from_partial(partial)
+    }
+
+    /// Return the listing of the record, with the items of its report descriptors.
+    pub fn summary(&self) -> String {
+        format!("{:#}", self.configuration)
+    }
+
+    /// Return the rule violations of the record, one message each.
+    pub fn violations(&self) -> Vec<String> {
+        self.configuration.validate().iter()
+            .map(|violation| violation.to_string())
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = isValid)]
+    pub fn is_valid(&self) -> bool {
+        self.configuration.is_valid()
+    }
+
+    /// Return the first report descriptor of the record, if it has one.
+    #[wasm_bindgen(js_name = reportDescriptor)]
+    pub fn report_descriptor(&self) -> Option<Vec<u8>> {
+        self.configuration.hid.class_descriptors.iter()
+            .find_map(|descriptor| match descriptor.kind() {
+                DescriptorKind::Report(data) => Some(data.to_vec()),
+                _ => None,
+            })
+    }
+
+    /// Return the record as SDP XML, indented if `pretty` is set.
+    #[wasm_bindgen(js_name = toSdpXml)]
+    pub fn to_sdp_xml(&self, pretty: bool) -> String {
+        let options = SerializeOptions { pretty, ..SerializeOptions::default() };
+        self.configuration.to_sdp_xml_string_with_options(&options)
+    }
+
+    /// Return the binary encoding of the record.
+    #[wasm_bindgen(js_name = toSdpBytes)]
+    pub fn to_sdp_bytes(&self) -> Vec<u8> {
+        self.configuration.to_sdp_bytes()
+    }
+
+    /// Return the fingerprint of the record, which is equal for records describing the same
+    /// device.
+    pub fn fingerprint(&self) -> u64 {
+        self.configuration.fingerprint()
+    }
+}
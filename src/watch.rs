@@ -0,0 +1,86 @@
+//! Watching a remote device for changes to its HID record.
+//!
+//! BlueZ does not expose remote SDP records over D-Bus, so the record itself is read by a
+//! caller-supplied function. The watcher only decides when to read it: every time the device
+//! finishes resolving its services or its UUIDs change, such as after a firmware update.
+
+use bluer::{Device, DeviceEvent, DeviceProperty};
+use futures::stream::{self, Stream, StreamExt};
+use std::fmt::{self, Display, Formatter};
+use std::future::Future;
+
+use crate::{Configuration, Error, PartialConfiguration};
+
+/// Error type for configuration watchers.
+#[derive(Debug)]
+pub enum WatchError {
+    /// The device's events could not be subscribed to.
+    Bluer(bluer::Error),
+    /// The record could not be read.
+    Fetch(Box<dyn std::error::Error + Send + Sync>),
+    /// The record was read, but is not a valid HID configuration.
+    Parse(Error),
+}
+
+impl Display for WatchError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Bluer(e) => write!(f, "Bluetooth error: {}", e),
+            Self::Fetch(e) => write!(f, "failed to read record: {}", e),
+            Self::Parse(e) => write!(f, "failed to parse record: {}", e),
+        }
+    }
+}
+
+/// Return true if the event may indicate that the device's records have changed.
+fn is_record_change(event: &DeviceEvent) -> bool {
+    matches!(event,
+             DeviceEvent::PropertyChanged(DeviceProperty::ServicesResolved(true))
+             | DeviceEvent::PropertyChanged(DeviceProperty::Uuids(_)))
+}
+
+/// Parse a complete configuration from SDP XML.
+fn parse_configuration(xml: &[u8]) -> Result<Configuration, WatchError> {
+    PartialConfiguration::from_sdp_xml(xml)
+        .and_then(Configuration::try_from)
+        .map_err(WatchError::Parse)
+}
+
+/// Watch the given device, yielding its configuration every time its HID record changes.
+///
+/// `fetch` is called to read the device's HID record as SDP XML whenever the device resolves its
+/// services or changes its UUIDs. Records identical to the last one read are skipped, so the
+/// stream only yields actual changes.
+pub async fn watch_configuration<F, Fut>(
+    device: &Device,
+    fetch: F,
+) -> Result<impl Stream<Item = Result<Configuration, WatchError>>, WatchError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>>,
+{
+    let events = device.events().await.map_err(WatchError::Bluer)?;
+    let state = (Box::pin(events), fetch, None::<Vec<u8>>);
+
+    Ok(stream::unfold(state, |(mut events, mut fetch, mut last)| async move {
+        loop {
+            let event = events.next().await?;
+            if !is_record_change(&event) {
+                continue;
+            }
+            let xml = match fetch().await {
+                Ok(xml) => xml,
+                Err(e) => {
+                    return Some((Err(WatchError::Fetch(e)), (events, fetch, last)));
+                },
+            };
+            // Skip records which have not changed since the last read.
+            if last.as_ref() == Some(&xml) {
+                continue;
+            }
+            let configuration = parse_configuration(&xml);
+            last = Some(xml);
+            return Some((configuration, (events, fetch, last)));
+        }
+    }))
+}
@@ -0,0 +1,133 @@
+//! Registration of configurations with BlueZ over zbus, without bluer.
+//!
+//! `org.bluez.ProfileManager1.RegisterProfile` takes the path of an `org.bluez.Profile1` object
+//! exported by the caller, the profile UUID, and a dictionary of options including the service
+//! record. `register` exports the profile object on the given connection and registers it with
+//! the same options as `Configuration::to_bluer_profile`. BlueZ hands each connection to the
+//! control PSM to the profile object, which passes it on to a caller-supplied function.
+
+use std::collections::HashMap;
+use std::os::fd::OwnedFd;
+
+use ::zbus::zvariant::{self, ObjectPath, OwnedObjectPath, OwnedValue, Value};
+use ::zbus::Connection;
+use hid_device_id::bluetooth::psm;
+use uuid::Uuid;
+
+use crate::ids::ServiceClass;
+use crate::{Configuration, SerializeOptions};
+
+/// Function called with the device and socket of each connection to the control PSM.
+pub type ConnectionHandler = Box<dyn Fn(OwnedObjectPath, OwnedFd) + Send + Sync>;
+
+#[::zbus::proxy(
+    interface = "org.bluez.ProfileManager1",
+    default_service = "org.bluez",
+    default_path = "/org/bluez"
+)]
+trait ProfileManager1 {
+    fn register_profile(
+        &self,
+        profile: &ObjectPath<'_>,
+        uuid: &str,
+        options: HashMap<&str, Value<'_>>,
+    ) -> ::zbus::Result<()>;
+
+    fn unregister_profile(&self, profile: &ObjectPath<'_>) -> ::zbus::Result<()>;
+}
+
+/// The `org.bluez.Profile1` object BlueZ calls back.
+struct ProfileObject {
+    on_connection: ConnectionHandler,
+}
+
+#[::zbus::interface(name = "org.bluez.Profile1")]
+impl ProfileObject {
+    fn release(&self) {}
+
+    fn new_connection(
+        &self,
+        device: ObjectPath<'_>,
+        fd: zvariant::OwnedFd,
+        _properties: HashMap<String, OwnedValue>,
+    ) {
+        (self.on_connection)(device.into(), fd.into());
+    }
+
+    fn request_disconnection(&self, _device: ObjectPath<'_>) {}
+}
+
+/// A profile registered with BlueZ. The record stays published until `unregister` is called.
+pub struct Registration {
+    connection: Connection,
+    path: OwnedObjectPath,
+}
+
+impl Registration {
+    /// Return the path at which the profile object is exported.
+    pub fn path(&self) -> &ObjectPath<'_> {
+        &self.path
+    }
+
+    /// Unregister the profile, withdrawing its record, and remove the profile object.
+    pub async fn unregister(self) -> ::zbus::Result<()> {
+        ProfileManager1Proxy::new(&self.connection).await?
+            .unregister_profile(&self.path).await?;
+        self.connection.object_server().remove::<ProfileObject, _>(&self.path).await?;
+        Ok(())
+    }
+}
+
+impl Configuration {
+    /// Return the options with which the profile of this configuration is registered,
+    /// serializing the record with the given options.
+    pub fn profile_options(
+        &self,
+        options: &SerializeOptions,
+    ) -> HashMap<&'static str, Value<'static>> {
+        let psm = self.transport.control_psm.unwrap_or(psm::HID_CONTROL);
+        let record = self.to_bluez_service_record_with_options(options);
+        let mut profile_options = HashMap::new();
+        if let Some(name) = &self.service_name {
+            profile_options.insert("Name", Value::from(name.to_string()));
+        }
+        profile_options.insert("Role", Value::from("server"));
+        profile_options.insert("PSM", Value::from(psm));
+        profile_options.insert("Version", Value::from(self.version.packed()));
+        profile_options.insert("ServiceRecord", Value::from(record));
+        profile_options
+    }
+}
+
+/// Export a profile object at `path` on a system bus connection and register the profile of a
+/// configuration with BlueZ, publishing its record. Connections are passed to `on_connection`.
+pub async fn register(
+    connection: &Connection,
+    path: ObjectPath<'_>,
+    config: &Configuration,
+    on_connection: ConnectionHandler,
+) -> ::zbus::Result<Registration> {
+    register_with_options(connection, path, config, &SerializeOptions::default(), on_connection)
+        .await
+}
+
+/// Register the profile of a configuration with BlueZ, serializing the record with the given
+/// options; see `register`.
+pub async fn register_with_options(
+    connection: &Connection,
+    path: ObjectPath<'_>,
+    config: &Configuration,
+    options: &SerializeOptions,
+    on_connection: ConnectionHandler,
+) -> ::zbus::Result<Registration> {
+    let path = OwnedObjectPath::from(path);
+    connection.object_server().at(&path, ProfileObject { on_connection }).await?;
+    let uuid = Uuid::from(ServiceClass::Hid).to_string();
+    let result = ProfileManager1Proxy::new(connection).await?
+        .register_profile(&path, &uuid, config.profile_options(options)).await;
+    if let Err(e) = result {
+        connection.object_server().remove::<ProfileObject, _>(&path).await?;
+        return Err(e);
+    }
+    Ok(Registration { connection: connection.clone(), path })
+}